@@ -0,0 +1,19 @@
+// 开启 ffi 特性时用 cbindgen 按 src/ffi.rs 重新生成C头文件 include/chess.h
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir =
+            std::env::var("CARGO_MANIFEST_DIR").expect("cargo 会设置 CARGO_MANIFEST_DIR");
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+            .expect("cbindgen.toml 无效");
+        // 只解析 ffi.rs，头文件中不会出现库里其他的常量
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(format!("{}/src/ffi.rs", crate_dir))
+            .generate()
+            .expect("无法生成C头文件")
+            .write_to_file(format!("{}/include/chess.h", crate_dir));
+    }
+}
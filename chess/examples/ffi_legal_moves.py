@@ -0,0 +1,46 @@
+#!/usr/bin/env python3
+# 通过 C ABI（ffi 特性）在 Python 中使用规则引擎：统计随机对局中每步的合法走法数。
+# 先构建动态库：
+#   cargo rustc --release --lib --features ffi --crate-type cdylib
+# 再运行（库路径可用第一个参数指定，缺省为 target/release/libchess.so）：
+#   python3 examples/ffi_legal_moves.py [target/release/libchess.dylib]
+import ctypes
+import random
+import sys
+
+CHESS_ONGOING = 0
+STATUS_NAMES = {1: "白方胜", 2: "黑方胜", 3: "和棋"}
+
+lib = ctypes.CDLL(sys.argv[1] if len(sys.argv) > 1 else "target/release/libchess.so")
+lib.chess_board_new.restype = ctypes.c_void_p
+lib.chess_board_free.argtypes = [ctypes.c_void_p]
+lib.chess_board_set_fen.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
+for name in ("chess_board_get_fen", "chess_board_legal_moves"):
+    getattr(lib, name).argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_size_t]
+    getattr(lib, name).restype = ctypes.c_ssize_t
+lib.chess_board_push_uci.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
+lib.chess_board_status.argtypes = [ctypes.c_void_p]
+
+
+def read_string(function, board):
+    # 先查询长度，再分配足够的缓冲区
+    needed = function(board, None, 0)
+    buffer = ctypes.create_string_buffer(needed + 1)
+    function(board, buffer, len(buffer))
+    return buffer.value.decode()
+
+
+board = lib.chess_board_new()
+try:
+    counts = []
+    while lib.chess_board_status(board) == CHESS_ONGOING and len(counts) < 200:
+        moves = read_string(lib.chess_board_legal_moves, board).split()
+        counts.append(len(moves))
+        assert lib.chess_board_push_uci(board, random.choice(moves).encode()) == 0
+
+    print("步数:", len(counts))
+    print("平均合法走法数: %.1f" % (sum(counts) / len(counts)))
+    print("结果:", STATUS_NAMES.get(lib.chess_board_status(board), "未结束"))
+    print("最终局面:", read_string(lib.chess_board_get_fen, board))
+finally:
+    lib.chess_board_free(board)
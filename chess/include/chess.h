@@ -0,0 +1,79 @@
+#ifndef CHESS_H
+#define CHESS_H
+
+/* 由 cbindgen 根据 src/ffi.rs 生成，请勿手工修改 */
+
+#include <stddef.h>
+typedef struct ChessBoard ChessBoard;
+
+#define CHESS_OK 0
+
+#define CHESS_ERROR -1
+
+#define CHESS_ONGOING 0
+
+#define CHESS_WHITE_WINS 1
+
+#define CHESS_BLACK_WINS 2
+
+#define CHESS_DRAW 3
+
+/**
+ * 创建初始局面的棋盘；用完必须调用 chess_board_free 释放。
+ */
+ChessBoard *chess_board_new(void);
+
+/**
+ * 释放棋盘。
+ *
+ * # Safety
+ * board 必须是 chess_board_new 返回且尚未释放的指针，或为空指针（忽略）。
+ */
+void chess_board_free(ChessBoard *board);
+
+/**
+ * 把棋盘设为 FEN 描述的局面；FEN 无效时返回 CHESS_ERROR，棋盘不变。
+ *
+ * # Safety
+ * board 必须是有效的棋盘指针，fen 必须是以NUL结尾的字符串。
+ */
+int chess_board_set_fen(ChessBoard *board, const char *fen);
+
+/**
+ * 当前局面的 FEN 写入 buffer，返回其长度。
+ *
+ * # Safety
+ * board 必须是有效的棋盘指针，buffer 至少有 capacity 字节可写（capacity 为0时可为空）。
+ */
+ptrdiff_t chess_board_get_fen(ChessBoard *board,
+                              char *buffer,
+                              size_t capacity);
+
+/**
+ * 全部合法走法的 UCI 记法，以空格分隔写入 buffer，返回其长度（没有合法走法时为0）。
+ *
+ * # Safety
+ * 同 chess_board_get_fen。
+ */
+ptrdiff_t chess_board_legal_moves(ChessBoard *board,
+                                  char *buffer,
+                                  size_t capacity);
+
+/**
+ * 走一步 UCI 记法的走法（如 "e2e4"、"e7e8q"）；不合法时返回 CHESS_ERROR，棋盘不变。
+ *
+ * # Safety
+ * board 必须是有效的棋盘指针，uci 必须是以NUL结尾的字符串。
+ */
+int chess_board_push_uci(ChessBoard *board,
+                         const char *uci);
+
+/**
+ * 对局状态：CHESS_ONGOING、CHESS_WHITE_WINS、CHESS_BLACK_WINS 或 CHESS_DRAW。
+ *
+ * # Safety
+ * board 必须是有效的棋盘指针。
+ */
+int chess_board_status(ChessBoard *board);
+
+#endif  /* CHESS_H */
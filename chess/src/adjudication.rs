@@ -0,0 +1,229 @@
+use crate::chessboard::{Color, Outcome};
+
+// 引擎对局裁决：仅用于双方均为引擎的自动对局，人类参与的对局从不裁决
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdjudicationConfig {
+    // 评分绝对值不超过该值（厘兵）视为均势
+    pub draw_score: i32,
+    // 连续多少步均势后判和
+    pub draw_move_count: usize,
+    // 评分绝对值不低于该值（厘兵）视为胜势
+    pub win_score: i32,
+    // 连续多少步同向胜势后判胜
+    pub win_move_count: usize,
+}
+
+impl Default for AdjudicationConfig {
+    fn default() -> Self {
+        Self {
+            draw_score: 10,
+            draw_move_count: 40,
+            win_score: 1000,
+            win_move_count: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjudication {
+    Draw,
+    Win(Color),
+}
+
+impl Adjudication {
+    // PGN结果标记
+    pub fn result(&self) -> &'static str {
+        match self {
+            Adjudication::Draw => "1/2-1/2",
+            Adjudication::Win(Color::White) => "1-0",
+            Adjudication::Win(Color::Black) => "0-1",
+        }
+    }
+
+    // 记录到棋盘上的结束方式：裁决胜负视为败方认输，裁决和棋视为协议和棋
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            Adjudication::Draw => Outcome::DrawAgreed,
+            Adjudication::Win(winner) => Outcome::Resigned(winner.opposite()),
+        }
+    }
+
+    // 写入PGN的裁决注释
+    pub fn pgn_comment(&self) -> &'static str {
+        match self {
+            Adjudication::Draw => "{Adjudication: draw by agreement of engines}",
+            Adjudication::Win(Color::White) => "{Adjudication: White wins by agreement of engines}",
+            Adjudication::Win(Color::Black) => "{Adjudication: Black wins by agreement of engines}",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Adjudicator {
+    config: AdjudicationConfig,
+    draw_streak: usize,
+    win_streak: usize,
+    win_side: Option<Color>,
+    // 白方、黑方引擎最近一次给出的评分（白方视角）
+    last_scores: [Option<i32>; 2],
+}
+
+impl Adjudicator {
+    // 只有双方都是引擎时才创建裁决器
+    pub fn for_players(
+        config: AdjudicationConfig,
+        white_is_engine: bool,
+        black_is_engine: bool,
+    ) -> Option<Self> {
+        if !(white_is_engine && black_is_engine) {
+            return None;
+        }
+
+        Some(Self {
+            config,
+            draw_streak: 0,
+            win_streak: 0,
+            win_side: None,
+            last_scores: [None, None],
+        })
+    }
+
+    // 引擎走完一步后记下它的评分（白方视角）；双方引擎都评过分后，每步按两者最近的评分裁决
+    pub fn record_move(&mut self, mover: Color, score: i32) -> Option<Adjudication> {
+        let index = match mover {
+            Color::White => 0,
+            Color::Black => 1,
+        };
+        self.last_scores[index] = Some(score);
+        match self.last_scores {
+            [Some(white), Some(black)] => self.record(white, black),
+            _ => None,
+        }
+    }
+
+    // 记录一步后两个引擎的评分（均为白方视角的厘兵值），满足条件时返回裁决
+    pub fn record(
+        &mut self,
+        white_engine_score: i32,
+        black_engine_score: i32,
+    ) -> Option<Adjudication> {
+        let scores = [white_engine_score, black_engine_score];
+
+        if scores
+            .iter()
+            .all(|score| score.abs() <= self.config.draw_score)
+        {
+            self.draw_streak += 1;
+        } else {
+            self.draw_streak = 0;
+        }
+
+        let win_side = if scores.iter().all(|&score| score >= self.config.win_score) {
+            Some(Color::White)
+        } else if scores.iter().all(|&score| score <= -self.config.win_score) {
+            Some(Color::Black)
+        } else {
+            None
+        };
+
+        if win_side.is_some() && win_side == self.win_side {
+            self.win_streak += 1;
+        } else {
+            self.win_streak = usize::from(win_side.is_some());
+        }
+        self.win_side = win_side;
+
+        if let Some(side) = self.win_side {
+            if self.win_streak >= self.config.win_move_count {
+                return Some(Adjudication::Win(side));
+            }
+        }
+
+        if self.draw_streak >= self.config.draw_move_count {
+            return Some(Adjudication::Draw);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chessboard::{Chessboard, GameStatus};
+    use crate::player_config::Players;
+    use crate::{pgn, search};
+
+    fn config() -> AdjudicationConfig {
+        AdjudicationConfig {
+            draw_score: 10,
+            draw_move_count: 3,
+            win_score: 500,
+            win_move_count: 2,
+        }
+    }
+
+    #[test]
+    fn human_games_are_never_adjudicated() {
+        assert!(Adjudicator::for_players(config(), true, false).is_none());
+        assert!(Adjudicator::for_players(config(), false, true).is_none());
+    }
+
+    #[test]
+    fn draw_after_consecutive_level_scores() {
+        let mut adjudicator = Adjudicator::for_players(config(), true, true).unwrap();
+        assert_eq!(adjudicator.record(5, -3), None);
+        assert_eq!(adjudicator.record(0, 0), None);
+        assert_eq!(adjudicator.record(40, 0), None);
+        assert_eq!(adjudicator.record(2, 1), None);
+        assert_eq!(adjudicator.record(-8, 10), None);
+        assert_eq!(adjudicator.record(0, 0), Some(Adjudication::Draw));
+    }
+
+    #[test]
+    fn engine_game_ends_in_adjudicated_win() {
+        // 两个本地搜索引擎对弈，白方多一个后：双方的评分都认定白方胜势，连续两步后判白胜
+        let mut board = Chessboard::from_fen("4k3/pppp4/8/8/8/8/PPPP4/Q3K3 w - - 0 1").unwrap();
+        let mut adjudicator = Adjudicator::for_players(config(), true, true).unwrap();
+        let mut verdict = None;
+        for _ in 0..20 {
+            let mover = board.current_turn();
+            let (mv, score) = search::best_move_with_score(&board, 2).unwrap();
+            board.make_move(&mv).unwrap();
+            let score = match mover {
+                Color::White => score,
+                Color::Black => -score,
+            };
+            verdict = adjudicator.record_move(mover, score);
+            if verdict.is_some() {
+                break;
+            }
+        }
+        let verdict = verdict.expect("应在20步内裁决");
+        assert_eq!(verdict, Adjudication::Win(Color::White));
+        assert!(board.status() == GameStatus::Ongoing);
+        assert!(board.move_history().len() <= 4);
+        board.conclude(verdict.outcome());
+        assert_eq!(pgn::result_tag(&board), "1-0");
+
+        let pgn = pgn::adjudicated_game(&board, &Players::default(), &[], verdict);
+        assert!(pgn.contains("[Result \"1-0\"]"));
+        assert!(pgn.contains("{Adjudication: White wins by agreement of engines} 1-0"));
+    }
+
+    #[test]
+    fn win_requires_same_sign_from_both_engines() {
+        let mut adjudicator = Adjudicator::for_players(config(), true, true).unwrap();
+        assert_eq!(adjudicator.record(800, -800), None);
+        assert_eq!(adjudicator.record(-900, -600), None);
+        assert_eq!(
+            adjudicator.record(-700, -1200),
+            Some(Adjudication::Win(Color::Black))
+        );
+        assert_eq!(
+            Adjudication::Draw.pgn_comment(),
+            "{Adjudication: draw by agreement of engines}"
+        );
+    }
+}
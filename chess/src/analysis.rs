@@ -0,0 +1,403 @@
+use crate::api_client::SiliconFlowClient;
+use crate::chessboard::{Chessboard, GameStatus, Language, Move, Piece};
+use crate::glyphs::PieceGlyphs;
+use crate::input;
+use crate::pgn;
+use crate::player_config::Players;
+use crate::search;
+use crate::search_limits::SearchLimits;
+
+// 终局后的分析棋盘：在终局局面的副本上走子，双方都可以走，
+// 不影响真实对局的走子历史；试过的走法组成一棵变着树，
+// 可以退回、前进、在同一局面的不同尝试之间切换，也可以把分析保存为PGN变着
+
+// 没有云端引擎且未指定 --depth 时本地搜索的深度
+const LOCAL_DEPTH: u8 = 3;
+
+// 变着树的节点；0号为终局局面（根）
+struct Node {
+    board: Chessboard,
+    // 走到这个局面的PGN着法，由同一方连续走棋时前面插入空着 "--"；根节点为空
+    tokens: Vec<String>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // forward 进入的子节点在children中的位置（最近走过或切换到的那个）
+    selected: usize,
+}
+
+pub struct AnalysisSession {
+    nodes: Vec<Node>,
+    current: usize,
+}
+
+impl AnalysisSession {
+    pub fn new(final_position: &Chessboard) -> Self {
+        let mut board = final_position.clone();
+        board.continue_from_here();
+        Self {
+            nodes: vec![Node {
+                board,
+                tokens: Vec::new(),
+                parent: None,
+                children: Vec::new(),
+                selected: 0,
+            }],
+            current: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Chessboard {
+        &self.nodes[self.current].board
+    }
+
+    // 走一步分析走法，起点棋子属于哪一方就由哪一方走；
+    // 这个局面下已经试过同样的走法时进入原来的分支，不重复添加
+    pub fn try_move(&mut self, mv: &Move) -> Result<(), String> {
+        let color = match self.board().get(mv.from) {
+            Some(piece) => piece.color(),
+            None => return Err("起始位置没有棋子".to_string()),
+        };
+
+        let mut board = self.board().clone();
+        let mut tokens = Vec::new();
+        if color != board.current_turn() {
+            tokens.push("--".to_string());
+        }
+        board.play_as(color)?;
+        board.make_move(mv)?;
+        let played = board.move_history().last().expect("刚走过一步");
+        tokens.push(pgn::coordinate_move(played));
+
+        let parent = self.current;
+        let existing = self.nodes[parent]
+            .children
+            .iter()
+            .position(|&child| self.nodes[child].tokens == tokens);
+        let index = match existing {
+            Some(index) => index,
+            None => {
+                self.nodes.push(Node {
+                    board,
+                    tokens,
+                    parent: Some(parent),
+                    children: Vec::new(),
+                    selected: 0,
+                });
+                let child = self.nodes.len() - 1;
+                self.nodes[parent].children.push(child);
+                self.nodes[parent].children.len() - 1
+            }
+        };
+        self.enter(index);
+        Ok(())
+    }
+
+    fn enter(&mut self, index: usize) {
+        let node = &mut self.nodes[self.current];
+        node.selected = index;
+        self.current = node.children[index];
+    }
+
+    // 退回上一步分析走法；已经退回到终局局面时返回false
+    pub fn back(&mut self) -> bool {
+        match self.nodes[self.current].parent {
+            Some(parent) => {
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 沿最近走过的分支前进一步；这个局面之后没有试过走法时返回false
+    pub fn forward(&mut self) -> bool {
+        let node = &self.nodes[self.current];
+        if node.children.is_empty() {
+            return false;
+        }
+        self.enter(node.selected);
+        true
+    }
+
+    // 当前局面下试过的各个走法，按尝试的先后顺序
+    pub fn variations(&self) -> Vec<String> {
+        self.nodes[self.current]
+            .children
+            .iter()
+            .map(|&child| self.nodes[child].tokens.join(" "))
+            .collect()
+    }
+
+    // 进入当前局面下第n个（从1开始）试过的走法
+    pub fn switch(&mut self, n: usize) -> Result<(), String> {
+        let count = self.nodes[self.current].children.len();
+        if n == 0 || n > count {
+            return Err(match count {
+                0 => "这个局面还没有试过走法".to_string(),
+                _ => format!("变着编号应为1到{}", count),
+            });
+        }
+        self.enter(n - 1);
+        Ok(())
+    }
+
+    // 从终局局面到当前局面的分析线PGN着法（含空着）
+    pub fn line(&self) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut index = self.current;
+        while let Some(parent) = self.nodes[index].parent {
+            path.push(index);
+            index = parent;
+        }
+        path.iter()
+            .rev()
+            .flat_map(|&index| self.nodes[index].tokens.iter().cloned())
+            .collect()
+    }
+
+    // 整棵变着树，用于导出全部分支
+    pub fn variation_tree(&self) -> Vec<pgn::Variation> {
+        self.subtree(0)
+    }
+
+    fn subtree(&self, index: usize) -> Vec<pgn::Variation> {
+        self.nodes[index]
+            .children
+            .iter()
+            .map(|&child| pgn::Variation {
+                moves: self.nodes[child].tokens.clone(),
+                continuations: self.subtree(child),
+            })
+            .collect()
+    }
+}
+
+fn print_help() {
+    println!("分析模式: 双方均可走子，每步由引擎评分");
+    println!("  'back' / 'forward' - 退回上一步 / 沿最近走过的分支前进一步");
+    println!("  'variations' - 列出当前局面下试过的走法");
+    println!("  'switch <n>' - 进入第n个试过的走法");
+    println!("  'line' - 显示对局和当前分析线");
+    println!("  'save [文件]' - 把对局连同当前分析线(作为变着)保存为PGN，默认 analysis.pgn");
+    println!("  'save all [文件]' - 同上，但保存全部分支");
+    println!("  'exit' - 结束分析，返回对局总结");
+}
+
+// 交互式分析循环，直到输入 exit；result 是对局的实际结果，保存PGN时写入
+pub async fn run(
+    game: &Chessboard,
+    result: &str,
+    client: Option<&SiliconFlowClient>,
+    limits: &SearchLimits,
+    language: Language,
+    players: &Players,
+    glyphs: &PieceGlyphs,
+) {
+    let mut session = AnalysisSession::new(game);
+    print_help();
+
+    loop {
+        println!("{}", session.board().display(glyphs));
+        println!("\n分析> 请输入走法或命令:");
+
+        let Some(input) = input::read_line() else {
+            break;
+        };
+        let input = input.text.as_str();
+
+        match input {
+            "exit" | "quit" => break,
+            "back" => {
+                if !session.back() {
+                    println!("已经回到终局局面");
+                }
+                continue;
+            }
+            "forward" => {
+                if !session.forward() {
+                    println!("这个局面之后还没有试过走法");
+                }
+                continue;
+            }
+            "variations" => {
+                let variations = session.variations();
+                if variations.is_empty() {
+                    println!("这个局面还没有试过走法");
+                }
+                for (index, variation) in variations.iter().enumerate() {
+                    println!("  {}. {}", index + 1, variation);
+                }
+                continue;
+            }
+            "line" => {
+                // 真实对局不随分析改变，与分析线分开显示
+                println!("对局: 共{}步 (终局)", game.move_history().len());
+                println!("分析线: {}", session.line().join(" "));
+                continue;
+            }
+            "help" => {
+                print_help();
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(n) = input.strip_prefix("switch ") {
+            match n.trim().parse() {
+                Ok(n) => {
+                    if let Err(e) = session.switch(n) {
+                        println!("{}", e);
+                    }
+                }
+                Err(_) => println!("请使用格式: switch 2"),
+            }
+            continue;
+        }
+
+        if input == "save" || input.starts_with("save ") {
+            let path = input["save".len()..].trim();
+            let (all, path) = match path.strip_prefix("all") {
+                Some(rest) if rest.is_empty() || rest.starts_with(' ') => (true, rest.trim()),
+                _ => (false, path),
+            };
+            let path = if path.is_empty() {
+                "analysis.pgn"
+            } else {
+                path
+            };
+            let text = if all {
+                pgn::game_with_variations(
+                    game.move_history(),
+                    &session.variation_tree(),
+                    result,
+                    players,
+                )
+            } else {
+                pgn::game_with_variation(game.move_history(), &session.line(), result, players)
+            };
+            match std::fs::write(path, text) {
+                Ok(_) => println!("已保存到 {}", path),
+                Err(e) => println!("保存失败: {}", e),
+            }
+            continue;
+        }
+
+        let mut mv = match session.board().parse_move(input) {
+            Ok(mv) => mv,
+            Err(e) => {
+                println!("{}，请使用格式: e2 e4", e);
+                continue;
+            }
+        };
+        // 分析模式下升变字母可选，缺省升变为后
+        if session.board().is_promotion(&mv) && mv.promotion.is_none() {
+            let color = session.board().get(mv.from).unwrap().color();
+            mv.promotion = Some(Piece::Queen(color));
+        }
+
+        if let Err(e) = session.try_move(&mv) {
+            println!("移动失败: {}", e);
+            continue;
+        }
+
+        if session.board().status() != GameStatus::Ongoing {
+            println!("{}", session.board().describe_status(language));
+            continue;
+        }
+        // 没有云端引擎时只给出本地搜索推荐的走法
+        let Some(client) = client else {
+            let depth = limits.depth.unwrap_or(LOCAL_DEPTH);
+            if let Some(best_move) = search::best_move(session.board(), depth) {
+                println!("本地搜索推荐 {}", best_move.to_notation());
+            }
+            continue;
+        };
+        match client.analyze(&session.board().to_fen(), limits).await {
+            Ok((best_move, score)) => {
+                println!("评分: {} (引擎推荐 {})", score, best_move.to_notation())
+            }
+            Err(e) => println!("引擎评分失败: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(notation: &str) -> Move {
+        Move::from_notation(notation).unwrap()
+    }
+
+    fn fools_mate() -> Chessboard {
+        let mut game = Chessboard::new();
+        for notation in ["f2 f3", "e7 e5", "g2 g4", "d8 h4"] {
+            game.make_move(&mv(notation)).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn analysis_moves_do_not_touch_the_game() {
+        let game = fools_mate();
+        assert_eq!(
+            game.status(),
+            GameStatus::Checkmate {
+                winner: crate::chessboard::Color::Black
+            }
+        );
+
+        let mut session = AnalysisSession::new(&game);
+        // 白方已被将死，分析时可以替黑方继续走（白方仍被将死，着法带 # 后缀）
+        session.try_move(&mv("b8 c6")).unwrap();
+        session.try_move(&mv("g8 f6")).unwrap();
+        assert_eq!(session.line(), vec!["--", "Nc6#", "--", "Nf6#"]);
+        assert!(session.try_move(&mv("e1 e2")).is_err());
+
+        assert!(session.back());
+        assert_eq!(session.line(), vec!["--", "Nc6#"]);
+        assert!(session.back());
+        assert!(!session.back());
+        assert!(session.board().position_eq(&game));
+        assert_eq!(game.move_history().len(), 4);
+        assert_eq!(pgn::result_tag(&game), "0-1");
+    }
+
+    #[test]
+    fn tries_form_a_tree() {
+        let game = fools_mate();
+        let mut session = AnalysisSession::new(&game);
+        session.try_move(&mv("b8 c6")).unwrap();
+        session.try_move(&mv("g8 f6")).unwrap();
+        session.back();
+        session.back();
+        session.try_move(&mv("d7 d6")).unwrap();
+        session.back();
+        // 再试一次同样的走法不会新增分支
+        session.try_move(&mv("b8 c6")).unwrap();
+        session.back();
+        assert_eq!(session.variations(), vec!["-- Nc6#", "-- d6#"]);
+
+        // forward 沿最近走过的分支，一直到之前试过的 Nf6
+        assert!(session.forward());
+        assert!(session.forward());
+        assert_eq!(session.line(), vec!["--", "Nc6#", "--", "Nf6#"]);
+        assert!(!session.forward());
+
+        while session.back() {}
+        assert!(session.switch(3).is_err());
+        session.switch(2).unwrap();
+        assert_eq!(session.line(), vec!["--", "d6#"]);
+        assert!(session.switch(1).is_err());
+
+        let pgn = pgn::game_with_variations(
+            game.move_history(),
+            &session.variation_tree(),
+            "0-1",
+            &Players::default(),
+        );
+        assert!(pgn
+            .ends_with("2. g4 Qh4# (2... Qh4# 3. -- Nc6# 4. -- Nf6#) (2... Qh4# 3. -- d6#) 0-1\n"));
+        assert_eq!(game.move_history().len(), 4);
+    }
+}
@@ -1,4 +1,6 @@
-use super::Move;
+use crate::chessboard::{Chessboard, Move};
+use crate::credentials::{self, ApiKey};
+use crate::search_limits::SearchLimits;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -16,12 +18,50 @@ struct AiResponse {
 
 pub struct SiliconFlowClient {
     client: Client,
-    api_key: String,
+    api_key: ApiKey,
     base_url: String,
 }
 
+// 按需创建的云端客户端：第一次需要云端引擎时才读取API密钥；
+// 读不到密钥时只提示一次，之后一直返回None，由调用方改用本地搜索
+pub struct CloudEngine {
+    args: Vec<String>,
+    load_key: fn(&[String]) -> Result<ApiKey, String>,
+    // 外层None表示还没有尝试读取密钥
+    client: Option<Option<SiliconFlowClient>>,
+}
+
+impl CloudEngine {
+    pub fn new(args: &[String]) -> Self {
+        Self::with_loader(args, credentials::load_api_key)
+    }
+
+    fn with_loader(args: &[String], load_key: fn(&[String]) -> Result<ApiKey, String>) -> Self {
+        Self {
+            args: args.to_vec(),
+            load_key,
+            client: None,
+        }
+    }
+
+    pub fn client(&mut self) -> Option<&SiliconFlowClient> {
+        if self.client.is_none() {
+            let client = match (self.load_key)(&self.args) {
+                Ok(key) => Some(SiliconFlowClient::new(key)),
+                Err(e) => {
+                    println!("{}", e);
+                    println!("没有API密钥，改用本地搜索");
+                    None
+                }
+            };
+            self.client = Some(client);
+        }
+        self.client.as_ref().and_then(Option::as_ref)
+    }
+}
+
 impl SiliconFlowClient {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(api_key: ApiKey) -> Self {
         Self {
             client: Client::new(),
             api_key,
@@ -29,17 +69,22 @@ impl SiliconFlowClient {
         }
     }
 
-    // 非传统用途：使用棋局分析API进行走法推荐（而非深度分析）
-    pub async fn get_best_move(&self, fen: &str) -> Result<Move, Box<dyn std::error::Error>> {
+    // 非传统用途：使用棋局分析API进行走法推荐（而非深度分析），返回推荐走法及API给出的评分（厘兵）。
+    // 该API只支持搜索深度，limits中的节点数和时间限制会被忽略
+    pub async fn analyze(
+        &self,
+        fen: &str,
+        limits: &SearchLimits,
+    ) -> Result<(Move, i32), Box<dyn std::error::Error + Send + Sync>> {
         let request = AiRequest {
             fen: fen.to_string(),
-            depth: Some(3), // 降低深度以加快响应速度
+            depth: limits.depth, // 较浅的深度也能加快响应速度
         };
 
         let response = self
             .client
             .post(&self.base_url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Authorization", format!("Bearer {}", self.api_key.expose()))
             .json(&request)
             .send()
             .await?;
@@ -49,7 +94,43 @@ impl SiliconFlowClient {
         }
 
         let ai_response: AiResponse = response.json().await?;
-        Move::from_notation(&ai_response.best_move)
-            .ok_or_else(|| "Invalid move format from API".into())
+        // 回复中的走法按请求局面解析，易位的各种写法在这里统一规范；
+        // 局面解析不认识时再按UCI记法（e2e4、e7e8q）读取，合法性留给make_move判断
+        let board = Chessboard::from_fen(fen)?;
+        let best_move = board
+            .parse_move(&ai_response.best_move)
+            .or_else(|e| Move::from_uci(ai_response.best_move.trim()).ok_or(e))
+            .map_err(|e| format!("Invalid move format from API: {}", e))?;
+        Ok((best_move, ai_response.score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static MISSING_KEY_LOADS: AtomicUsize = AtomicUsize::new(0);
+
+    fn missing_key(_: &[String]) -> Result<ApiKey, String> {
+        MISSING_KEY_LOADS.fetch_add(1, Ordering::SeqCst);
+        Err("未找到SiliconFlow API密钥".to_string())
+    }
+
+    #[test]
+    fn key_is_loaded_once_and_only_when_needed() {
+        let mut cloud = CloudEngine::with_loader(&[], missing_key);
+        assert_eq!(MISSING_KEY_LOADS.load(Ordering::SeqCst), 0);
+        assert!(cloud.client().is_none());
+        assert!(cloud.client().is_none());
+        assert_eq!(MISSING_KEY_LOADS.load(Ordering::SeqCst), 1);
+
+        let args = [
+            "chess".to_string(),
+            "--api-key".to_string(),
+            "sk-test".to_string(),
+        ];
+        let mut cloud = CloudEngine::with_loader(&args, credentials::load_api_key);
+        assert!(cloud.client().is_some());
     }
 }
@@ -0,0 +1,204 @@
+use crate::chessboard::{Chessboard, GameStatus};
+use std::time::{Duration, Instant};
+
+// 基准测试子命令：chess bench
+// 固定的工作量，节点数与线程和时间无关，可以在不同提交之间比较；
+// 节点总数同时充当走法生成的正确性冒烟测试（perft数值为公认标准值）
+
+// (名称, 局面, 深度, 标准perft节点数)
+const PERFT_POSITIONS: [(&str, &str, u32, u64); 3] = [
+    (
+        "startpos",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+        4,
+        197_281,
+    ),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+        3,
+        97_862,
+    ),
+    (
+        "endgame",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -",
+        4,
+        43_238,
+    ),
+];
+
+const MAKE_UNDO_CYCLES: u64 = 10_000;
+
+// 终局判定（将死/逼和都要生成全部合法走法）：中局局面，走了20步以上，有一定长度的走子历史
+const STATUS_MOVES: [&str; 24] = [
+    "e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7", "Re1", "b5", "Bb3", "d6",
+    "c3", "O-O", "h3", "Nb8", "d4", "Nbd7", "c4", "c6", "cxb5", "axb5",
+];
+const STATUS_CHECKS: u64 = 2_000;
+
+// 一项基准的结果
+struct BenchResult {
+    name: String,
+    nodes: u64,
+    elapsed: Duration,
+}
+
+impl BenchResult {
+    fn nodes_per_second(&self) -> u64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            (self.nodes as f64 / seconds) as u64
+        } else {
+            0
+        }
+    }
+}
+
+pub fn run() -> Result<(), String> {
+    let mut results = Vec::new();
+
+    for (name, fen, depth, expected) in PERFT_POSITIONS {
+        let board = Chessboard::from_minimal_fen(fen)?;
+        let start = Instant::now();
+        let nodes = board.perft(depth);
+        let elapsed = start.elapsed();
+        if nodes != expected {
+            return Err(format!(
+                "perft {} 深度{} 结果错误: 得到{}，应为{}",
+                name, depth, nodes, expected
+            ));
+        }
+        results.push(BenchResult {
+            name: format!("perft {} d{}", name, depth),
+            nodes,
+            elapsed,
+        });
+    }
+
+    let start = Instant::now();
+    let nodes = make_undo_cycles(MAKE_UNDO_CYCLES);
+    results.push(BenchResult {
+        name: "make/undo".to_string(),
+        nodes,
+        elapsed: start.elapsed(),
+    });
+
+    let start = Instant::now();
+    let nodes = status_checks(STATUS_CHECKS)?;
+    results.push(BenchResult {
+        name: "status (middlegame)".to_string(),
+        nodes,
+        elapsed: start.elapsed(),
+    });
+
+    let mut total_nodes = 0;
+    let mut total_time = Duration::ZERO;
+    for result in &results {
+        println!(
+            "{:<24} {:>10} 节点 {:>8} ms {:>10} nps",
+            result.name,
+            result.nodes,
+            result.elapsed.as_millis(),
+            result.nodes_per_second()
+        );
+        total_nodes += result.nodes;
+        total_time += result.elapsed;
+    }
+    let total = BenchResult {
+        name: "total".to_string(),
+        nodes: total_nodes,
+        elapsed: total_time,
+    };
+    println!("===========================");
+    println!("总耗时 (ms) : {}", total.elapsed.as_millis());
+    println!("节点数      : {}", total.nodes);
+    println!("节点/秒     : {}", total.nodes_per_second());
+    println!("bench {}", total.nodes);
+    Ok(())
+}
+
+// 调试子命令：chess perft <深度> [FEN]，按根走法分别列出节点数（UCI记法，按字母排序）
+pub fn run_perft(args: &[String]) -> Result<(), String> {
+    let usage = "用法: chess perft <深度> [FEN]";
+    let depth: u32 = args
+        .get(2)
+        .and_then(|depth| depth.parse().ok())
+        .filter(|&depth| depth > 0)
+        .ok_or(usage)?;
+    let board = match args.get(3..).filter(|fen| !fen.is_empty()) {
+        Some(fen) => Chessboard::from_fen(&fen.join(" "))
+            .or_else(|_| Chessboard::from_minimal_fen(&fen.join(" ")))?,
+        None => Chessboard::new(),
+    };
+
+    let start = Instant::now();
+    let mut divide: Vec<(String, u64)> = board
+        .perft_divide(depth)
+        .into_iter()
+        .map(|(mv, nodes)| (mv.to_uci(), nodes))
+        .collect();
+    divide.sort();
+    for (mv, nodes) in &divide {
+        println!("{}: {}", mv, nodes);
+    }
+    let total = BenchResult {
+        name: "perft".to_string(),
+        nodes: divide.iter().map(|(_, nodes)| nodes).sum(),
+        elapsed: start.elapsed(),
+    };
+    println!();
+    println!("节点数      : {}", total.nodes);
+    println!("总耗时 (ms) : {}", total.elapsed.as_millis());
+    println!("节点/秒     : {}", total.nodes_per_second());
+    Ok(())
+}
+
+// 固定走法序列上反复走子再悔棋
+fn make_undo_cycles(cycles: u64) -> u64 {
+    let mut board = Chessboard::new();
+    let moves = board.get_all_legal_moves();
+    let mut nodes = 0;
+    for cycle in 0..cycles {
+        if board
+            .make_move(&moves[cycle as usize % moves.len()])
+            .is_ok()
+        {
+            nodes += 1;
+            board.undo_last().expect("刚走过一步");
+        }
+    }
+    nodes
+}
+
+// 在中局局面上反复判定对局状态
+fn status_checks(checks: u64) -> Result<u64, String> {
+    let mut board = Chessboard::new();
+    for san in STATUS_MOVES {
+        let mv = board.parse_move(san)?;
+        board.make_move(&mv)?;
+    }
+    let mut nodes = 0;
+    for _ in 0..checks {
+        if board.status() == GameStatus::Ongoing {
+            nodes += 1;
+        }
+    }
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perft_matches_reference_counts() {
+        // 调试构建较慢，这里用比基准浅一层的标准值
+        let reference = [(3, 8_902), (2, 2_039), (3, 2_812)];
+        for ((name, fen, _, _), (depth, expected)) in PERFT_POSITIONS.iter().zip(reference) {
+            let board = Chessboard::from_minimal_fen(fen).unwrap();
+            assert_eq!(board.perft(depth), expected, "{}", name);
+        }
+        assert_eq!(make_undo_cycles(100), 100);
+        assert_eq!(status_checks(3), Ok(3));
+    }
+}
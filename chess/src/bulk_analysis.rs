@@ -0,0 +1,258 @@
+use crate::api_client::SiliconFlowClient;
+use crate::chessboard::Chessboard;
+use crate::progress::{CancellationToken, Progress, TerminalProgress};
+use crate::search_limits::SearchLimits;
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+// 批量分析子命令：chess analyze-file <文件> [--output 结果.csv] [--jobs N]
+// 每行一个FEN，结果按完成顺序写成CSV；无效行跳过，最后连同行号一起报告
+
+// 单个局面的分析：输入FEN，返回推荐走法（UCI记法）和评分
+type AnalysisFuture = Pin<Box<dyn Future<Output = Result<(String, i32), String>> + Send>>;
+type Analyzer = Arc<dyn Fn(String) -> AnalysisFuture + Send + Sync>;
+
+pub async fn run(
+    args: &[String],
+    client: SiliconFlowClient,
+    limits: SearchLimits,
+) -> Result<(), String> {
+    let input = args
+        .get(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("用法: chess analyze-file <文件> [--output 结果.csv] [--jobs N]")?;
+    let output = flag_value(args, "--output");
+    let jobs = match flag_value(args, "--jobs") {
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|&jobs| jobs > 0)
+            .ok_or("--jobs 需要正整数")?,
+        None => 1,
+    };
+
+    let content =
+        std::fs::read_to_string(input).map_err(|e| format!("无法读取 {}: {}", input, e))?;
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => {
+            Box::new(File::create(path).map_err(|e| format!("无法创建 {}: {}", path, e))?)
+        }
+        None => Box::new(io::stdout()),
+    };
+    writeln!(writer, "fen,bestmove,eval,depth,time_ms").map_err(|e| e.to_string())?;
+
+    let client = Arc::new(client);
+    let analyzer: Analyzer = Arc::new(move |fen: String| {
+        let client = Arc::clone(&client);
+        Box::pin(async move {
+            client
+                .analyze(&fen, &limits)
+                .await
+                .map(|(best_move, eval)| (best_move.to_uci(), eval))
+                .map_err(|e| e.to_string())
+        })
+    });
+
+    let depth = limits
+        .depth
+        .map(|depth| depth.to_string())
+        .unwrap_or_default();
+    let progress = TerminalProgress::new();
+    let cancel = CancellationToken::new();
+    cancel.cancel_on_ctrl_c();
+
+    let mut errors = analyze_positions(
+        &content,
+        jobs,
+        analyzer,
+        &depth,
+        &mut writer,
+        &progress,
+        &cancel,
+    )
+    .await?;
+
+    if cancel.is_cancelled() {
+        eprintln!("已取消，已写出的结果保持不变");
+    }
+    if !errors.is_empty() {
+        errors.sort_by_key(|(line_number, _)| *line_number);
+        eprintln!("以下行未能分析:");
+        for (line_number, e) in errors {
+            eprintln!("  第{}行: {}", line_number, e);
+        }
+    }
+
+    Ok(())
+}
+
+// 并发分析每一行FEN，最多jobs个同时进行；返回无效或失败的行号及原因
+async fn analyze_positions(
+    content: &str,
+    jobs: usize,
+    analyzer: Analyzer,
+    depth: &str,
+    writer: &mut dyn Write,
+    progress: &dyn Progress,
+    cancel: &CancellationToken,
+) -> Result<Vec<(usize, String)>, String> {
+    let total = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count();
+    progress.start(total as u64);
+
+    let mut errors = Vec::new();
+    let mut tasks = JoinSet::new();
+    let mut lines = content.lines().enumerate();
+
+    loop {
+        if cancel.is_cancelled() {
+            tasks.abort_all();
+            break;
+        }
+
+        // 保持最多jobs个分析任务同时进行
+        while tasks.len() < jobs {
+            let Some((index, line)) = lines.next() else {
+                break;
+            };
+            let fen = line.trim().to_string();
+            if fen.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = fen.split_whitespace().take(4).collect();
+            if let Err(e) = Chessboard::from_minimal_fen(&fields.join(" ")) {
+                errors.push((index + 1, e.to_string()));
+                progress.advance(1);
+                continue;
+            }
+
+            let analyze = Arc::clone(&analyzer);
+            tasks.spawn(async move {
+                let start = Instant::now();
+                let result = analyze(fen.clone()).await;
+                (index + 1, fen, result, start.elapsed())
+            });
+        }
+
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let (line_number, fen, result, elapsed) = joined.map_err(|e| e.to_string())?;
+        match result {
+            Ok((best_move, eval)) => {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{}",
+                    fen,
+                    best_move,
+                    eval,
+                    depth,
+                    elapsed.as_millis()
+                )
+                .map_err(|e| e.to_string())?;
+                writer.flush().map_err(|e| e.to_string())?;
+            }
+            Err(e) => errors.push((line_number, format!("分析失败: {}", e))),
+        }
+        progress.advance(1);
+    }
+
+    progress.finish();
+    Ok(errors)
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // 记录每次进度更新后的累计值
+    #[derive(Default)]
+    struct RecordingProgress {
+        total: Mutex<u64>,
+        positions: Mutex<Vec<u64>>,
+    }
+
+    impl Progress for RecordingProgress {
+        fn start(&self, total: u64) {
+            *self.total.lock().unwrap() = total;
+        }
+
+        fn advance(&self, delta: u64) {
+            let mut positions = self.positions.lock().unwrap();
+            let last = positions.last().copied().unwrap_or(0);
+            positions.push(last + delta);
+        }
+
+        fn finish(&self) {}
+    }
+
+    fn fake_analyzer() -> Analyzer {
+        Arc::new(|_fen: String| Box::pin(async { Ok(("e2e4".to_string(), 20)) }))
+    }
+
+    const POSITIONS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n\
+                             not a fen\n\
+                             \n\
+                             4k3/8/8/8/8/8/8/4K3 b - - 0 1\n";
+
+    #[tokio::test]
+    async fn progress_is_reported_monotonically() {
+        let progress = RecordingProgress::default();
+        let mut output = Vec::new();
+        let errors = analyze_positions(
+            POSITIONS,
+            2,
+            fake_analyzer(),
+            "3",
+            &mut output,
+            &progress,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        let positions = progress.positions.lock().unwrap();
+        assert_eq!(*progress.total.lock().unwrap(), 3);
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(positions.last(), Some(&3));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 2);
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn cancelled_run_stops_early() {
+        let progress = RecordingProgress::default();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let mut output = Vec::new();
+        analyze_positions(
+            POSITIONS,
+            1,
+            fake_analyzer(),
+            "3",
+            &mut output,
+            &progress,
+            &cancel,
+        )
+        .await
+        .unwrap();
+
+        assert!(output.is_empty());
+        assert!(progress.positions.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+use super::{Color, Piece, Position, Square};
+
+// 位棋盘：每种颜色、每种棋子一个u64，第i位对应 Square 编号i（a1=0 ... h8=63）。
+// 与 Chessboard::board 同步维护（见 Chessboard::set_square），目前只用于快速判断格子是否被攻击；
+// 马、王、兵的攻击格预先算好，车、象、后沿8个方向的射线表求出第一个阻挡棋子
+
+// 8个方向（列增量, 横排增量）：前4个沿编号增大的方向，后4个沿编号减小的方向；
+// 偶数下标是直线方向（车），奇数下标是斜线方向（象）
+const DIRECTIONS: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (-1, 1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (1, -1),
+];
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+// 从每个格子按给定增量走一步（或沿方向走到底）能到达的格子
+const fn offsets_table(offsets: &[(i32, i32)], sliding: bool) -> [u64; 64] {
+    let mut table = [0u64; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < offsets.len() {
+            let (df, dr) = offsets[i];
+            let mut file = (square % 8) as i32 + df;
+            let mut rank = (square / 8) as i32 + dr;
+            while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[square] |= 1 << (rank * 8 + file);
+                if !sliding {
+                    break;
+                }
+                file += df;
+                rank += dr;
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+const KNIGHT_ATTACKS: [u64; 64] = offsets_table(&KNIGHT_OFFSETS, false);
+const KING_ATTACKS: [u64; 64] = offsets_table(&DIRECTIONS, false);
+// 该颜色的兵在某格时攻击的格子
+const PAWN_ATTACKS: [[u64; 64]; 2] = [
+    offsets_table(&[(-1, 1), (1, 1)], false),
+    offsets_table(&[(-1, -1), (1, -1)], false),
+];
+const RAYS: [[u64; 64]; 8] = {
+    let mut rays = [[0u64; 64]; 8];
+    let mut dir = 0;
+    while dir < 8 {
+        rays[dir] = offsets_table(&[DIRECTIONS[dir]], true);
+        dir += 1;
+    }
+    rays
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Bitboards {
+    pieces: [u64; 12],
+}
+
+impl Bitboards {
+    pub(super) fn from_board(board: &[[Option<Piece>; 8]; 8]) -> Self {
+        let mut bitboards = Bitboards::default();
+        for (row, squares) in board.iter().enumerate() {
+            for (col, square) in squares.iter().enumerate() {
+                if let Some(piece) = square {
+                    bitboards.toggle(Position { row, col }, *piece);
+                }
+            }
+        }
+        bitboards
+    }
+
+    // 放上或拿走一个棋子（异或，同一格同一棋子调用两次即还原）
+    pub(super) fn toggle(&mut self, pos: Position, piece: Piece) {
+        self.pieces[Self::slot(piece)] ^= 1 << Square::from(pos).index();
+    }
+
+    // 棋子在 pieces 中的下标：颜色 * 6 + 棋子种类，不区分"已移动"标记
+    fn slot(piece: Piece) -> usize {
+        let kind = match piece {
+            Piece::Pawn(_, _) => 0,
+            Piece::Knight(_) => 1,
+            Piece::Bishop(_) => 2,
+            Piece::Rook(_, _) => 3,
+            Piece::Queen(_) => 4,
+            Piece::King(_, _) => 5,
+        };
+        Self::color_index(piece.color()) * 6 + kind
+    }
+
+    fn color_index(color: Color) -> usize {
+        match color {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
+
+    fn occupied(&self) -> u64 {
+        self.pieces.iter().fold(0, |all, bits| all | bits)
+    }
+
+    // 沿第dir个方向的攻击格，到第一个棋子（含）为止
+    fn ray_attacks(dir: usize, square: usize, occupied: u64) -> u64 {
+        let ray = RAYS[dir][square];
+        let blockers = ray & occupied;
+        if blockers == 0 {
+            return ray;
+        }
+        let first = if dir < 4 {
+            blockers.trailing_zeros()
+        } else {
+            63 - blockers.leading_zeros()
+        };
+        ray & !RAYS[dir][first as usize]
+    }
+
+    pub(super) fn is_attacked(&self, pos: Position, by_color: Color) -> bool {
+        let square = Square::from(pos).index();
+        let base = Self::color_index(by_color) * 6;
+        let [pawns, knights, bishops, rooks, queens, king] =
+            std::array::from_fn(|kind| self.pieces[base + kind]);
+
+        // 被by_color的兵攻击，等价于对方颜色的兵站在这里能攻击到那个兵
+        let defender = Self::color_index(by_color.opposite());
+        if PAWN_ATTACKS[defender][square] & pawns != 0
+            || KNIGHT_ATTACKS[square] & knights != 0
+            || KING_ATTACKS[square] & king != 0
+        {
+            return true;
+        }
+
+        let occupied = self.occupied();
+        (0..8).any(|dir| {
+            let sliders = if dir % 2 == 0 { rooks } else { bishops } | queens;
+            Self::ray_attacks(dir, square, occupied) & sliders != 0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Chessboard, Move};
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // 原先逐格扫描的实现，作为对照
+    fn scan_attacked(board: &Chessboard, pos: Position, by_color: Color) -> bool {
+        let at = |dr: i32, dc: i32| {
+            Position::new(
+                (pos.row as i32 + dr) as usize,
+                (pos.col as i32 + dc) as usize,
+            )
+            .and_then(|square| board.get(square))
+            .filter(|piece| piece.color() == by_color)
+        };
+        let pawn_direction = match by_color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        if KNIGHT_OFFSETS
+            .iter()
+            .any(|&(dr, dc)| matches!(at(dr, dc), Some(Piece::Knight(_))))
+            || [-1, 1]
+                .iter()
+                .any(|&dc| matches!(at(pawn_direction, dc), Some(Piece::Pawn(_, _))))
+            || DIRECTIONS
+                .iter()
+                .any(|&(dr, dc)| matches!(at(dr, dc), Some(Piece::King(_, _))))
+        {
+            return true;
+        }
+        DIRECTIONS.iter().any(|&(dr, dc)| {
+            let mut distance = 1;
+            loop {
+                let (row, col) = (
+                    pos.row as i32 + dr * distance,
+                    pos.col as i32 + dc * distance,
+                );
+                if !(0..8).contains(&row) || !(0..8).contains(&col) {
+                    return false;
+                }
+                if let Some(piece) = board.get(Position::new(row as usize, col as usize).unwrap()) {
+                    return piece.color() == by_color
+                        && match piece {
+                            Piece::Queen(_) => true,
+                            Piece::Rook(_, _) => dr == 0 || dc == 0,
+                            Piece::Bishop(_) => dr != 0 && dc != 0,
+                            _ => false,
+                        };
+                }
+                distance += 1;
+            }
+        })
+    }
+
+    fn assert_agrees(board: &Chessboard) {
+        assert_eq!(board.bitboards, Bitboards::from_board(&board.board));
+        for square in Square::ALL {
+            let pos = Position::from(square);
+            for color in [Color::White, Color::Black] {
+                assert_eq!(
+                    board.is_square_attacked(pos, color),
+                    scan_attacked(board, pos, color),
+                    "{} 被{}攻击, FEN: {}",
+                    pos.to_notation(),
+                    color,
+                    board.to_fen()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn attack_tables() {
+        let d4 = Square::from_notation("d4").unwrap().index();
+        let a1 = Square::from_notation("a1").unwrap().index();
+        assert_eq!(KNIGHT_ATTACKS[d4].count_ones(), 8);
+        assert_eq!(KNIGHT_ATTACKS[a1].count_ones(), 2);
+        assert_eq!(KING_ATTACKS[a1].count_ones(), 3);
+        assert_eq!(RAYS[0][a1].count_ones(), 7);
+        assert_eq!(
+            PAWN_ATTACKS[0][a1],
+            1 << Square::from_notation("b2").unwrap().index()
+        );
+    }
+
+    // 随机对局中每一步后逐格比较位棋盘实现和逐格扫描的结果，并检查悔棋后位棋盘复原
+    #[test]
+    fn bitboard_attacks_match_scan_in_random_games() {
+        let mut rng = StdRng::seed_from_u64(268);
+        for _ in 0..40 {
+            let mut board = Chessboard::new();
+            assert_agrees(&board);
+            for _ in 0..120 {
+                let moves: Vec<Move> = board.get_all_legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                let mv = &moves[rng.random_range(0..moves.len())];
+                board.make_move(mv).unwrap();
+                assert_agrees(&board);
+            }
+            while board.undo_last().is_ok() {}
+            assert_eq!(board.bitboards, Chessboard::new().bitboards);
+        }
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ] {
+            assert_agrees(&Chessboard::from_fen(fen).unwrap());
+        }
+    }
+}
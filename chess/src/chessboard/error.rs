@@ -0,0 +1,91 @@
+use super::{Color, Position};
+use std::fmt;
+
+// make_move 拒绝走法的原因；Display 给出终端显示的中文说明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    // 已分出胜负或和棋（分析模式除外）
+    GameOver,
+    NoPieceAtSource,
+    // 起点是对方的棋子
+    WrongColor,
+    // 这枚棋子按规则走不到终点
+    IllegalMove,
+    // 棋子本身能走到终点，但走后己方王处于被将军状态
+    WouldLeaveKingInCheck,
+    // 升变棋子不合规则，附带具体说明
+    InvalidPromotion(String),
+    // 棋盘内部状态自相矛盾（规则实现有误或局面被破坏），见 check_consistency
+    InternalInconsistency(String),
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::GameOver => write!(f, "对局已结束"),
+            MoveError::NoPieceAtSource => write!(f, "起始位置没有棋子"),
+            MoveError::WrongColor => write!(f, "不能移动对方的棋子"),
+            MoveError::IllegalMove => write!(f, "非法的移动"),
+            MoveError::WouldLeaveKingInCheck => write!(f, "非法的移动: 走后己方王被将军"),
+            MoveError::InvalidPromotion(reason) => write!(f, "{}", reason),
+            MoveError::InternalInconsistency(detail) => write!(f, "内部状态不一致: {}", detail),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+// 解析、回放等仍以字符串报错的路径可以直接用 ? 传递
+impl From<MoveError> for String {
+    fn from(error: MoveError) -> String {
+        error.to_string()
+    }
+}
+
+// from_fen / from_minimal_fen 拒绝FEN的原因；rank为横排号（1-8）
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError {
+    // 字段数不对（完整FEN为6个，精简FEN为4个）
+    WrongFieldCount { expected: usize, found: usize },
+    // 棋盘布局不是8行
+    WrongRankCount(usize),
+    // 某一横排的格子数不是8
+    BadRankLength { rank: usize },
+    InvalidPieceChar(char),
+    InvalidSideToMove(String),
+    InvalidCastlingField(String),
+    // 格式不对，或与走棋方不符（白方走棋时只能在第6横排，黑方在第3横排）
+    InvalidEnPassantSquare(String),
+    InvalidMoveCounter(String),
+    MissingKing(Color),
+    TooManyKings(Color),
+    PawnOnBackRank(Position),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount { expected, found } => {
+                write!(f, "FEN需要{}个字段，实际为{}个", expected, found)
+            }
+            FenError::WrongRankCount(found) => write!(f, "棋盘布局需要8行，实际为{}行", found),
+            FenError::BadRankLength { rank } => write!(f, "第{}横排的格子数不是8", rank),
+            FenError::InvalidPieceChar(c) => write!(f, "无法识别的棋子字符: {}", c),
+            FenError::InvalidSideToMove(field) => write!(f, "无法识别的回合字段: {}", field),
+            FenError::InvalidCastlingField(field) => write!(f, "无法识别的易位权字段: {}", field),
+            FenError::InvalidEnPassantSquare(field) => write!(f, "无效的过路兵格: {}", field),
+            FenError::InvalidMoveCounter(field) => write!(f, "无法识别的回合计数: {}", field),
+            FenError::MissingKing(color) => write!(f, "{}没有王", color),
+            FenError::TooManyKings(color) => write!(f, "{}有不止一个王", color),
+            FenError::PawnOnBackRank(pos) => write!(f, "底线上不能有兵: {}", pos.to_notation()),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl From<FenError> for String {
+    fn from(error: FenError) -> String {
+        error.to_string()
+    }
+}
@@ -0,0 +1,136 @@
+use super::{Chessboard, Color, Piece};
+
+// 静态局面评估：子力 + 位置分（piece-square table），单位厘兵，白方为正
+// 位置表按白方视角书写，第一行是第8横排（与棋盘的row一致），黑方使用上下翻转后的同一张表
+// 数值取自常见的简化评估函数：马在边上扣分、兵越前进分越高、中局王躲在易位后的位置
+
+type Table = [[i32; 8]; 8];
+
+const PAWN_TABLE: Table = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [50, 50, 50, 50, 50, 50, 50, 50],
+    [10, 10, 20, 30, 30, 20, 10, 10],
+    [5, 5, 10, 25, 25, 10, 5, 5],
+    [0, 0, 0, 20, 20, 0, 0, 0],
+    [5, -5, -10, 0, 0, -10, -5, 5],
+    [5, 10, 10, -20, -20, 10, 10, 5],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+];
+
+const KNIGHT_TABLE: Table = [
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+    [-40, -20, 0, 0, 0, 0, -20, -40],
+    [-30, 0, 10, 15, 15, 10, 0, -30],
+    [-30, 5, 15, 20, 20, 15, 5, -30],
+    [-30, 0, 15, 20, 20, 15, 0, -30],
+    [-30, 5, 10, 15, 15, 10, 5, -30],
+    [-40, -20, 0, 5, 5, 0, -20, -40],
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+];
+
+const BISHOP_TABLE: Table = [
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-10, 0, 5, 10, 10, 5, 0, -10],
+    [-10, 5, 5, 10, 10, 5, 5, -10],
+    [-10, 0, 10, 10, 10, 10, 0, -10],
+    [-10, 10, 10, 10, 10, 10, 10, -10],
+    [-10, 5, 0, 0, 0, 0, 5, -10],
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+];
+
+const ROOK_TABLE: Table = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [5, 10, 10, 10, 10, 10, 10, 5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [0, 0, 0, 5, 5, 0, 0, 0],
+];
+
+const QUEEN_TABLE: Table = [
+    [-20, -10, -10, -5, -5, -10, -10, -20],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-5, 0, 5, 5, 5, 5, 0, -5],
+    [0, 0, 5, 5, 5, 5, 0, -5],
+    [-10, 5, 5, 5, 5, 5, 0, -10],
+    [-10, 0, 5, 0, 0, 0, 0, -10],
+    [-20, -10, -10, -5, -5, -10, -10, -20],
+];
+
+const KING_TABLE: Table = [
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-20, -30, -30, -40, -40, -30, -30, -20],
+    [-10, -20, -20, -20, -20, -20, -20, -10],
+    [20, 20, 0, 0, 0, 0, 20, 20],
+    [20, 30, 10, 0, 0, 10, 30, 20],
+];
+
+fn table(piece: Piece) -> &'static Table {
+    match piece {
+        Piece::Pawn(_, _) => &PAWN_TABLE,
+        Piece::Knight(_) => &KNIGHT_TABLE,
+        Piece::Bishop(_) => &BISHOP_TABLE,
+        Piece::Rook(_, _) => &ROOK_TABLE,
+        Piece::Queen(_) => &QUEEN_TABLE,
+        Piece::King(_, _) => &KING_TABLE,
+    }
+}
+
+impl Chessboard {
+    // 子力加位置分，白方为正；王只计位置分
+    pub fn evaluate(&self) -> i32 {
+        let mut score = 0;
+        for row in 0..8 {
+            for col in 0..8 {
+                let Some(piece) = self.board[row][col] else {
+                    continue;
+                };
+                let material = match piece {
+                    Piece::King(_, _) => 0,
+                    _ => piece.value(),
+                };
+                match piece.color() {
+                    Color::White => score += material + table(piece)[row][col],
+                    Color::Black => score -= material + table(piece)[7 - row][col],
+                }
+            }
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(fen: &str) -> i32 {
+        Chessboard::from_minimal_fen(fen).unwrap().evaluate()
+    }
+
+    #[test]
+    fn start_position_is_balanced() {
+        assert_eq!(Chessboard::new().evaluate(), 0);
+        // 上下翻转并交换颜色的局面分值相反
+        assert_eq!(
+            eval("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -"),
+            -eval("r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq -")
+        );
+    }
+
+    #[test]
+    fn placement_matters() {
+        // 马在中心比在边上好，兵越往前越好，中局王易位后比留在中间安全
+        assert!(eval("4k3/8/8/8/3N4/8/8/4K3 w - -") > eval("4k3/8/8/8/N7/8/8/4K3 w - -"));
+        assert!(eval("4k3/8/4P3/8/8/8/8/4K3 w - -") > eval("4k3/8/8/8/8/8/4P3/4K3 w - -"));
+        assert!(eval("4k3/8/8/8/8/8/8/6K1 w - -") > eval("4k3/8/8/8/8/8/8/4K3 w - -"));
+        // 子力仍是主要部分
+        assert!(eval("4k3/8/8/8/N7/8/8/4K3 w - -") > 250);
+    }
+}
@@ -0,0 +1,276 @@
+use super::{Bitboards, CastlingRights, Chessboard, Color, FenError, Piece, Position};
+
+impl Chessboard {
+    // 转换为FEN字符串
+    pub fn to_fen(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.minimal_fen(),
+            self.halfmove_clock(),
+            self.fullmove_number()
+        )
+    }
+
+    // 精简FEN：只含前四个字段（棋子布局、回合、易位权、过路兵），可作为局面键用于缓存
+    pub fn minimal_fen(&self) -> String {
+        let mut fen = String::new();
+
+        // 棋盘布局
+        for row in 0..8 {
+            let mut empty = 0;
+            for col in 0..8 {
+                match self.board[row][col] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(match piece {
+                            Piece::King(Color::White, _) => 'K',
+                            Piece::Queen(Color::White) => 'Q',
+                            Piece::Rook(Color::White, _) => 'R',
+                            Piece::Bishop(Color::White) => 'B',
+                            Piece::Knight(Color::White) => 'N',
+                            Piece::Pawn(Color::White, _) => 'P',
+                            Piece::King(Color::Black, _) => 'k',
+                            Piece::Queen(Color::Black) => 'q',
+                            Piece::Rook(Color::Black, _) => 'r',
+                            Piece::Bishop(Color::Black) => 'b',
+                            Piece::Knight(Color::Black) => 'n',
+                            Piece::Pawn(Color::Black, _) => 'p',
+                        });
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if row < 7 {
+                fen.push('/');
+            }
+        }
+
+        // 当前回合
+        fen.push(' ');
+        fen.push(if self.side_to_move() == Color::White {
+            'w'
+        } else {
+            'b'
+        });
+
+        // 王车易位权限
+        fen.push(' ');
+        let rights = self.castling_rights();
+        let mut castling = String::new();
+        if rights.white_kingside {
+            castling.push('K');
+        }
+        if rights.white_queenside {
+            castling.push('Q');
+        }
+        if rights.black_kingside {
+            castling.push('k');
+        }
+        if rights.black_queenside {
+            castling.push('q');
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        fen.push_str(&castling);
+
+        // 吃过路兵目标
+        fen.push(' ');
+        fen.push_str(&match self.en_passant_target() {
+            Some(pos) => pos.to_notation(),
+            None => "-".to_string(),
+        });
+
+        fen
+    }
+
+    // 从完整FEN（六个字段）构造棋盘，是to_fen的逆操作
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::WrongFieldCount {
+                expected: 6,
+                found: fields.len(),
+            });
+        }
+        let halfmove_clock = fields[4]
+            .parse::<u32>()
+            .map_err(|_| FenError::InvalidMoveCounter(fields[4].to_string()))?;
+        let fullmove_number = fields[5]
+            .parse::<u32>()
+            .ok()
+            .filter(|&fullmove| fullmove >= 1)
+            .ok_or_else(|| FenError::InvalidMoveCounter(fields[5].to_string()))?;
+        let mut chessboard = Self::from_minimal_fen(&fields[..4].join(" "))?;
+        chessboard.halfmove_clock = halfmove_clock;
+        chessboard.fullmove_number = fullmove_number;
+        Ok(chessboard)
+    }
+
+    // 从精简FEN（前四个字段）构造棋盘，是minimal_fen的逆操作；回合计数取 "0 1"
+    // 除格式外还检查局面本身：双方各一个王、底线上没有兵、过路兵格与走棋方相符
+    pub fn from_minimal_fen(fields: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+        if fields.len() != 4 {
+            return Err(FenError::WrongFieldCount {
+                expected: 4,
+                found: fields.len(),
+            });
+        }
+
+        // 棋盘布局
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+        let mut board = [[None; 8]; 8];
+        for (row, rank) in ranks.iter().enumerate() {
+            let mut col = 0;
+            for c in rank.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    col += empty as usize;
+                    continue;
+                }
+                if col >= 8 {
+                    return Err(FenError::BadRankLength { rank: 8 - row });
+                }
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                board[row][col] = Some(match c.to_ascii_lowercase() {
+                    'k' => Piece::King(color, true),
+                    'q' => Piece::Queen(color),
+                    'r' => Piece::Rook(color, true),
+                    'b' => Piece::Bishop(color),
+                    'n' => Piece::Knight(color),
+                    'p' => {
+                        let start_row = if color == Color::White { 6 } else { 1 };
+                        Piece::Pawn(color, row != start_row)
+                    }
+                    _ => return Err(FenError::InvalidPieceChar(c)),
+                });
+                col += 1;
+            }
+            if col != 8 {
+                return Err(FenError::BadRankLength { rank: 8 - row });
+            }
+        }
+
+        for color in [Color::White, Color::Black] {
+            let kings = board
+                .iter()
+                .flatten()
+                .filter(|square| matches!(square, Some(Piece::King(c, _)) if *c == color))
+                .count();
+            match kings {
+                0 => return Err(FenError::MissingKing(color)),
+                1 => {}
+                _ => return Err(FenError::TooManyKings(color)),
+            }
+        }
+        for row in [0, 7] {
+            if let Some(col) =
+                (0..8).find(|&col| matches!(board[row][col], Some(Piece::Pawn(_, _))))
+            {
+                return Err(FenError::PawnOnBackRank(Position { row, col }));
+            }
+        }
+
+        // 当前回合
+        let current_turn = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(FenError::InvalidSideToMove(other.to_string())),
+        };
+
+        // 王车易位权限
+        let mut castling_rights = CastlingRights {
+            white_kingside: false,
+            white_queenside: false,
+            black_kingside: false,
+            black_queenside: false,
+        };
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                match c {
+                    'K' => castling_rights.white_kingside = true,
+                    'Q' => castling_rights.white_queenside = true,
+                    'k' => castling_rights.black_kingside = true,
+                    'q' => castling_rights.black_queenside = true,
+                    _ => return Err(FenError::InvalidCastlingField(fields[2].to_string())),
+                }
+            }
+        }
+
+        // 王和车的"已移动"标记按易位权推断：不在初始格或没有对应易位权的王和车视为已移动
+        for (row, color, kingside, queenside) in [
+            (
+                7,
+                Color::White,
+                castling_rights.white_kingside,
+                castling_rights.white_queenside,
+            ),
+            (
+                0,
+                Color::Black,
+                castling_rights.black_kingside,
+                castling_rights.black_queenside,
+            ),
+        ] {
+            for (col, square) in board[row].iter_mut().enumerate() {
+                match square {
+                    Some(Piece::King(c, moved)) if *c == color && col == 4 => {
+                        *moved = !(kingside || queenside);
+                    }
+                    Some(Piece::Rook(c, moved)) if *c == color => {
+                        *moved = !((col == 7 && kingside) || (col == 0 && queenside));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // 吃过路兵目标
+        // 过路兵格必须是对方兵刚越过的格子：白方走棋时在第6横排，黑方走棋时在第3横排
+        let en_passant_target = match fields[3] {
+            "-" => None,
+            square => {
+                let expected_rank = match current_turn {
+                    Color::White => 6,
+                    Color::Black => 3,
+                };
+                Some(
+                    Position::from_notation(square)
+                        .filter(|pos| pos.rank() == expected_rank)
+                        .ok_or_else(|| FenError::InvalidEnPassantSquare(square.to_string()))?,
+                )
+            }
+        };
+
+        let mut chessboard = Chessboard {
+            board,
+            bitboards: Bitboards::from_board(&board),
+            current_turn,
+            castling_rights,
+            en_passant_target,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            move_history: Vec::new(),
+            position_history: Vec::new(),
+            undo_stack: Vec::new(),
+            concluded: None,
+            analysis: false,
+            strict: false,
+        };
+        chessboard.normalize_en_passant();
+        chessboard.position_history.push(chessboard.position_hash());
+        Ok(chessboard)
+    }
+}
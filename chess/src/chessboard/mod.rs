@@ -0,0 +1,2019 @@
+use crate::glyphs::PieceGlyphs;
+use std::fmt;
+
+// 子模块：位棋盘、走子错误、局面评估、FEN转换、走法生成、SAN记谱、格子编号、棋局状态判定与战术分析
+mod bitboard;
+mod error;
+mod eval;
+mod fen_converter;
+mod movegen;
+mod notation;
+mod san;
+mod square;
+mod status;
+mod tactics;
+mod zobrist;
+
+use bitboard::Bitboards;
+pub use error::{FenError, MoveError};
+pub use square::Square;
+pub use status::{GameStatus, Language, Outcome};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn opposite(&self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Color::White => write!(f, "白方"),
+            Color::Black => write!(f, "黑方"),
+        }
+    }
+}
+
+// 开启 serde 特性时序列化为 {"Rook": ["White", true]} 的形式：颜色和"已移动"标记
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Piece {
+    King(Color, bool),
+    Queen(Color),
+    Rook(Color, bool),
+    Bishop(Color),
+    Knight(Color),
+    Pawn(Color, bool),
+}
+
+impl Piece {
+    pub fn color(&self) -> Color {
+        match self {
+            Piece::King(color, _) => *color,
+            Piece::Queen(color) => *color,
+            Piece::Rook(color, _) => *color,
+            Piece::Bishop(color) => *color,
+            Piece::Knight(color) => *color,
+            Piece::Pawn(color, _) => *color,
+        }
+    }
+
+    // 棋子价值（厘兵），用于交换评估
+    pub fn value(&self) -> i32 {
+        match self {
+            Piece::King(_, _) => 20000,
+            Piece::Queen(_) => 900,
+            Piece::Rook(_, _) => 500,
+            Piece::Bishop(_) => 330,
+            Piece::Knight(_) => 320,
+            Piece::Pawn(_, _) => 100,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Piece::King(_, _) => "王",
+            Piece::Queen(_) => "后",
+            Piece::Rook(_, _) => "车",
+            Piece::Bishop(_) => "象",
+            Piece::Knight(_) => "马",
+            Piece::Pawn(_, _) => "兵",
+        }
+    }
+}
+
+// serde 特性：完整保存棋盘（含走子历史和悔棋记录），读回后可以继续走子和悔棋
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chessboard {
+    board: [[Option<Piece>; 8]; 8],
+    // 与 board 同步的位棋盘，只通过 set_square 修改
+    bitboards: Bitboards,
+    current_turn: Color,
+    castling_rights: CastlingRights,
+    en_passant_target: Option<Position>,
+    // 自上次吃子或走兵以来的半回合数（五十步规则）
+    halfmove_clock: u32,
+    // 全回合数，从1开始，黑方每走一步加1
+    fullmove_number: u32,
+    move_history: Vec<String>,
+    // 每个局面的Zobrist哈希（含初始局面），用于重复局面判定
+    position_history: Vec<u64>,
+    // 悔棋记录，与 move_history 一一对应
+    undo_stack: Vec<(Move, UndoInfo)>,
+    // 对局已通过认输、协议和棋或超时结束
+    concluded: Option<Outcome>,
+    // 分析模式：终局后仍允许继续走子
+    analysis: bool,
+    // 严格模式：调试构建中内部状态不一致时也返回错误而不是panic，见 check_consistency
+    strict: bool,
+}
+
+// 撤销一步所需的信息，由 make_move_unchecked 返回、交给 undo_move：
+// 走子前的棋子（含"已移动"标记，升变时即原来的兵）、被吃的棋子及其所在格
+// （吃过路兵时不在终点）、易位时车的起止格，以及走子前的易位权、过路兵目标和回合计数
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UndoInfo {
+    piece: Piece,
+    captured: Option<(Position, Piece)>,
+    rook_movement: Option<(Position, Position)>,
+    castling_rights: CastlingRights,
+    en_passant_target: Option<Position>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+
+impl CastlingRights {
+    pub fn new() -> Self {
+        Self {
+            white_kingside: true,
+            white_queenside: true,
+            black_kingside: true,
+            black_queenside: true,
+        }
+    }
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 格子颜色：a1为深色格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquareColor {
+    Light,
+    Dark,
+}
+
+// 坐标约定：row=0 为第8横排（白方视角下的棋盘顶部），col=0 为a列；
+// FEN、代数记谱和GUI显示都以此为准，换算统一通过 file/rank 完成；
+// serde 特性下序列化为记谱字符串（如 "e4"），读入时检查格子是否有效
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Position {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Position {
+    pub fn new(row: usize, col: usize) -> Option<Self> {
+        if row < 8 && col < 8 {
+            Some(Self { row, col })
+        } else {
+            None
+        }
+    }
+
+    // 由列号（0=a列）和横排号（1-8）构造
+    pub fn from_file_rank(file: usize, rank: usize) -> Option<Self> {
+        if (1..=8).contains(&rank) {
+            Self::new(8 - rank, file)
+        } else {
+            None
+        }
+    }
+
+    // 列号，0对应a列
+    pub fn file(self) -> usize {
+        self.col
+    }
+
+    // 横排号，1-8
+    pub fn rank(self) -> usize {
+        8 - self.row
+    }
+
+    // 格子颜色（浅/深），同色格上的象永远不会相遇
+    pub fn square_color(self) -> SquareColor {
+        if (self.row + self.col).is_multiple_of(2) {
+            SquareColor::Light
+        } else {
+            SquareColor::Dark
+        }
+    }
+
+    pub fn from_notation(notation: &str) -> Option<Self> {
+        if notation.len() != 2 {
+            return None;
+        }
+        let mut chars = notation.chars();
+        let col_char = chars.next()?;
+        let row_char = chars.next()?;
+
+        let file = match col_char {
+            'a'..='h' => (col_char as usize) - ('a' as usize),
+            _ => return None,
+        };
+
+        let rank = match row_char {
+            '1'..='8' => row_char as usize - '0' as usize,
+            _ => return None,
+        };
+
+        Self::from_file_rank(file, rank)
+    }
+
+    pub fn to_notation(self) -> String {
+        format!("{}{}", (b'a' + self.file() as u8) as char, self.rank())
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub from: Position,
+    pub to: Position,
+    pub promotion: Option<Piece>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for Position {
+    type Error = String;
+
+    fn try_from(notation: String) -> Result<Self, Self::Error> {
+        Position::from_notation(&notation).ok_or_else(|| format!("无效的格子: {}", notation))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Position> for String {
+    fn from(pos: Position) -> Self {
+        pos.to_notation()
+    }
+}
+
+impl Move {
+    // "e2 e4"，可跟第三项升变字母（q/r/b/n，不区分大小写）如 "e7 e8 q"；
+    // 没有棋盘可查，升变棋子的颜色按终点所在的底线决定（第8横排为白方，第1横排为黑方）
+    pub fn from_notation(notation: &str) -> Option<Self> {
+        let parts: Vec<&str> = notation.split_whitespace().collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return None;
+        }
+
+        let from = Position::from_notation(parts[0])?;
+        let to = Position::from_notation(parts[1])?;
+        let promotion = match parts.get(2) {
+            None => None,
+            Some(letter) => {
+                let color = match to.rank() {
+                    8 => Color::White,
+                    1 => Color::Black,
+                    _ => return None,
+                };
+                Some(crate::promotion::piece_from_letter(letter, color)?)
+            }
+        };
+
+        Some(Move {
+            from,
+            to,
+            promotion,
+        })
+    }
+
+    pub fn to_notation(&self) -> String {
+        format!("{} {}", self.from.to_notation(), self.to.to_notation())
+    }
+
+    // UCI记法 "e2e4" / "e7e8q"：4个字符，升变时第5个字符为升变棋子字母；
+    // 升变棋子的颜色与 from_notation 一样按终点所在的底线决定
+    pub fn from_uci(uci: &str) -> Option<Self> {
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return None;
+        }
+        let notation = format!("{} {} {}", &uci[..2], &uci[2..4], &uci[4..]);
+        Self::from_notation(&notation)
+    }
+
+    pub fn to_uci(&self) -> String {
+        let promotion = match self.promotion {
+            Some(Piece::Queen(_)) => "q",
+            Some(Piece::Rook(_, _)) => "r",
+            Some(Piece::Bishop(_)) => "b",
+            Some(Piece::Knight(_)) => "n",
+            _ => "",
+        };
+        format!(
+            "{}{}{}",
+            self.from.to_notation(),
+            self.to.to_notation(),
+            promotion
+        )
+    }
+}
+
+// Chessboard::render 的选项：字形方案、哪一方在下方、是否附带页脚
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub glyphs: PieceGlyphs,
+    // 显示在下方的一方
+    pub bottom: Color,
+    // 是否在棋盘下附带当前回合和将军提示
+    pub footer: bool,
+}
+
+impl RenderOptions {
+    // 纯ASCII字母（白方大写、黑方小写），适合日志和不支持unicode的终端
+    pub fn ascii() -> Self {
+        Self {
+            glyphs: PieceGlyphs::preset("letters").expect("内置方案"),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            glyphs: PieceGlyphs::default(),
+            bottom: Color::White,
+            footer: true,
+        }
+    }
+}
+
+// 按局面比较：只比较与规则相关的状态（棋盘、回合、易位权、过路兵），
+// 走子历史和局面历史不参与比较；需要比较完整对局时请另行比较历史记录
+impl PartialEq for Chessboard {
+    fn eq(&self, other: &Self) -> bool {
+        self.position_eq(other)
+    }
+}
+
+impl Default for Chessboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chessboard {
+    pub fn new() -> Self {
+        let mut board = [[None; 8]; 8];
+
+        // 初始化兵
+        board[1] = [Some(Piece::Pawn(Color::Black, false)); 8];
+        board[6] = [Some(Piece::Pawn(Color::White, false)); 8];
+
+        // 初始化其他棋子 - 黑方
+        board[0][0] = Some(Piece::Rook(Color::Black, false));
+        board[0][1] = Some(Piece::Knight(Color::Black));
+        board[0][2] = Some(Piece::Bishop(Color::Black));
+        board[0][3] = Some(Piece::Queen(Color::Black));
+        board[0][4] = Some(Piece::King(Color::Black, false));
+        board[0][5] = Some(Piece::Bishop(Color::Black));
+        board[0][6] = Some(Piece::Knight(Color::Black));
+        board[0][7] = Some(Piece::Rook(Color::Black, false));
+
+        // 初始化其他棋子 - 白方
+        board[7][0] = Some(Piece::Rook(Color::White, false));
+        board[7][1] = Some(Piece::Knight(Color::White));
+        board[7][2] = Some(Piece::Bishop(Color::White));
+        board[7][3] = Some(Piece::Queen(Color::White));
+        board[7][4] = Some(Piece::King(Color::White, false));
+        board[7][5] = Some(Piece::Bishop(Color::White));
+        board[7][6] = Some(Piece::Knight(Color::White));
+        board[7][7] = Some(Piece::Rook(Color::White, false));
+
+        let mut chessboard = Chessboard {
+            board,
+            bitboards: Bitboards::from_board(&board),
+            current_turn: Color::White,
+            castling_rights: CastlingRights::new(),
+            en_passant_target: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            move_history: Vec::new(),
+            position_history: Vec::new(),
+            undo_stack: Vec::new(),
+            concluded: None,
+            analysis: false,
+            strict: false,
+        };
+        chessboard.position_history.push(chessboard.position_hash());
+        chessboard
+    }
+
+    // 两个棋盘是否处于相同局面（与 == 等价）
+    pub fn position_eq(&self, other: &Chessboard) -> bool {
+        self.board == other.board
+            && self.current_turn == other.current_turn
+            && self.castling_rights == other.castling_rights
+            && self.en_passant_target == other.en_passant_target
+    }
+
+    pub fn get(&self, pos: Position) -> Option<Piece> {
+        self.board[pos.row][pos.col]
+    }
+
+    pub fn current_turn(&self) -> Color {
+        self.current_turn
+    }
+
+    // 同 current_turn，与FEN字段的叫法一致
+    pub fn side_to_move(&self) -> Color {
+        self.current_turn
+    }
+
+    // 当前的易位权（只反映王和车是否动过，不考虑此刻能否真的易位）；例如：
+    //   board.make_move(&Move::from_notation("h1 h2").unwrap())?;
+    //   assert!(!board.castling_rights().white_kingside);
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    // 可以吃过路兵时的目标格（对方兵刚越过的格子）
+    pub fn en_passant_target(&self) -> Option<Position> {
+        self.en_passant_target
+    }
+
+    // 自上次吃子或走兵以来的半回合数
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    // 当前回合数（从1开始，黑方走完后加一）
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    // 走子历史（SAN，如 "Nf3"、"exd5"、"e8=Q+"）
+    pub fn move_history(&self) -> &[String] {
+        &self.move_history
+    }
+
+    // 这步走法是否是兵走到底线升变
+    pub fn is_promotion(&self, mv: &Move) -> bool {
+        match self.get(mv.from) {
+            Some(Piece::Pawn(Color::White, _)) => mv.to.row == 0,
+            Some(Piece::Pawn(Color::Black, _)) => mv.to.row == 7,
+            _ => false,
+        }
+    }
+
+    pub fn make_move(&mut self, mv: &Move) -> Result<(), MoveError> {
+        self.check_consistency()?;
+        if !self.analysis && self.is_game_over() {
+            return Err(MoveError::GameOver);
+        }
+        match self.get(mv.from) {
+            None => return Err(MoveError::NoPieceAtSource),
+            Some(piece) if piece.color() != self.current_turn => return Err(MoveError::WrongColor),
+            Some(_) => {}
+        }
+
+        let mv = &self.checked_promotion(mv)?;
+        let same = |candidate: &Move| {
+            candidate.from == mv.from
+                && candidate.to == mv.to
+                && candidate.promotion == mv.promotion
+        };
+        if !self.get_legal_moves(mv.from).iter().any(same) {
+            // 区分走法本身不合规则和走后王被将军
+            return Err(if self.pseudo_legal_moves(mv.from).iter().any(same) {
+                MoveError::WouldLeaveKingInCheck
+            } else {
+                MoveError::IllegalMove
+            });
+        }
+
+        let san = self.move_to_san(mv);
+        let undo = self.make_move_unchecked(mv)?;
+        self.move_history.push(san);
+        self.undo_stack.push((mv.clone(), undo));
+        Ok(())
+    }
+
+    // 起点没有棋子说明走法不是从当前局面生成的，返回 InternalInconsistency
+    fn undo_info(&self, mv: &Move) -> Result<UndoInfo, MoveError> {
+        let piece = self.get(mv.from).ok_or_else(|| {
+            MoveError::InternalInconsistency(format!("{} 的起点没有棋子", mv.to_notation()))
+        })?;
+        let captured = match self.get(mv.to) {
+            Some(target) => Some((mv.to, target)),
+            // 兵斜走到空格是吃过路兵，被吃的兵在起点所在横排
+            None if matches!(piece, Piece::Pawn(_, _)) && mv.from.col != mv.to.col => {
+                let square = Position {
+                    row: mv.from.row,
+                    col: mv.to.col,
+                };
+                self.get(square).map(|pawn| (square, pawn))
+            }
+            None => None,
+        };
+        Ok(UndoInfo {
+            piece,
+            captured,
+            rook_movement: self.castle_rook_movement(mv),
+            castling_rights: self.castling_rights,
+            en_passant_target: self.en_passant_target,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        })
+    }
+
+    // 悔棋：撤销 make_move 走的最后一步，走子历史同时回退
+    pub fn undo_last(&mut self) -> Result<(), String> {
+        let (mv, undo) = self.undo_stack.pop().ok_or("没有可以悔棋的走法")?;
+        self.undo_move(&mv, undo);
+        self.move_history.pop();
+        Ok(())
+    }
+
+    // 撤销 make_move_unchecked 走的一步，恢复走子前的局面（被吃的棋子、易位的车、升变前的兵、
+    // 易位权、过路兵目标和回合计数）和局面历史；undo必须是这步走法返回的信息
+    pub fn undo_move(&mut self, mv: &Move, undo: UndoInfo) {
+        self.set_square(mv.to, None);
+        self.set_square(mv.from, Some(undo.piece));
+        if let Some((square, piece)) = undo.captured {
+            self.set_square(square, Some(piece));
+        }
+        if let Some((rook_from, rook_to)) = undo.rook_movement {
+            let rook = self.set_square(rook_to, None);
+            self.set_square(rook_from, rook);
+        }
+
+        self.current_turn = undo.piece.color();
+        self.castling_rights = undo.castling_rights;
+        self.en_passant_target = undo.en_passant_target;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.position_history.pop();
+    }
+
+    // 校验并规范走法中的升变棋子：
+    // - 只有走到底线的兵可以带升变棋子，且必须是走子方的后、车、象或马
+    // - 兵走到底线却没有指定升变棋子时默认升变为后（引擎回复 "e7e8" 等写法依赖这一点）
+    // - 升变出的车记为已移动（不能参与易位），与走法生成一致
+    fn checked_promotion(&self, mv: &Move) -> Result<Move, MoveError> {
+        let mut checked = mv.clone();
+        if !self.is_promotion(mv) {
+            if mv.promotion.is_some() {
+                return Err(MoveError::InvalidPromotion(
+                    "只有走到底线的兵才能升变".to_string(),
+                ));
+            }
+            return Ok(checked);
+        }
+
+        let color = self.get(mv.from).expect("升变走法的起点有兵").color();
+        checked.promotion = Some(match mv.promotion {
+            None => Piece::Queen(color),
+            Some(piece) if piece.color() != color => {
+                return Err(MoveError::InvalidPromotion(
+                    "升变棋子必须是己方棋子".to_string(),
+                ))
+            }
+            Some(Piece::Rook(_, _)) => Piece::Rook(color, true),
+            Some(piece @ (Piece::Queen(_) | Piece::Bishop(_) | Piece::Knight(_))) => piece,
+            Some(piece) => {
+                return Err(MoveError::InvalidPromotion(format!(
+                    "不能升变为{}",
+                    piece.name()
+                )))
+            }
+        });
+        Ok(checked)
+    }
+
+    // 王车易位时车的起点和终点（供GUI动画使用）；不是易位走法时返回None
+    pub fn castle_rook_movement(&self, mv: &Move) -> Option<(Position, Position)> {
+        match self.get(mv.from) {
+            Some(Piece::King(_, _)) => {}
+            _ => return None,
+        }
+        if (mv.from.col as i32 - mv.to.col as i32).abs() != 2 {
+            return None;
+        }
+
+        let row = mv.from.row;
+        match mv.to.col {
+            6 => Some((Position { row, col: 7 }, Position { row, col: 5 })),
+            2 => Some((Position { row, col: 0 }, Position { row, col: 3 })),
+            _ => None,
+        }
+    }
+
+    // 严格模式开关；长时间无人值守的对局打开后，规则实现的错误只会中止当前这一局
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // 完整棋盘状态的JSON（serde 特性），用于存盘或网络传输
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("棋盘总能序列化")
+    }
+
+    // 读回 to_json 的结果；内部状态不一致（如手工修改过的文件）时返回错误而不是在走子时panic
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Chessboard, String> {
+        let board: Chessboard =
+            serde_json::from_str(json).map_err(|e| format!("无效的棋盘JSON: {}", e))?;
+        match board.inconsistency() {
+            Some(detail) => Err(format!("内部状态不一致: {}", detail)),
+            None => Ok(board),
+        }
+    }
+
+    // 检查棋盘的内部不变量（双方各有一个王、底线上没有兵、悔棋记录与走子历史一致）。
+    // 这些条件不成立时后续代码会在 find_king 等处panic，所以返回 InternalInconsistency 由调用方中止本局；
+    // 调试构建中非严格模式下直接panic（尽早暴露规则实现的错误），release构建和库的使用者只会得到错误
+    pub fn check_consistency(&self) -> Result<(), MoveError> {
+        let Some(detail) = self.inconsistency() else {
+            return Ok(());
+        };
+        debug_assert!(self.strict, "内部状态不一致: {}", detail);
+        Err(MoveError::InternalInconsistency(detail))
+    }
+
+    fn inconsistency(&self) -> Option<String> {
+        for color in [Color::White, Color::Black] {
+            let kings = self
+                .board
+                .iter()
+                .flatten()
+                .filter(|square| matches!(square, Some(Piece::King(c, _)) if *c == color))
+                .count();
+            if kings != 1 {
+                return Some(format!("{}有{}个王", color, kings));
+            }
+        }
+        for row in [0, 7] {
+            if let Some(col) =
+                (0..8).find(|&col| matches!(self.board[row][col], Some(Piece::Pawn(_, _))))
+            {
+                return Some(format!(
+                    "底线上有兵: {}",
+                    Position { row, col }.to_notation()
+                ));
+            }
+        }
+        if self.bitboards != Bitboards::from_board(&self.board) {
+            return Some("位棋盘与棋盘不一致".to_string());
+        }
+        if self.undo_stack.len() != self.move_history.len() {
+            return Some(format!(
+                "悔棋记录{}步，走子历史{}步",
+                self.undo_stack.len(),
+                self.move_history.len()
+            ));
+        }
+        None
+    }
+
+    // 放置（None为清空）一格上的棋子并同步位棋盘，返回原来的棋子；修改 board 都要经过这里
+    fn set_square(&mut self, pos: Position, piece: Option<Piece>) -> Option<Piece> {
+        let old = std::mem::replace(&mut self.board[pos.row][pos.col], piece);
+        for changed in [old, piece].into_iter().flatten() {
+            self.bitboards.toggle(pos, changed);
+        }
+        old
+    }
+
+    // 不检查合法性、不记录走子历史的走子，供搜索试走；mv应当来自 get_all_legal_moves，
+    // 起点没有棋子时返回 InternalInconsistency，局面不变。用返回的 UndoInfo 调用 undo_move 撤销
+    pub fn make_move_unchecked(&mut self, mv: &Move) -> Result<UndoInfo, MoveError> {
+        let undo = self.undo_info(mv)?;
+        let rook_movement = undo.rook_movement;
+        let piece = undo.piece;
+        self.set_square(mv.from, None);
+
+        // 回合计数：走兵或吃子（包括吃过路兵，升变也是走兵）时半回合数清零
+        let is_pawn = matches!(piece, Piece::Pawn(_, _));
+        if is_pawn || self.board[mv.to.row][mv.to.col].is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.current_turn == Color::Black {
+            self.fullmove_number += 1;
+        }
+
+        // 处理王车易位
+        if let Piece::King(color, _) = piece {
+            if let Some((rook_from, rook_to)) = rook_movement {
+                let rook = self.set_square(rook_from, None);
+                self.set_square(rook_to, rook);
+            }
+
+            match color {
+                Color::White => {
+                    self.castling_rights.white_kingside = false;
+                    self.castling_rights.white_queenside = false;
+                }
+                Color::Black => {
+                    self.castling_rights.black_kingside = false;
+                    self.castling_rights.black_queenside = false;
+                }
+            }
+        }
+
+        // 车离开或在原始角格被吃掉时失去对应的易位权；
+        // 只看角格：从其他格子出发的车不是原始位置的车
+        for corner in [mv.from, mv.to] {
+            match (corner.row, corner.col) {
+                (7, 0) => self.castling_rights.white_queenside = false,
+                (7, 7) => self.castling_rights.white_kingside = false,
+                (0, 0) => self.castling_rights.black_queenside = false,
+                (0, 7) => self.castling_rights.black_kingside = false,
+                _ => {}
+            }
+        }
+
+        // 处理兵的移动
+        if let Piece::Pawn(_color, _) = piece {
+            if let Some(en_passant_pos) = self.en_passant_target {
+                if mv.to.row == en_passant_pos.row && mv.to.col == en_passant_pos.col {
+                    self.set_square(Position::new(mv.from.row, mv.to.col).unwrap(), None);
+                }
+            }
+
+            if (mv.from.row as i32 - mv.to.row as i32).abs() == 2 {
+                let en_passant_row = (mv.from.row + mv.to.row) / 2;
+                self.en_passant_target = Some(Position::new(en_passant_row, mv.from.col).unwrap());
+            } else {
+                self.en_passant_target = None;
+            }
+        } else {
+            self.en_passant_target = None;
+        }
+
+        // 升变时落在目标格的是升变后的棋子
+        let placed = match piece {
+            Piece::Pawn(_, _) => mv.promotion.unwrap_or(piece),
+            _ => piece,
+        };
+        self.set_square(mv.to, Some(placed));
+        self.current_turn = self.current_turn.opposite();
+        self.normalize_en_passant();
+        self.position_history.push(self.position_hash());
+        Ok(undo)
+    }
+
+    // 文本棋盘（含上下的列标和左右的横排号），每格按字形方案的宽度补齐；
+    // 黑方在下方时横排和列都倒过来，页脚为当前回合和将军提示
+    pub fn render(&self, opts: &RenderOptions) -> String {
+        let width = opts.glyphs.cell_width();
+        let (rows, cols): (Vec<usize>, Vec<usize>) = match opts.bottom {
+            Color::White => ((0..8).collect(), (0..8).collect()),
+            Color::Black => ((0..8).rev().collect(), (0..8).rev().collect()),
+        };
+        let files: Vec<String> = cols
+            .iter()
+            .map(|&col| format!("{:<width$}", (b'a' + col as u8) as char, width = width))
+            .collect();
+        let files = format!("  {}", files.join(" "));
+        let border = format!("  {}", "-".repeat(8 * (width + 1)));
+
+        let mut lines = vec![files.clone(), border.clone()];
+        for &row in &rows {
+            let cells: Vec<String> = cols
+                .iter()
+                .map(|&col| opts.glyphs.cell(self.board[row][col]))
+                .collect();
+            lines.push(format!("{}|{}|{}", 8 - row, cells.join(" "), 8 - row));
+        }
+        lines.push(border);
+        lines.push(files);
+
+        if opts.footer {
+            lines.push(format!("当前回合: {}", self.current_turn));
+            if self.is_in_check(self.current_turn) {
+                lines.push(format!("{}被将军!", self.current_turn));
+            }
+        }
+        lines.join("\n")
+    }
+
+    // 白方在下方、带页脚的棋盘，字形按配置
+    pub fn display(&self, glyphs: &PieceGlyphs) -> String {
+        self.render(&RenderOptions {
+            glyphs: glyphs.clone(),
+            ..RenderOptions::default()
+        })
+    }
+
+    pub fn display_move_history(&self) {
+        println!("移动历史:");
+        for (i, mv) in self.move_history.iter().enumerate() {
+            println!("{}. {}", i + 1, mv);
+        }
+    }
+}
+
+// 默认的unicode棋盘，白方在下方，带页脚
+impl fmt::Display for Chessboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(&RenderOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(board: &mut Chessboard, moves: &[&str]) {
+        for notation in moves {
+            let mv = Move::from_notation(notation).unwrap();
+            board.make_move(&mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn twenty_legal_moves_from_the_start() {
+        let board = Chessboard::new();
+        assert_eq!(board.get_all_legal_moves().len(), 20);
+        assert_eq!(board.legal_moves().count(), 20);
+        assert!(board.has_legal_move());
+    }
+
+    // 公认的perft标准值：(局面, 各深度的叶子节点数)
+    fn assert_perft(fen: &str, expected: &[u64]) {
+        let board = Chessboard::from_fen(fen).unwrap();
+        for (depth, &nodes) in (1..).zip(expected) {
+            assert_eq!(board.perft(depth), nodes, "{} 深度{}", fen, depth);
+        }
+    }
+
+    #[test]
+    fn perft_start_position() {
+        assert_perft(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8_902, 197_281],
+        );
+    }
+
+    #[test]
+    #[ignore = "调试构建约需一分钟，用 cargo test --release -- --ignored 运行"]
+    fn perft_start_position_depth_5() {
+        assert_eq!(Chessboard::new().perft(5), 4_865_609);
+    }
+
+    #[test]
+    fn perft_kiwipete() {
+        // 易位、吃过路兵、升变和牵制都有
+        assert_perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2_039, 97_862],
+        );
+    }
+
+    #[test]
+    fn perft_tricky_positions() {
+        // 吃过路兵后横向暴露己方王（过路兵牵制）
+        assert_perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2_812, 43_238],
+        );
+        // 双方底线都有待升变的兵，白方被将军
+        assert_perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9_467],
+        );
+        assert_perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1_486, 62_379],
+        );
+    }
+
+    #[test]
+    fn perft_divide_sums_to_perft() {
+        let board = Chessboard::new();
+        let divide = board.perft_divide(3);
+        assert_eq!(divide.len(), 20);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 8_902);
+        let e2e4 = divide.iter().find(|(mv, _)| mv.to_uci() == "e2e4").unwrap();
+        assert_eq!(e2e4.1, 600);
+        assert!(board.perft_divide(0).is_empty());
+    }
+
+    #[test]
+    fn random_legal_move_is_legal() {
+        let board = Chessboard::new();
+        let mv = board.get_random_legal_move().unwrap();
+        assert!(board
+            .get_legal_moves(mv.from)
+            .iter()
+            .any(|legal| legal.to == mv.to));
+    }
+
+    #[test]
+    fn random_legal_move_none_after_checkmate() {
+        let mut board = Chessboard::new();
+        play(&mut board, &["f2 f3", "e7 e5", "g2 g4", "d8 h4"]);
+        assert!(matches!(board.status(), GameStatus::Checkmate { .. }));
+        assert!(board.get_random_legal_move().is_none());
+        assert!(board.get_all_legal_moves().is_empty());
+        assert_eq!(board.legal_moves().count(), 0);
+    }
+
+    #[test]
+    fn repetition_count_climbs_with_knight_shuffle() {
+        let mut board = Chessboard::new();
+        assert_eq!(board.repetition_count(), 1);
+
+        play(&mut board, &["g1 f3", "g8 f6", "f3 g1", "f6 g8"]);
+        assert_eq!(board.repetition_count(), 2);
+
+        play(&mut board, &["g1 f3", "g8 f6", "f3 g1", "f6 g8"]);
+        assert_eq!(board.repetition_count(), 3);
+        assert!(board.is_threefold_repetition());
+        assert!(board.is_game_over());
+        assert_eq!(
+            board.describe_status(Language::English),
+            "Draw by threefold repetition"
+        );
+    }
+
+    #[test]
+    fn threefold_repetition_with_ne2_nf4_shuffle() {
+        let mut board = Chessboard::new();
+        play(&mut board, &["e2 e3", "e7 e6", "g1 e2", "g8 e7"]);
+        for _ in 0..2 {
+            assert!(!board.is_threefold_repetition());
+            play(&mut board, &["e2 f4", "e7 f5", "f4 e2", "f5 e7"]);
+        }
+        assert!(board.is_threefold_repetition());
+        assert!(board.is_game_over());
+
+        // 王走回原位后失去易位权，与之前的局面不算重复
+        let mut board = Chessboard::new();
+        play(&mut board, &["e2 e3", "e7 e6"]);
+        for _ in 0..2 {
+            play(&mut board, &["e1 e2", "e8 e7", "e2 e1", "e7 e8"]);
+        }
+        assert_eq!(board.repetition_count(), 2);
+
+        // 可以吃过路兵的局面与同样布局但不能吃过路兵的局面不同
+        let mut board = Chessboard::from_fen("4k3/8/8/8/4p3/8/3P4/4K3 w - - 0 1").unwrap();
+        play(&mut board, &["d2 d4"]);
+        let with_target = board.position_hash();
+        play(&mut board, &["e8 d8", "e1 d1", "d8 e8", "d1 e1"]);
+        assert_eq!(board.repetition_count(), 1);
+        assert_ne!(board.position_hash(), with_target);
+    }
+
+    #[test]
+    fn position_hash_ignores_move_order_and_counters() {
+        // 不同走法次序到达同一局面，回合计数不同但哈希相同
+        let mut first = Chessboard::new();
+        play(&mut first, &["g1 f3", "g8 f6", "b1 c3", "b8 c6"]);
+        let mut second = Chessboard::new();
+        play(&mut second, &["b1 c3", "b8 c6", "g1 f3", "g8 f6"]);
+        assert_eq!(first.position_hash(), second.position_hash());
+        let later = Chessboard::from_fen(&first.to_fen().replace(" 4 3", " 20 30")).unwrap();
+        assert_eq!(later.position_hash(), first.position_hash());
+
+        // 易位权、过路兵和走棋方不同都算不同局面
+        let fen = "r3k2r/8/8/8/4p3/8/3P4/R3K2R w KQkq - 0 1";
+        let hash = |fen: &str| Chessboard::from_fen(fen).unwrap().position_hash();
+        assert_ne!(hash(fen), hash(&fen.replace("KQkq", "Qkq")));
+        assert_ne!(hash(fen), hash(&fen.replace(" w ", " b ")));
+        let mut pushed = Chessboard::from_fen(fen).unwrap();
+        play(&mut pushed, &["d2 d4"]);
+        assert_ne!(
+            pushed.position_hash(),
+            hash("r3k2r/8/8/8/3Pp3/8/8/R3K2R b KQkq - 0 1")
+        );
+    }
+
+    #[test]
+    fn hanging_pieces_counts_defenders() {
+        let mut board = Chessboard::new();
+        play(&mut board, &["e2 e4", "d7 d5"]);
+
+        let e4 = Position::from_notation("e4").unwrap();
+        assert_eq!(board.hanging_pieces(Color::White), vec![e4]);
+        // d5兵由d8后保护，兑换不亏
+        assert!(board.hanging_pieces(Color::Black).is_empty());
+    }
+
+    #[test]
+    fn square_report_lists_attackers_and_pins() {
+        // e2的马被e8的车牵制在王前
+        let board = Chessboard::from_fen("4r1k1/8/8/8/3p4/8/4N3/4K3 w - - 0 1").unwrap();
+        let d4 = Position::from_notation("d4").unwrap();
+        assert_eq!(
+            board.square_report(d4),
+            ["白方攻击d4 1次: 马e2(被牵制)", "黑方保护d4 0次"]
+        );
+        let e5 = Position::from_notation("e5").unwrap();
+        assert_eq!(
+            board.square_report(e5),
+            ["白方攻击e5 0次", "黑方攻击e5 1次: 车e8"]
+        );
+    }
+
+    #[test]
+    fn stalemate_traps_are_detected() {
+        // 王后对单王：Qf7逼和，Qf8#将杀
+        let board = Chessboard::from_fen("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1").unwrap();
+        let mv = |notation: &str| Move::from_notation(notation).unwrap();
+        assert!(board.stalemates_opponent(&mv("f1 f7")));
+        assert!(!board.stalemates_opponent(&mv("f1 f8")));
+        assert!(board.stalemate_reply(&mv("f1 f8")).is_none());
+
+        // 白王被困在h1：后走到车能吃的格子，黑车吃掉白后，白方无子可动
+        let board = Chessboard::from_fen("Q7/8/8/1r6/8/6p1/5k2/7K w - - 0 1").unwrap();
+        assert!(!board.stalemates_opponent(&mv("a8 a5")));
+        assert_eq!(
+            board.stalemate_reply(&mv("a8 a5")).unwrap().to_notation(),
+            "b5 a5"
+        );
+        assert!(board.stalemate_reply(&mv("a8 a7")).is_none());
+    }
+
+    #[test]
+    fn transposed_move_orders_are_position_eq() {
+        let mut first = Chessboard::new();
+        play(&mut first, &["g1 f3", "g8 f6", "b1 c3", "b8 c6"]);
+        let mut second = Chessboard::new();
+        play(&mut second, &["b1 c3", "b8 c6", "g1 f3", "g8 f6"]);
+
+        assert!(first.position_eq(&second));
+        assert!(first == second);
+        assert_eq!(first.minimal_fen(), second.minimal_fen());
+        assert!(!first.position_eq(&Chessboard::new()));
+    }
+
+    #[test]
+    fn castle_rook_movement_for_both_wings() {
+        let mut board = Chessboard::new();
+        play(
+            &mut board,
+            &[
+                "g1 f3", "b8 c6", "e2 e3", "d7 d6", "f1 e2", "c8 e6", "a2 a3", "d8 d7",
+            ],
+        );
+
+        let square = |notation| Position::from_notation(notation).unwrap();
+        let kingside = Move::from_notation("e1 g1").unwrap();
+        assert_eq!(
+            board.castle_rook_movement(&kingside),
+            Some((square("h1"), square("f1")))
+        );
+
+        board.make_move(&kingside).unwrap();
+        let queenside = Move::from_notation("e8 c8").unwrap();
+        assert_eq!(
+            board.castle_rook_movement(&queenside),
+            Some((square("a8"), square("d8")))
+        );
+
+        let quiet = Move::from_notation("d7 d8").unwrap();
+        assert_eq!(board.castle_rook_movement(&quiet), None);
+    }
+
+    #[test]
+    fn describe_status_in_both_languages() {
+        let mut board = Chessboard::new();
+        assert_eq!(board.describe_status(Language::Chinese), "轮到白方走棋");
+        assert_eq!(board.describe_status(Language::English), "White to move");
+
+        play(&mut board, &["e2 e4", "f7 f6", "d2 d4", "g7 g5"]);
+        play(&mut board, &["d1 h5"]);
+        assert_eq!(board.describe_status(Language::Chinese), "将死! 白方获胜!");
+        assert_eq!(
+            board.describe_status(Language::English),
+            "White wins by checkmate"
+        );
+
+        let mut board = Chessboard::new();
+        play(&mut board, &["e2 e4", "e7 e5", "f1 c4", "d7 d6", "c4 f7"]);
+        assert_eq!(board.describe_status(Language::Chinese), "黑方被将军!");
+        assert_eq!(
+            board.describe_status(Language::English),
+            "Black is in check"
+        );
+
+        // Sam Loyd的十步逼和
+        let mut board = Chessboard::new();
+        play(
+            &mut board,
+            &[
+                "e2 e3", "a7 a5", "d1 h5", "a8 a6", "h5 a5", "h7 h5", "h2 h4", "a6 h6", "a5 c7",
+                "f7 f6", "c7 d7", "e8 f7", "d7 b7", "d8 d3", "b7 b8", "d3 h7", "b8 c8", "f7 g6",
+                "c8 e6",
+            ],
+        );
+        assert_eq!(board.describe_status(Language::Chinese), "僵局! 游戏平局!");
+        assert_eq!(
+            board.describe_status(Language::English),
+            "Draw by stalemate"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn board_round_trips_through_json() {
+        let mut board = Chessboard::new();
+        for notation in ["e4", "c5", "Nf3", "d6", "Bb5+", "Nc6", "O-O", "a6", "Bxc6+"] {
+            let mv = board.parse_move(notation).unwrap();
+            board.make_move(&mv).unwrap();
+        }
+
+        let mut restored = Chessboard::from_json(&board.to_json()).unwrap();
+        assert_eq!(restored, board);
+        assert_eq!(restored.to_fen(), board.to_fen());
+        let uci = |board: &Chessboard| -> Vec<String> {
+            board
+                .get_all_legal_moves()
+                .iter()
+                .map(Move::to_uci)
+                .collect()
+        };
+        assert_eq!(uci(&restored), uci(&board));
+        assert_eq!(restored.move_history(), board.move_history());
+        assert_eq!(restored.check_consistency(), Ok(()));
+
+        // 读回的棋盘可以悔棋
+        restored.undo_last().unwrap();
+        assert_eq!(restored.move_history().last().unwrap(), "a6");
+
+        let pos: Position = serde_json::from_str("\"e4\"").unwrap();
+        assert_eq!(pos, Position::from_notation("e4").unwrap());
+        assert!(serde_json::from_str::<Position>("\"e9\"").is_err());
+
+        // 去掉一个王的存档被拒绝
+        let json = board
+            .to_json()
+            .replacen("{\"King\":[\"Black\",false]}", "null", 1);
+        assert!(Chessboard::from_json(&json)
+            .unwrap_err()
+            .contains("黑方有0个王"));
+        assert!(Chessboard::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn start_position_renders_in_both_orientations() {
+        let board = Chessboard::new();
+        assert_eq!(
+            board.to_string(),
+            "  a b c d e f g h\n\
+             \x20 ----------------\n\
+             8|♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜|8\n\
+             7|♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟|7\n\
+             6|               |6\n\
+             5|               |5\n\
+             4|               |4\n\
+             3|               |3\n\
+             2|♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙|2\n\
+             1|♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖|1\n\
+             \x20 ----------------\n\
+             \x20 a b c d e f g h\n\
+             当前回合: 白方"
+        );
+
+        let flipped = RenderOptions {
+            bottom: Color::Black,
+            footer: false,
+            ..RenderOptions::ascii()
+        };
+        assert_eq!(
+            board.render(&flipped),
+            "  h g f e d c b a\n\
+             \x20 ----------------\n\
+             1|R N B K Q B N R|1\n\
+             2|P P P P P P P P|2\n\
+             3|               |3\n\
+             4|               |4\n\
+             5|               |5\n\
+             6|               |6\n\
+             7|p p p p p p p p|7\n\
+             8|r n b k q b n r|8\n\
+             \x20 ----------------\n\
+             \x20 h g f e d c b a"
+        );
+    }
+
+    #[test]
+    fn footer_reports_check() {
+        let board = Chessboard::from_fen("4k3/8/8/8/8/8/8/4R2K b - - 0 1").unwrap();
+        assert!(board.to_string().ends_with("当前回合: 黑方\n黑方被将军!"));
+        let plain = RenderOptions {
+            footer: false,
+            ..RenderOptions::default()
+        };
+        assert!(board.render(&plain).ends_with("  a b c d e f g h"));
+    }
+
+    #[test]
+    fn describe_last_move_names_special_moves() {
+        let described = |fen: &str, moves: &[&str]| {
+            let mut board = match fen {
+                "" => Chessboard::new(),
+                fen => Chessboard::from_fen(fen).unwrap(),
+            };
+            for notation in moves {
+                let mv = board.parse_move(notation).unwrap();
+                board.make_move(&mv).unwrap();
+            }
+            [Language::Chinese, Language::English].map(|lang| board.describe_last_move(lang))
+        };
+
+        assert_eq!(described("", &[]), [None, None]);
+        assert_eq!(
+            described("", &["e4", "Nf6"]).map(Option::unwrap),
+            ["黑方: Nf6", "Black: Nf6"]
+        );
+        assert_eq!(
+            described("", &["e4", "d5", "exd5"]).map(Option::unwrap),
+            ["白方: exd5 吃掉黑兵", "White: exd5 captures black pawn"]
+        );
+        assert_eq!(
+            described("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", &["exd6"]).map(Option::unwrap),
+            ["白方: exd6 吃过路兵", "White: exd6 captures en passant"]
+        );
+        assert_eq!(
+            described("4k3/8/8/8/8/8/8/4K2R w K - 0 1", &["O-O"]).map(Option::unwrap),
+            ["白方: O-O 短易位", "White: O-O castles kingside"]
+        );
+        assert_eq!(
+            described("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", &["O-O-O"]).map(Option::unwrap),
+            ["黑方: O-O-O 长易位", "Black: O-O-O castles queenside"]
+        );
+        assert_eq!(
+            described("k7/4P3/8/8/8/8/8/4K3 w - - 0 1", &["e8=Q+"]).map(Option::unwrap),
+            [
+                "白方: e8=Q+ 升变为后并将军",
+                "White: e8=Q+ promotes to queen with check"
+            ]
+        );
+        assert_eq!(
+            described("", &["f3", "e5", "g4", "Qh4#"]).map(Option::unwrap),
+            ["黑方: Qh4# 将死", "Black: Qh4# checkmate"]
+        );
+    }
+
+    #[test]
+    fn no_moves_after_game_concluded() {
+        let mut board = Chessboard::new();
+        board.conclude(Outcome::Resigned(Color::White));
+        assert!(board.is_game_over());
+        assert_eq!(board.outcome(), Some(Outcome::Resigned(Color::White)));
+        assert!(board.get_random_legal_move().is_none());
+        assert!(board.get_all_legal_moves().is_empty());
+        assert_eq!(board.legal_moves().count(), 0);
+        assert!(board
+            .get_legal_moves(Position::from_notation("e2").unwrap())
+            .is_empty());
+        // 认输不改变局面本身的结果判定
+        assert_eq!(board.status(), GameStatus::Ongoing);
+        let mv = Move::from_notation("e2 e4").unwrap();
+        assert_eq!(board.make_move(&mv), Err(MoveError::GameOver));
+
+        board.continue_from_here();
+        assert!(!board.is_game_over());
+        assert_eq!(board.get_all_legal_moves().len(), 20);
+        assert!(board.make_move(&mv).is_ok());
+    }
+
+    #[test]
+    fn position_file_rank_conversions() {
+        let a1 = Position::from_notation("a1").unwrap();
+        assert_eq!(a1, Position { row: 7, col: 0 });
+        assert_eq!((a1.file(), a1.rank()), (0, 1));
+        assert_eq!(Position::from_file_rank(7, 8), Position::new(0, 7));
+        assert_eq!(Position::from_file_rank(0, 0), None);
+        assert_eq!(Position::new(0, 7).unwrap().to_notation(), "h8");
+    }
+
+    #[test]
+    fn same_colored_bishops_dead_position() {
+        let square = |notation| Position::from_notation(notation).unwrap();
+        assert_eq!(square("a1").square_color(), SquareColor::Dark);
+        assert_eq!(square("h1").square_color(), SquareColor::Light);
+
+        // 王+c1象 对 王+f8象：两只象都在深色格
+        let board = Chessboard::from_minimal_fen("4kb2/8/8/8/8/8/8/2B1K3 w - -").unwrap();
+        assert_eq!(board.bishops_on(Color::White), (0, 1));
+        assert_eq!(board.bishops_on(Color::Black), (0, 1));
+
+        let opposite = Chessboard::from_minimal_fen("4k1b1/8/8/8/8/8/8/2B1K3 w - -").unwrap();
+        assert_eq!(opposite.bishops_on(Color::Black), (1, 0));
+    }
+
+    #[test]
+    fn flag_fall_is_a_draw_without_mating_material() {
+        // 白方超时，黑方只剩单王/王+马/王+象：和棋
+        for fen in [
+            "4k3/8/8/8/8/8/8/R3K3 w - -",
+            "4k3/8/8/8/8/5n2/8/R3K3 w - -",
+            "4k3/8/8/8/8/5b2/8/R3K3 w - -",
+        ] {
+            let board = Chessboard::from_minimal_fen(fen).unwrap();
+            assert_eq!(board.flag_fall_winner(Color::White), None, "{}", fen);
+        }
+
+        // 黑方还有车、兵或两个轻子：超时判负
+        for fen in [
+            "r3k3/8/8/8/8/8/8/4K3 w - -",
+            "4k3/p7/8/8/8/8/8/4K3 w - -",
+            "4k3/8/8/8/8/2b2n2/8/4K3 w - -",
+        ] {
+            let board = Chessboard::from_minimal_fen(fen).unwrap();
+            assert_eq!(
+                board.flag_fall_winner(Color::White),
+                Some(Color::Black),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn from_fen_round_trips_and_validates() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            assert_eq!(Chessboard::from_fen(fen).unwrap().to_fen(), fen);
+        }
+        assert_eq!(
+            Chessboard::from_fen(&Chessboard::new().to_fen())
+                .unwrap()
+                .board,
+            Chessboard::new().board
+        );
+
+        // 已移动标记按易位权和初始横排推断
+        let square = |notation| Position::from_notation(notation).unwrap();
+        let board = Chessboard::from_fen("r3k2r/8/8/8/4P3/8/P7/R3K2R w Kq - 0 1").unwrap();
+        assert_eq!(
+            board.get(square("e1")),
+            Some(Piece::King(Color::White, false))
+        );
+        assert_eq!(
+            board.get(square("h1")),
+            Some(Piece::Rook(Color::White, false))
+        );
+        assert_eq!(
+            board.get(square("a1")),
+            Some(Piece::Rook(Color::White, true))
+        );
+        assert_eq!(
+            board.get(square("h8")),
+            Some(Piece::Rook(Color::Black, true))
+        );
+        assert_eq!(
+            board.get(square("a2")),
+            Some(Piece::Pawn(Color::White, false))
+        );
+        assert_eq!(
+            board.get(square("e4")),
+            Some(Piece::Pawn(Color::White, true))
+        );
+        let no_rights = Chessboard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            no_rights.get(square("e1")),
+            Some(Piece::King(Color::White, true))
+        );
+
+        for bad in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/7/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+        ] {
+            assert!(Chessboard::from_fen(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn read_only_state_accessors() {
+        let mut board = Chessboard::new();
+        assert_eq!(board.castling_rights(), CastlingRights::new());
+        play(&mut board, &["h2 h4", "e7 e5", "h1 h2"]);
+        // h1车动过后白方失去短易位权，长易位权仍在
+        assert!(!board.castling_rights().white_kingside);
+        assert!(board.castling_rights().white_queenside);
+        assert_eq!(board.side_to_move(), Color::Black);
+        assert_eq!(board.en_passant_target(), None);
+
+        let board =
+            Chessboard::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        assert_eq!(board.en_passant_target(), Position::from_notation("f6"));
+    }
+
+    #[test]
+    fn fen_errors_name_the_problem() {
+        let error = |fen: &str| Chessboard::from_fen(fen).unwrap_err();
+        let square = |notation| Position::from_notation(notation).unwrap();
+
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w - -"),
+            FenError::WrongFieldCount {
+                expected: 6,
+                found: 4
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/4K3 w - - 0 1"),
+            FenError::WrongRankCount(7)
+        );
+        assert_eq!(
+            error("4k3/8/8/9/8/8/8/4K3 w - - 0 1"),
+            FenError::BadRankLength { rank: 5 }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K4 w - - 0 1"),
+            FenError::BadRankLength { rank: 1 }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/3x4/8/8/4K3 w - - 0 1"),
+            FenError::InvalidPieceChar('x')
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            FenError::InvalidSideToMove("x".to_string())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w KX - 0 1"),
+            FenError::InvalidCastlingField("KX".to_string())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w - z9 0 1"),
+            FenError::InvalidEnPassantSquare("z9".to_string())
+        );
+        // 白方走棋时过路兵格不可能在第3横排
+        assert_eq!(
+            error("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"),
+            FenError::InvalidEnPassantSquare("e3".to_string())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+            FenError::InvalidMoveCounter("x".to_string())
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+            FenError::MissingKing(Color::Black)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K2K w - - 0 1"),
+            FenError::TooManyKings(Color::White)
+        );
+        assert_eq!(
+            error("4k2P/8/8/8/8/8/8/4K3 w - - 0 1"),
+            FenError::PawnOnBackRank(square("h8"))
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/p3K3 w - - 0 1").to_string(),
+            "底线上不能有兵: a1"
+        );
+    }
+
+    #[test]
+    fn simultaneous_results_follow_precedence() {
+        use super::status::DrawReason;
+
+        // 王+象对单王的逼和：逼和与子力不足同时成立，立即生效的原因按顺序列出
+        let board = Chessboard::from_fen("k7/8/1K1B4/8/8/8/8/8 b - - 0 1").unwrap();
+        let both = GameStatus::Draw {
+            reasons: vec![DrawReason::Stalemate, DrawReason::InsufficientMaterial],
+        };
+        assert_eq!(board.status(), both);
+        assert_eq!(
+            board.describe_status(Language::English),
+            "Draw by stalemate and insufficient material"
+        );
+        assert_eq!(crate::pgn::result_tag(&board), "1/2-1/2");
+
+        // 逼和局面同时是第三次重复：可提和的原因排在后面
+        let mut repeated = Chessboard::from_fen("k7/8/1KPB4/8/8/8/8/8 b - - 0 1").unwrap();
+        let key = repeated.position_hash();
+        repeated.position_history.extend([key, key]);
+        assert_eq!(
+            repeated.status(),
+            GameStatus::Draw {
+                reasons: vec![DrawReason::Stalemate, DrawReason::ThreefoldRepetition]
+            }
+        );
+
+        // 将死的一步同时造成第三次重复：将死优先
+        let mut mate = Chessboard::new();
+        play(&mut mate, &["f2 f3", "e7 e5", "g2 g4", "d8 h4"]);
+        let key = mate.position_hash();
+        mate.position_history.extend([key, key]);
+        assert_eq!(
+            mate.status(),
+            GameStatus::Checkmate {
+                winner: Color::Black
+            }
+        );
+        assert_eq!(crate::pgn::result_tag(&mate), "0-1");
+    }
+
+    #[test]
+    fn status_for_each_result() {
+        use super::status::DrawReason;
+
+        let draw = |reason| GameStatus::Draw {
+            reasons: vec![reason],
+        };
+        assert_eq!(Chessboard::new().status(), GameStatus::Ongoing);
+
+        // 底线将杀
+        let back_rank = Chessboard::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 30").unwrap();
+        assert_eq!(
+            back_rank.status(),
+            GameStatus::Checkmate {
+                winner: Color::White
+            }
+        );
+        assert!(back_rank.is_game_over());
+
+        let stalemate = Chessboard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 60").unwrap();
+        assert_eq!(stalemate.status(), draw(DrawReason::Stalemate));
+
+        let bare_knight = Chessboard::from_fen("8/8/4k3/8/8/3NK3/8/8 w - - 0 70").unwrap();
+        assert_eq!(bare_knight.status(), draw(DrawReason::InsufficientMaterial));
+
+        let fifty = Chessboard::from_fen("8/8/4k3/8/8/3RK3/8/8 w - - 100 90").unwrap();
+        assert_eq!(fifty.status(), draw(DrawReason::FiftyMoveRule));
+
+        let mut repeated = Chessboard::new();
+        play(
+            &mut repeated,
+            &[
+                "g1 f3", "g8 f6", "f3 g1", "f6 g8", "g1 f3", "g8 f6", "f3 g1", "f6 g8",
+            ],
+        );
+        assert_eq!(repeated.status(), draw(DrawReason::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn en_passant_target_kept_only_when_capturable() {
+        let normalized = |fen: &str| Chessboard::from_fen(fen).unwrap().to_fen();
+        // 吃过路兵后王沿横排暴露给车/后（白方、黑方各一例）
+        assert_eq!(
+            normalized("4k3/8/8/KPp4r/8/8/8/8 w - c6 0 2"),
+            "4k3/8/8/KPp4r/8/8/8/8 w - - 0 2"
+        );
+        assert_eq!(
+            normalized("8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1"),
+            "8/8/8/8/k2Pp2Q/8/8/3K4 b - - 0 1"
+        );
+        // 没有能吃过路兵的兵、目标格被占
+        assert_eq!(
+            normalized("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+        );
+        assert_eq!(
+            normalized("4k3/8/2n5/2pP4/8/8/8/4K3 w - c6 0 1"),
+            "4k3/8/2n5/2pP4/8/8/8/4K3 w - - 0 1"
+        );
+        // 吃过路兵解除兵的将军时保留
+        assert_eq!(
+            normalized("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1"),
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1"
+        );
+
+        // 对局中兵走两格后同样规范化，局面哈希与没有目标格的同一局面一致
+        let mut board = Chessboard::new();
+        play(&mut board, &["e2 e4"]);
+        assert!(board.to_fen().contains(" b KQkq - "));
+        let plain = Chessboard::from_fen(&board.to_fen()).unwrap();
+        assert_eq!(board.position_hash(), plain.position_hash());
+        play(&mut board, &["d7 d5", "e4 e5", "f7 f5"]);
+        assert!(board.to_fen().contains(" w KQkq f6 "));
+    }
+
+    #[test]
+    fn move_errors_name_the_reason() {
+        // 白王e1被e8的车沿e线牵制住e2的象
+        let board = Chessboard::from_fen("4r1k1/8/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        let error = |notation: &str| {
+            board
+                .clone()
+                .make_move(&Move::from_notation(notation).unwrap())
+                .unwrap_err()
+        };
+
+        assert_eq!(error("a1 a2"), MoveError::NoPieceAtSource);
+        assert_eq!(error("e8 e7"), MoveError::WrongColor);
+        assert_eq!(error("e2 e4"), MoveError::IllegalMove);
+        assert_eq!(error("e2 d3"), MoveError::WouldLeaveKingInCheck);
+        assert_eq!(error("e2 d3").to_string(), "非法的移动: 走后己方王被将军");
+        assert_eq!(error("e2 e4").to_string(), "非法的移动");
+        assert!(matches!(
+            board.clone().make_move(&Move {
+                promotion: Some(Piece::Queen(Color::White)),
+                ..Move::from_notation("e1 d1").unwrap()
+            }),
+            Err(MoveError::InvalidPromotion(_))
+        ));
+    }
+
+    // 去掉黑王，模拟规则实现出错后被破坏的局面
+    fn corrupted_board() -> Chessboard {
+        let mut board = Chessboard::new();
+        board.set_square(Position::new(0, 4).unwrap(), None);
+        board
+    }
+
+    #[test]
+    fn strict_mode_reports_inconsistency() {
+        let mut board = corrupted_board();
+        board.set_strict(true);
+        let error = board
+            .make_move(&Move::from_notation("e2 e4").unwrap())
+            .unwrap_err();
+        assert!(matches!(error, MoveError::InternalInconsistency(_)));
+        assert_eq!(error.to_string(), "内部状态不一致: 黑方有0个王");
+        assert!(Chessboard::new().check_consistency().is_ok());
+    }
+
+    #[test]
+    fn unchecked_move_from_empty_square_is_an_error() {
+        let mut board = Chessboard::new();
+        let error = board
+            .make_move_unchecked(&Move::from_notation("e4 e5").unwrap())
+            .unwrap_err();
+        assert!(matches!(error, MoveError::InternalInconsistency(_)));
+        assert_eq!(board.to_fen(), Chessboard::new().to_fen());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "内部状态不一致")]
+    fn inconsistency_panics_in_debug_builds_outside_strict_mode() {
+        let _ = corrupted_board().make_move(&Move::from_notation("e2 e4").unwrap());
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn inconsistency_is_an_error_in_release_builds() {
+        let error = corrupted_board()
+            .make_move(&Move::from_notation("e2 e4").unwrap())
+            .unwrap_err();
+        assert!(matches!(error, MoveError::InternalInconsistency(_)));
+    }
+
+    #[test]
+    fn promotion_pieces_are_validated() {
+        let board = Chessboard::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promote = |to: &str, piece| {
+            let mut mv = Move::from_notation(&format!("a7 {}", to)).unwrap();
+            mv.promotion = piece;
+            board.clone().make_move(&mv)
+        };
+
+        assert!(promote("a8", Some(Piece::King(Color::White, true))).is_err());
+        assert!(promote("a8", Some(Piece::Pawn(Color::White, true))).is_err());
+        assert!(promote("a8", Some(Piece::Queen(Color::Black))).is_err());
+        // 不是升变的走法不能带升变棋子
+        let mut king = Move::from_notation("e1 e2").unwrap();
+        king.promotion = Some(Piece::Queen(Color::White));
+        assert!(board.clone().make_move(&king).is_err());
+
+        // 低升变按指定棋子落子；没有指定时升变为后
+        let mut knight = board.clone();
+        knight
+            .make_move(&Move::from_notation("a7 b8 n").unwrap())
+            .unwrap();
+        assert_eq!(
+            knight.get(Position::from_notation("b8").unwrap()),
+            Some(Piece::Knight(Color::White))
+        );
+        assert_eq!(knight.move_history(), ["axb8=N"]);
+        let mut rook = board.clone();
+        rook.make_move(&Move {
+            promotion: Some(Piece::Rook(Color::White, false)),
+            ..Move::from_notation("a7 a8").unwrap()
+        })
+        .unwrap();
+        assert_eq!(
+            rook.get(Position::from_notation("a8").unwrap()),
+            Some(Piece::Rook(Color::White, true))
+        );
+        let mut queen = board.clone();
+        queen
+            .make_move(&Move::from_notation("a7 a8").unwrap())
+            .unwrap();
+        assert_eq!(queen.move_history(), ["a8=Q"]);
+    }
+
+    #[test]
+    fn undo_restores_special_moves() {
+        let cases = [
+            // 王车易位（两翼）
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 20", "e1 g1"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 20", "e8 c8"),
+            // 吃过路兵
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 30", "e5 d6"),
+            // 吃子升变、低升变
+            ("1n2k3/P7/8/8/8/8/8/4K3 w - - 5 40", "a7 b8 q"),
+            ("4k3/8/8/8/8/8/p7/4K3 b - - 0 40", "a2 a1 n"),
+            // 吃掉原始位置的车（易位权改变）
+            ("r3k2r/8/8/8/8/8/6b1/R3K2R b KQkq - 7 12", "g2 h1"),
+        ];
+        for (fen, notation) in cases {
+            let before = Chessboard::from_fen(fen).unwrap();
+            let mut board = before.clone();
+            play(&mut board, &[notation]);
+            assert_ne!(board.to_fen(), fen);
+
+            board.undo_last().unwrap();
+            assert_eq!(board.to_fen(), fen, "{}", notation);
+            assert_eq!(board.board, before.board, "{}", notation);
+            assert!(board.move_history().is_empty());
+            assert_eq!(board.position_history, before.position_history);
+            assert!(board.undo_last().is_err());
+        }
+
+        // 连续悔棋回到初始局面，已移动标记也一并恢复（之后仍可易位）
+        let mut board = Chessboard::new();
+        play(
+            &mut board,
+            &[
+                "e2 e4", "e7 e5", "g1 f3", "b8 c6", "f1 c4", "g8 f6", "e1 g1",
+            ],
+        );
+        for _ in 0..7 {
+            board.undo_last().unwrap();
+        }
+        assert_eq!(board.board, Chessboard::new().board);
+        assert_eq!(board.to_fen(), Chessboard::new().to_fen());
+    }
+
+    #[test]
+    fn make_and_undo_every_legal_move() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "1n2k3/P6P/8/8/8/8/p7/4K3 b - - 9 50",
+        ] {
+            let mut board = Chessboard::from_fen(fen).unwrap();
+            let original = board.clone();
+            for mv in board.get_all_legal_moves() {
+                let undo = board.make_move_unchecked(&mv).unwrap();
+                board.undo_move(&mv, undo);
+                assert_eq!(board.to_fen(), fen, "{}", mv.to_notation());
+                assert_eq!(board.board, original.board, "{}", mv.to_notation());
+                assert_eq!(board.position_history, original.position_history);
+
+                board.make_move(&mv).unwrap();
+                board.undo_last().unwrap();
+                assert_eq!(board.to_fen(), fen, "{}", mv.to_notation());
+                assert!(board.move_history().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn legal_moves_match_clone_per_move_filter() {
+        // 原来的实现：每个候选走法都在完整副本上试走
+        fn reference(board: &Chessboard) -> Vec<String> {
+            let mut moves: Vec<String> = (0..8)
+                .flat_map(|row| (0..8).map(move |col| Position { row, col }))
+                .flat_map(|from| board.pseudo_legal_moves(from))
+                .filter(|mv| {
+                    let mut test_board = board.clone();
+                    test_board.make_move_unchecked(mv).unwrap();
+                    !test_board.is_in_check(board.current_turn)
+                })
+                .map(|mv| mv.to_uci())
+                .collect();
+            moves.sort();
+            moves
+        }
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4r1k1/8/8/8/8/8/4B3/4K3 w - - 0 1",
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+        ] {
+            let board = Chessboard::from_fen(fen).unwrap();
+            let mut moves: Vec<String> = board
+                .get_all_legal_moves()
+                .iter()
+                .map(Move::to_uci)
+                .collect();
+            moves.sort();
+            let expected = reference(&board);
+            assert_eq!(moves, expected, "{}", fen);
+            assert_eq!(board.has_legal_move(), !expected.is_empty(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn move_counters_follow_pawn_moves_and_captures() {
+        let mut board = Chessboard::new();
+        play(&mut board, &["g1 f3", "g8 f6", "f3 g1"]);
+        assert_eq!(board.halfmove_clock(), 3);
+        assert!(board.to_fen().ends_with(" b KQkq - 3 2"));
+
+        // 走兵清零
+        play(&mut board, &["e7 e5"]);
+        assert!(board.to_fen().ends_with(" w KQkq - 0 3"));
+        play(&mut board, &["g1 f3", "b8 c6"]);
+        assert_eq!(board.halfmove_clock(), 2);
+        // 吃子清零
+        play(&mut board, &["f3 e5"]);
+        assert_eq!(board.halfmove_clock(), 0);
+
+        // 吃过路兵和升变都清零
+        let mut board = Chessboard::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 12 40").unwrap();
+        play(&mut board, &["e5 d6"]);
+        assert!(board.to_fen().ends_with(" b - - 0 40"));
+        let mut board = Chessboard::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 7 60").unwrap();
+        board
+            .make_move(&Move::from_notation("a7 a8 q").unwrap())
+            .unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+        assert_eq!(
+            Chessboard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 99 75")
+                .unwrap()
+                .to_fen(),
+            "4k3/8/8/8/8/8/8/4K3 w - - 99 75"
+        );
+    }
+
+    #[test]
+    fn notation_with_promotion_letter() {
+        let mv = Move::from_notation("e7 e8 q").unwrap();
+        assert!(matches!(mv.promotion, Some(Piece::Queen(Color::White))));
+        let mv = Move::from_notation("a2 a1 N").unwrap();
+        assert!(matches!(mv.promotion, Some(Piece::Knight(Color::Black))));
+        assert!(Move::from_notation("e2 e4").unwrap().promotion.is_none());
+
+        assert!(Move::from_notation("e7 e8 k").is_none());
+        assert!(Move::from_notation("e7 e8 queen").is_none());
+        // 终点不在底线时不能带升变字母
+        assert!(Move::from_notation("e6 e7 q").is_none());
+        assert!(Move::from_notation("e7 e8 q x").is_none());
+    }
+
+    #[test]
+    fn uci_move_strings() {
+        let mv = Move::from_uci("e2e4").unwrap();
+        assert_eq!(mv.to_notation(), "e2 e4");
+        assert_eq!(mv.to_uci(), "e2e4");
+
+        let mv = Move::from_uci("e7e8q").unwrap();
+        assert!(matches!(mv.promotion, Some(Piece::Queen(Color::White))));
+        assert_eq!(mv.to_uci(), "e7e8q");
+        let mv = Move::from_uci("b2a1N").unwrap();
+        assert!(matches!(mv.promotion, Some(Piece::Knight(Color::Black))));
+        assert_eq!(mv.to_uci(), "b2a1n");
+        let mv = Move::from_uci("a2a1n").unwrap();
+        assert!(matches!(mv.promotion, Some(Piece::Knight(Color::Black))));
+        assert_eq!(mv.to_uci(), "a2a1n");
+
+        for bad in [
+            "", "e2e", "e2e4e5", "e2 e4", "e7e8k", "e6e7q", "i2i4", "e9e4", "e2e４",
+        ] {
+            assert!(Move::from_uci(bad).is_none(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn fifty_move_rule_after_knight_shuffles() {
+        use super::status::DrawReason;
+
+        // 分析模式下三次重复不会结束对局，马来回跳满50回合
+        let mut board = Chessboard::new();
+        board.continue_from_here();
+        for _ in 0..24 {
+            play(&mut board, &["g1 f3", "g8 f6", "f3 g1", "f6 g8"]);
+        }
+        play(&mut board, &["g1 f3"]);
+        assert_eq!(board.halfmove_clock(), 97);
+        play(&mut board, &["g8 f6", "f3 g1"]);
+        assert!(!board.is_fifty_move_draw());
+        play(&mut board, &["f6 g8"]);
+        assert!(board.is_fifty_move_draw());
+        assert_eq!(
+            board.status(),
+            GameStatus::Draw {
+                reasons: vec![DrawReason::ThreefoldRepetition, DrawReason::FiftyMoveRule]
+            }
+        );
+
+        // 第100个半回合正好将死：将死优先
+        let mut mate = Chessboard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 99 80").unwrap();
+        play(&mut mate, &["a1 a8"]);
+        assert!(mate.is_fifty_move_draw());
+        assert_eq!(
+            mate.status(),
+            GameStatus::Checkmate {
+                winner: Color::White
+            }
+        );
+    }
+
+    #[test]
+    fn fifty_move_draw_ends_the_game_at_exactly_ply_100() {
+        // 白车在b2-h2（7格）、黑车在c7-h7（6格）上循环，42回合内局面不重复，不会先触发三次重复
+        let mut board = Chessboard::from_fen("k7/2r5/8/8/8/8/1R6/K7 w - - 0 1").unwrap();
+        let white = ["b2", "c2", "d2", "e2", "f2", "g2", "h2"];
+        let black = ["c7", "d7", "e7", "f7", "g7", "h7"];
+        for ply in 0..100usize {
+            assert_eq!(board.status(), GameStatus::Ongoing, "第{}个半回合", ply);
+            let step = ply / 2;
+            let (squares, from) = if ply.is_multiple_of(2) {
+                (&white[..], step % 7)
+            } else {
+                (&black[..], step % 6)
+            };
+            let to = (from + 1) % squares.len();
+            play(&mut board, &[&format!("{} {}", squares[from], squares[to])]);
+        }
+
+        assert_eq!(board.halfmove_clock(), 100);
+        assert!(board.is_fifty_move_draw());
+        assert!(board.is_game_over());
+        assert_eq!(
+            board.describe_status(Language::English),
+            "Draw by the fifty-move rule"
+        );
+        let mv = Move::from_notation("a1 b1").unwrap();
+        assert_eq!(board.make_move(&mv), Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn insufficient_material_configurations() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        ] {
+            let board = Chessboard::from_fen(fen).unwrap();
+            assert!(board.is_insufficient_material(), "{}", fen);
+            assert!(board.is_game_over(), "{}", fen);
+        }
+
+        for fen in [
+            "4k3/8/8/8/8/8/P7/4K3 w - - 0 1",
+            // 异色格象、马对象、双马都还有将杀的可能
+            "4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1",
+        ] {
+            let board = Chessboard::from_fen(fen).unwrap();
+            assert!(!board.is_insufficient_material(), "{}", fen);
+            assert_eq!(board.status(), GameStatus::Ongoing, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn capturing_a_home_rook_clears_castling_rights() {
+        let mut board = Chessboard::from_fen("r3k2r/p7/8/8/8/8/6N1/R3K2R w KQkq - 0 1").unwrap();
+        play(&mut board, &["g2 f4", "a7 a6", "f4 g6", "a6 a5", "g6 h8"]);
+        assert!(!board.castling_rights.black_kingside);
+        assert!(board.castling_rights.black_queenside);
+        let king = Position::from_notation("e8").unwrap();
+        assert!(board
+            .get_legal_moves(king)
+            .iter()
+            .all(|mv| mv.to.to_notation() != "g8"));
+        assert!(board
+            .to_fen()
+            .starts_with("r3k2N/8/8/p7/8/8/8/R3K2R b KQq -"));
+
+        // 不在原始角格的车移动不影响另一只车的易位权
+        let mut board = Chessboard::from_fen("4k3/8/8/8/R7/8/8/4K2R w K - 0 1").unwrap();
+        play(&mut board, &["a4 a5"]);
+        assert!(board.castling_rights.white_kingside);
+    }
+}
@@ -0,0 +1,626 @@
+use super::{Chessboard, Color, Move, Piece, Position, Square};
+use rand::Rng;
+
+// 当前走棋方合法走法的惰性迭代器：按 Square::ALL 的顺序（a1..h8）逐格生成候选走法，取到一步才试走一步，
+// 只需要知道"有没有"合法走法时（将死、逼和）找到第一步就可以停止
+pub struct LegalMoves<'a> {
+    board: &'a Chessboard,
+    scratch: Chessboard,
+    // 下一个要生成候选走法的格子在 Square::ALL 中的位置
+    square: usize,
+    pending: std::vec::IntoIter<Move>,
+}
+
+impl Iterator for LegalMoves<'_> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            for mv in self.pending.by_ref() {
+                if self.scratch.keeps_king_safe(&mv) {
+                    return Some(mv);
+                }
+            }
+            let from = *Square::ALL.get(self.square)?;
+            self.pending = self.board.pseudo_legal_moves(from.into()).into_iter();
+            self.square += 1;
+        }
+    }
+}
+
+impl Chessboard {
+    // 获取所有合法移动；认输或协议和棋后（分析模式除外）没有可走的棋，与 make_move 一致
+    pub fn get_legal_moves(&self, from: Position) -> Vec<Move> {
+        if self.accepts_no_moves() {
+            return Vec::new();
+        }
+        let moves = self.pseudo_legal_moves(from);
+        if moves.is_empty() {
+            return moves;
+        }
+        let mut scratch = self.scratch_copy();
+        moves
+            .into_iter()
+            .filter(|mv| scratch.keeps_king_safe(mv))
+            .collect()
+    }
+
+    // 当前走棋方合法走法的惰性迭代器；认输或协议和棋后（分析模式除外）为空
+    pub fn legal_moves(&self) -> LegalMoves<'_> {
+        let mut moves = self.rule_moves();
+        if self.accepts_no_moves() {
+            moves.square = Square::ALL.len();
+        }
+        moves
+    }
+
+    // 只按规则生成的合法走法，不管对局是否已经以认输或协议和棋结束
+    fn rule_moves(&self) -> LegalMoves<'_> {
+        LegalMoves {
+            board: self,
+            scratch: self.scratch_copy(),
+            square: 0,
+            pending: Vec::new().into_iter(),
+        }
+    }
+
+    // 对局已以认输或协议和棋结束，且不在分析模式：make_move 会拒绝任何走法
+    fn accepts_no_moves(&self) -> bool {
+        self.concluded.is_some() && !self.analysis
+    }
+
+    // 当前走棋方按规则是否至少有一步合法走法；找到第一步就停止，供将死/逼和判定使用
+    pub fn has_legal_move(&self) -> bool {
+        self.rule_moves().next().is_some()
+    }
+
+    // 试走一步再撤销，判断走后走子方的王是否安全；走完后局面与原来相同
+    fn keeps_king_safe(&mut self, mv: &Move) -> bool {
+        let color = self.current_turn;
+        // 起点没有棋子的走法走不出来，不算合法
+        let Ok(undo) = self.make_move_unchecked(mv) else {
+            return false;
+        };
+        let safe = !self.is_in_check(color);
+        self.undo_move(mv, undo);
+        safe
+    }
+
+    // 只含局面本身的副本：不复制走子历史、局面历史和悔棋记录，
+    // 供试走使用（走子再撤销，不关心重复局面）
+    pub(super) fn scratch_copy(&self) -> Chessboard {
+        Chessboard {
+            board: self.board,
+            bitboards: self.bitboards,
+            current_turn: self.current_turn,
+            castling_rights: self.castling_rights,
+            en_passant_target: self.en_passant_target,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            move_history: Vec::new(),
+            position_history: Vec::new(),
+            undo_stack: Vec::new(),
+            concluded: self.concluded,
+            analysis: self.analysis,
+            strict: self.strict,
+        }
+    }
+
+    // 按棋子走法规则能走的全部走法，不考虑走后己方王是否被将军
+    pub(super) fn pseudo_legal_moves(&self, from: Position) -> Vec<Move> {
+        let mut moves = Vec::new();
+
+        let piece = match self.get(from) {
+            Some(piece) => piece,
+            None => return moves,
+        };
+
+        if piece.color() != self.current_turn {
+            return moves;
+        }
+
+        match piece {
+            Piece::Pawn(color, _) => self.pawn_moves(from, color, &mut moves),
+            Piece::Knight(color) => self.knight_moves(from, color, &mut moves),
+            Piece::Bishop(color) => self.bishop_moves(from, color, &mut moves),
+            Piece::Rook(color, _) => self.rook_moves(from, color, &mut moves),
+            Piece::Queen(color) => self.queen_moves(from, color, &mut moves),
+            Piece::King(color, _) => self.king_moves(from, color, &mut moves),
+        }
+        moves
+    }
+
+    // 当前走棋方的全部合法走法，搜索、随机走子和终局判定都从这里取走法
+    pub fn get_all_legal_moves(&self) -> Vec<Move> {
+        self.legal_moves().collect()
+    }
+
+    // 从当前局面向下depth层的叶子节点数，用于校验走法生成和性能测试
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.get_all_legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        self.perft_divide(depth)
+            .iter()
+            .map(|(_, nodes)| nodes)
+            .sum()
+    }
+
+    // 每个根走法之下的perft节点数；与其他引擎的同名输出逐项比对，可以定位出错的走法
+    pub fn perft_divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let mut child = self.scratch_copy();
+        self.get_all_legal_moves()
+            .into_iter()
+            .filter_map(|mv| {
+                let undo = child.make_move_unchecked(&mv).ok()?;
+                let nodes = child.perft(depth - 1);
+                child.undo_move(&mv, undo);
+                Some((mv, nodes))
+            })
+            .collect()
+    }
+
+    // 随机合法走法（新增方法）
+    pub fn get_random_legal_move(&self) -> Option<Move> {
+        let legal_moves = self.get_all_legal_moves();
+        if legal_moves.is_empty() {
+            return None;
+        }
+
+        // 随机选择一个走法
+        let mut rng = rand::rng();
+        let random_index = rng.random_range(0..legal_moves.len());
+        Some(legal_moves[random_index].clone())
+    }
+
+    // 过路兵目标格的规范化：只有走棋方确实有合法的吃过路兵走法时才保留，否则清除
+    // （与Stockfish、python-chess的规范化一致）；外部导入的FEN和对局中兵走两格之后都经过这里，
+    // 这样FEN输出、Zobrist哈希和重复局面判定只取决于实际可走的棋。
+    // 吃过路兵会让王沿横排暴露给车/后时（"过路兵牵制"），目标格同样被清除
+    pub(super) fn normalize_en_passant(&mut self) {
+        let Some(target) = self.en_passant_target else {
+            return;
+        };
+        let color = self.current_turn;
+        // 吃子的兵所在横排、被吃的兵出发的格子
+        let (pawn_row, origin_row) = match color {
+            Color::White => (target.row + 1, target.row.checked_sub(1)),
+            Color::Black => (target.row.wrapping_sub(1), Some(target.row + 1)),
+        };
+        let squares_empty = self.get(target).is_none()
+            && origin_row
+                .and_then(|row| Position::new(row, target.col))
+                .is_some_and(|origin| self.get(origin).is_none());
+        let capturable = squares_empty
+            && [target.col.wrapping_sub(1), target.col + 1]
+                .into_iter()
+                .filter_map(|col| Position::new(pawn_row, col))
+                .filter(|&from| matches!(self.get(from), Some(Piece::Pawn(c, _)) if c == color))
+                .any(|from| self.get_legal_moves(from).iter().any(|mv| mv.to == target));
+        if !capturable {
+            self.en_passant_target = None;
+        }
+    }
+
+    // 兵的移动逻辑
+    fn pawn_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
+        let direction = match color {
+            Color::White => -1,
+            Color::Black => 1,
+        };
+
+        let new_row = from.row as i32 + direction;
+        if !(0..8).contains(&new_row) {
+            return;
+        }
+
+        let new_row = new_row as usize;
+
+        // 前进一格
+        if self.board[new_row][from.col].is_none() {
+            self.add_pawn_move(from, new_row, from.col, color, moves);
+
+            // 前进两格（初始位置）
+            let start_row = match color {
+                Color::White => 6,
+                Color::Black => 1,
+            };
+            if from.row == start_row {
+                let double_row = (from.row as i32 + 2 * direction) as usize;
+                if self.board[double_row][from.col].is_none() {
+                    moves.push(Move {
+                        from,
+                        to: Position {
+                            row: double_row,
+                            col: from.col,
+                        },
+                        promotion: None,
+                    });
+                }
+            }
+        }
+
+        // 吃子（左侧）
+        if from.col > 0 {
+            let left_col = from.col - 1;
+            if self.can_capture(Position::new(new_row, left_col).unwrap(), color) {
+                self.add_pawn_move(from, new_row, left_col, color, moves);
+            }
+        }
+
+        // 吃子（右侧）
+        if from.col < 7 {
+            let right_col = from.col + 1;
+            if self.can_capture(Position::new(new_row, right_col).unwrap(), color) {
+                self.add_pawn_move(from, new_row, right_col, color, moves);
+            }
+        }
+
+        // 吃过路兵
+        if let Some(en_passant_pos) = self.en_passant_target {
+            if en_passant_pos.row == new_row
+                && (en_passant_pos.col as i32 - from.col as i32).abs() == 1
+            {
+                let en_passant_direction = match color {
+                    Color::White => -1,
+                    Color::Black => 1,
+                };
+                let pawn_behind_row = (en_passant_pos.row as i32 - en_passant_direction) as usize;
+
+                if let Some(Piece::Pawn(opponent_color, _)) =
+                    self.board[pawn_behind_row][en_passant_pos.col]
+                {
+                    if opponent_color != color {
+                        moves.push(Move {
+                            from,
+                            to: en_passant_pos,
+                            promotion: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_pawn_move(
+        &self,
+        from: Position,
+        to_row: usize,
+        to_col: usize,
+        color: Color,
+        moves: &mut Vec<Move>,
+    ) {
+        let promotion_row = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+
+        if to_row == promotion_row {
+            // 升变选择
+            let promotions = [
+                Piece::Queen(color),
+                Piece::Rook(color, true),
+                Piece::Bishop(color),
+                Piece::Knight(color),
+            ];
+            for &promotion in &promotions {
+                moves.push(Move {
+                    from,
+                    to: Position {
+                        row: to_row,
+                        col: to_col,
+                    },
+                    promotion: Some(promotion),
+                });
+            }
+        } else {
+            moves.push(Move {
+                from,
+                to: Position {
+                    row: to_row,
+                    col: to_col,
+                },
+                promotion: None,
+            });
+        }
+    }
+
+    // 马的移动逻辑
+    fn knight_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
+        let knight_moves = [
+            (-2, -1),
+            (-2, 1),
+            (-1, -2),
+            (-1, 2),
+            (1, -2),
+            (1, 2),
+            (2, -1),
+            (2, 1),
+        ];
+
+        for &(dr, dc) in &knight_moves {
+            let new_row = from.row as i32 + dr;
+            let new_col = from.col as i32 + dc;
+
+            if (0..8).contains(&new_row) && (0..8).contains(&new_col) {
+                let new_row = new_row as usize;
+                let new_col = new_col as usize;
+                let to_pos = Position::new(new_row, new_col).unwrap();
+
+                if self.can_move_to(to_pos, color) {
+                    moves.push(Move {
+                        from,
+                        to: to_pos,
+                        promotion: None,
+                    });
+                }
+            }
+        }
+    }
+
+    // 象的移动逻辑
+    fn bishop_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
+        let directions = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+        self.sliding_moves(from, color, &directions, moves);
+    }
+
+    // 车的移动逻辑
+    fn rook_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
+        let directions = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+        self.sliding_moves(from, color, &directions, moves);
+    }
+
+    // 后的移动逻辑
+    fn queen_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
+        let directions = [
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+        ];
+        self.sliding_moves(from, color, &directions, moves);
+    }
+
+    // 王的移动逻辑（包括王车易位）
+    fn king_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
+        let king_moves = [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ];
+
+        for &(dr, dc) in &king_moves {
+            let new_row = from.row as i32 + dr;
+            let new_col = from.col as i32 + dc;
+
+            if (0..8).contains(&new_row) && (0..8).contains(&new_col) {
+                let new_row = new_row as usize;
+                let new_col = new_col as usize;
+                let to_pos = Position::new(new_row, new_col).unwrap();
+
+                if self.can_move_to(to_pos, color) {
+                    moves.push(Move {
+                        from,
+                        to: to_pos,
+                        promotion: None,
+                    });
+                }
+            }
+        }
+
+        // 王车易位
+        self.castling_moves(from, color, moves);
+    }
+
+    // 王车易位逻辑
+    fn castling_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
+        if self.is_in_check(color) {
+            return;
+        }
+
+        let (kingside_right, queenside_right, back_rank) = match color {
+            Color::White => (
+                self.castling_rights.white_kingside,
+                self.castling_rights.white_queenside,
+                7,
+            ),
+            Color::Black => (
+                self.castling_rights.black_kingside,
+                self.castling_rights.black_queenside,
+                0,
+            ),
+        };
+
+        // 短易位（王翼易位）
+        if kingside_right
+            && self.board[back_rank][5].is_none()
+            && self.board[back_rank][6].is_none()
+            && !self.is_square_attacked(Position::new(back_rank, 4).unwrap(), color.opposite())
+            && !self.is_square_attacked(Position::new(back_rank, 5).unwrap(), color.opposite())
+            && !self.is_square_attacked(Position::new(back_rank, 6).unwrap(), color.opposite())
+        {
+            moves.push(Move {
+                from,
+                to: Position {
+                    row: back_rank,
+                    col: 6,
+                },
+                promotion: None,
+            });
+        }
+
+        // 长易位（后翼易位）
+        if queenside_right
+            && self.board[back_rank][1].is_none()
+            && self.board[back_rank][2].is_none()
+            && self.board[back_rank][3].is_none()
+            && !self.is_square_attacked(Position::new(back_rank, 2).unwrap(), color.opposite())
+            && !self.is_square_attacked(Position::new(back_rank, 3).unwrap(), color.opposite())
+            && !self.is_square_attacked(Position::new(back_rank, 4).unwrap(), color.opposite())
+        {
+            moves.push(Move {
+                from,
+                to: Position {
+                    row: back_rank,
+                    col: 2,
+                },
+                promotion: None,
+            });
+        }
+    }
+
+    // 滑动棋子（象、车、后）的通用移动逻辑
+    fn sliding_moves(
+        &self,
+        from: Position,
+        color: Color,
+        directions: &[(i32, i32)],
+        moves: &mut Vec<Move>,
+    ) {
+        for &(dr, dc) in directions {
+            let mut new_row = from.row as i32 + dr;
+            let mut new_col = from.col as i32 + dc;
+
+            while (0..8).contains(&new_row) && (0..8).contains(&new_col) {
+                let new_row_usize = new_row as usize;
+                let new_col_usize = new_col as usize;
+                let to_pos = Position::new(new_row_usize, new_col_usize).unwrap();
+
+                if self.board[new_row_usize][new_col_usize].is_none() {
+                    moves.push(Move {
+                        from,
+                        to: to_pos,
+                        promotion: None,
+                    });
+                } else {
+                    if self.can_capture(to_pos, color) {
+                        moves.push(Move {
+                            from,
+                            to: to_pos,
+                            promotion: None,
+                        });
+                    }
+                    break;
+                }
+
+                new_row += dr;
+                new_col += dc;
+            }
+        }
+    }
+
+    fn can_move_to(&self, to: Position, color: Color) -> bool {
+        match self.board[to.row][to.col] {
+            Some(piece) => piece.color() != color,
+            None => true,
+        }
+    }
+
+    fn can_capture(&self, to: Position, color: Color) -> bool {
+        match self.board[to.row][to.col] {
+            Some(piece) => piece.color() != color,
+            None => false,
+        }
+    }
+
+    // 列出by_color一方所有攻击pos的棋子位置（与is_square_attacked使用相同的攻击规则）
+    pub fn attackers_of(&self, pos: Position, by_color: Color) -> Vec<Position> {
+        let mut attackers = Vec::new();
+
+        let knight_moves = [
+            (-2, -1),
+            (-2, 1),
+            (-1, -2),
+            (-1, 2),
+            (1, -2),
+            (1, 2),
+            (2, -1),
+            (2, 1),
+        ];
+        let king_moves = [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ];
+        let pawn_direction = match by_color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+
+        // 马、王、兵：单步攻击
+        let mut check_step = |dr: i32, dc: i32, matches: &dyn Fn(Piece) -> bool| {
+            let new_row = pos.row as i32 + dr;
+            let new_col = pos.col as i32 + dc;
+            if (0..8).contains(&new_row) && (0..8).contains(&new_col) {
+                let from = Position::new(new_row as usize, new_col as usize).unwrap();
+                if let Some(piece) = self.get(from) {
+                    if piece.color() == by_color && matches(piece) {
+                        attackers.push(from);
+                    }
+                }
+            }
+        };
+        for &(dr, dc) in &knight_moves {
+            check_step(dr, dc, &|piece| matches!(piece, Piece::Knight(_)));
+        }
+        for &(dr, dc) in &king_moves {
+            check_step(dr, dc, &|piece| matches!(piece, Piece::King(_, _)));
+        }
+        for &dc in &[-1, 1] {
+            check_step(pawn_direction, dc, &|piece| {
+                matches!(piece, Piece::Pawn(_, _))
+            });
+        }
+
+        // 滑动棋子
+        for &(dr, dc) in &king_moves {
+            let mut new_row = pos.row as i32 + dr;
+            let mut new_col = pos.col as i32 + dc;
+
+            while (0..8).contains(&new_row) && (0..8).contains(&new_col) {
+                let from = Position::new(new_row as usize, new_col as usize).unwrap();
+                if let Some(piece) = self.get(from) {
+                    if piece.color() == by_color {
+                        match piece {
+                            Piece::Queen(_) => attackers.push(from),
+                            Piece::Rook(_, _) if dr == 0 || dc == 0 => attackers.push(from),
+                            Piece::Bishop(_) if dr != 0 && dc != 0 => attackers.push(from),
+                            _ => (),
+                        }
+                    }
+                    break;
+                }
+                new_row += dr;
+                new_col += dc;
+            }
+        }
+
+        attackers
+    }
+
+    pub(super) fn is_square_attacked(&self, pos: Position, by_color: Color) -> bool {
+        self.bitboards.is_attacked(pos, by_color)
+    }
+}
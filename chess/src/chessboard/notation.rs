@@ -0,0 +1,199 @@
+use super::{Chessboard, Color, Move, Piece, Position};
+use crate::promotion::piece_from_letter;
+
+// 外部输入的走法统一在这里解析：CLI、API回复以及今后的UCI、PGN/SAN导入共用
+// 王车易位的各种写法（O-O、0-0、e1g1、Chess960式的王吃车 e1h1、
+// "castle kingside" 等文字描述）都规范为内部表示：王横走两格
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CastleSide {
+    King,
+    Queen,
+}
+
+impl Chessboard {
+    // 解析当前走棋方的一步走法；易位写法在当前局面不能易位时返回错误，
+    // 普通走法只检查格式，合法性仍由make_move判断
+    pub fn parse_move(&self, text: &str) -> Result<Move, String> {
+        let text = text.trim();
+        if let Some(side) = castle_side_from_text(text) {
+            return self.castle_move(side);
+        }
+
+        // SAN（Nf3、exd5、e8=Q）优先，与坐标记法不会混淆；
+        // 也不是坐标记法时报告SAN的错误（有歧义、没有棋子能走到等）
+        let san_error = match self.parse_san(text) {
+            Ok(mv) => return Ok(mv),
+            Err(e) => e,
+        };
+
+        let compact: String = text.split_whitespace().collect();
+        if compact.len() < 4 || !compact.is_ascii() {
+            return Err(san_error);
+        }
+        let mut mv = Move::from_notation(&format!("{} {}", &compact[..2], &compact[2..4]))
+            .ok_or(san_error)?;
+        let (from, to) = (mv.from, mv.to);
+
+        if let Some(side) = self.castle_side_of(from, to) {
+            return self.castle_move(side);
+        }
+
+        // 终点后可跟升变字母：UCI式的 e7e8q 或 "e7 e8 q"；升变棋子与起点棋子同色
+        let color = self
+            .get(from)
+            .map_or(self.current_turn, |piece| piece.color());
+        mv.promotion = match &compact[4..] {
+            "" => None,
+            letter => Some(
+                piece_from_letter(letter, color)
+                    .ok_or_else(|| format!("无效的升变棋子: {}", letter))?,
+            ),
+        };
+        Ok(mv)
+    }
+
+    // 走棋方的王从初始格出发、横走两格或走到己方车上时视为易位
+    fn castle_side_of(&self, from: Position, to: Position) -> Option<CastleSide> {
+        let color = match self.get(from) {
+            Some(Piece::King(color, _)) if color == self.current_turn => color,
+            _ => return None,
+        };
+        let back_rank = match color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+        if from.row != back_rank || from.col != 4 || to.row != back_rank {
+            return None;
+        }
+
+        let onto_own_rook =
+            matches!(self.get(to), Some(Piece::Rook(rook_color, _)) if rook_color == color);
+        match to.col {
+            6 => Some(CastleSide::King),
+            2 => Some(CastleSide::Queen),
+            7 if onto_own_rook => Some(CastleSide::King),
+            0 if onto_own_rook => Some(CastleSide::Queen),
+            _ => None,
+        }
+    }
+
+    // 当前走棋方向side一侧易位的内部走法，不合法时返回错误
+    fn castle_move(&self, side: CastleSide) -> Result<Move, String> {
+        let from = self.find_king(self.current_turn);
+        let to_col = match side {
+            CastleSide::King => 6,
+            CastleSide::Queen => 2,
+        };
+
+        if from.col == 4 {
+            let to = Position {
+                row: from.row,
+                col: to_col,
+            };
+            if let Some(mv) = self
+                .get_legal_moves(from)
+                .into_iter()
+                .find(|mv| mv.to == to)
+            {
+                return Ok(mv);
+            }
+        }
+
+        Err(match side {
+            CastleSide::King => "当前局面不能短易位".to_string(),
+            CastleSide::Queen => "当前局面不能长易位".to_string(),
+        })
+    }
+}
+
+// 识别文字形式的易位：O-O / 0-0 / O-O-O（可带 + 或 #），以及英文描述
+fn castle_side_from_text(text: &str) -> Option<CastleSide> {
+    let normalized = text
+        .trim_end_matches(['+', '#', '!', '?', '.'])
+        .to_ascii_lowercase()
+        .replace('0', "o");
+    match normalized.as_str() {
+        "o-o" | "oo" => return Some(CastleSide::King),
+        "o-o-o" | "ooo" => return Some(CastleSide::Queen),
+        _ => {}
+    }
+
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    let castles = words.iter().any(|word| word.starts_with("castl"));
+    if !castles {
+        return None;
+    }
+    if words
+        .iter()
+        .any(|&word| matches!(word, "kingside" | "king-side" | "short"))
+    {
+        Some(CastleSide::King)
+    } else if words
+        .iter()
+        .any(|&word| matches!(word, "queenside" | "queen-side" | "long"))
+    {
+        Some(CastleSide::Queen)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_castling_spelling_normalizes_to_king_two_squares() {
+        let white = Chessboard::from_minimal_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq -").unwrap();
+        let black = Chessboard::from_minimal_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq -").unwrap();
+        let cases = [
+            (&white, "O-O", "e1 g1"),
+            (&white, "0-0", "e1 g1"),
+            (&white, "o-o+", "e1 g1"),
+            (&white, "e1g1", "e1 g1"),
+            (&white, "e1 g1", "e1 g1"),
+            (&white, "e1h1", "e1 g1"),
+            (&white, "castle kingside", "e1 g1"),
+            (&white, "Castles king-side.", "e1 g1"),
+            (&white, "short castle", "e1 g1"),
+            (&white, "O-O-O", "e1 c1"),
+            (&white, "0-0-0#", "e1 c1"),
+            (&white, "e1c1", "e1 c1"),
+            (&white, "e1a1", "e1 c1"),
+            (&white, "castle queenside", "e1 c1"),
+            (&white, "long castle", "e1 c1"),
+            (&black, "O-O", "e8 g8"),
+            (&black, "e8h8", "e8 g8"),
+            (&black, "0-0-0", "e8 c8"),
+            (&black, "e8a8", "e8 c8"),
+        ];
+
+        for (board, spelling, expected) in cases {
+            let mv = board
+                .parse_move(spelling)
+                .unwrap_or_else(|e| panic!("{}: {}", spelling, e));
+            assert_eq!(mv.to_notation(), expected, "{}", spelling);
+            let mut played = board.clone();
+            played.make_move(&mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn castling_spellings_rejected_without_rights() {
+        let board = Chessboard::from_minimal_fen("r3k2r/8/8/8/8/8/8/R3K2R w - -").unwrap();
+        for spelling in ["O-O", "0-0-0", "e1g1", "e1h1", "e1a1", "castle kingside"] {
+            assert!(board.parse_move(spelling).is_err(), "{}", spelling);
+        }
+
+        // 普通走法和UCI升变照常解析
+        let mv = board.parse_move("e1f1").unwrap();
+        assert_eq!(mv.to_notation(), "e1 f1");
+        let pawn = Chessboard::from_minimal_fen("4k3/P7/8/8/8/8/8/4K3 w - -").unwrap();
+        assert_eq!(
+            pawn.parse_move("a7a8n").unwrap().promotion,
+            Some(Piece::Knight(Color::White))
+        );
+        assert!(pawn.parse_move("hello").is_err());
+    }
+}
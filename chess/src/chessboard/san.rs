@@ -0,0 +1,272 @@
+use super::{Chessboard, Move, Piece, Position};
+
+// 标准代数记谱(SAN)：Nf3、exd5、O-O、e8=Q、Qxd7+、Qh4#
+// 走子历史和PGN导出都用SAN；读入时按当前局面的合法走法逐一生成SAN比对，
+// 这样消歧义、吃过路兵等规则只在 move_to_san 一处实现
+
+// 棋子字母（兵没有字母）
+fn piece_letter(piece: Piece) -> &'static str {
+    match piece {
+        Piece::King(_, _) => "K",
+        Piece::Queen(_) => "Q",
+        Piece::Rook(_, _) => "R",
+        Piece::Bishop(_) => "B",
+        Piece::Knight(_) => "N",
+        Piece::Pawn(_, _) => "",
+    }
+}
+
+// SAN棋子字母对应的中文名称（兵没有字母）
+fn letter_name(letter: &str) -> &'static str {
+    match letter {
+        "K" => "王",
+        "Q" => "后",
+        "R" => "车",
+        "B" => "象",
+        "N" => "马",
+        _ => "兵",
+    }
+}
+
+// 比较时忽略将军/将杀后缀、注释符号和升变的等号（e8Q 与 e8=Q 视为相同）
+fn normalize(san: &str) -> String {
+    san.trim()
+        .trim_end_matches(['+', '#', '!', '?'])
+        .replace('=', "")
+}
+
+impl Chessboard {
+    // 当前局面下一步合法走法的SAN
+    pub fn move_to_san(&self, mv: &Move) -> String {
+        let piece = match self.get(mv.from) {
+            Some(piece) => piece,
+            None => return mv.to_notation(),
+        };
+
+        let mut san = if self.castle_rook_movement(mv).is_some() {
+            match mv.to.col {
+                6 => "O-O".to_string(),
+                _ => "O-O-O".to_string(),
+            }
+        } else {
+            // 兵斜走一定是吃子（包括吃过路兵，终点为空）
+            let is_pawn = matches!(piece, Piece::Pawn(_, _));
+            let capture = self.get(mv.to).is_some() || (is_pawn && mv.from.col != mv.to.col);
+            let mut san = piece_letter(piece).to_string();
+            if is_pawn {
+                if capture {
+                    san.push((b'a' + mv.from.file() as u8) as char);
+                }
+            } else {
+                san.push_str(&self.disambiguation(piece, mv));
+            }
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&mv.to.to_notation());
+            if let Some(promotion) = mv.promotion {
+                san.push('=');
+                san.push_str(piece_letter(promotion));
+            }
+            san
+        };
+
+        let mut after = self.clone();
+        if after.make_move_unchecked(mv).is_ok() && after.is_in_check(after.current_turn) {
+            san.push(if !after.has_legal_move() { '#' } else { '+' });
+        }
+        san
+    }
+
+    // 同种棋子有另一个也能走到终点时，先用起点列区分，列相同再用横排，都相同时两者都写
+    fn disambiguation(&self, piece: Piece, mv: &Move) -> String {
+        let rivals: Vec<_> = self
+            .get_all_legal_moves()
+            .into_iter()
+            .filter(|other| other.to == mv.to && other.from != mv.from)
+            .filter(|other| {
+                self.get(other.from)
+                    .is_some_and(|rival| piece_letter(rival) == piece_letter(piece))
+            })
+            .map(|other| other.from)
+            .collect();
+        if rivals.is_empty() {
+            return String::new();
+        }
+
+        let from = mv.from.to_notation();
+        if rivals.iter().all(|rival| rival.col != mv.from.col) {
+            from[..1].to_string()
+        } else if rivals.iter().all(|rival| rival.row != mv.from.row) {
+            from[1..].to_string()
+        } else {
+            from
+        }
+    }
+
+    // 按SAN找到当前局面下对应的合法走法；找不到时说明原因（有歧义、没有棋子能走到、写法不对）
+    pub fn parse_san(&self, text: &str) -> Result<Move, String> {
+        let wanted = normalize(text);
+        let moves = self.get_all_legal_moves();
+        if let Some(mv) = moves
+            .iter()
+            .find(|mv| !wanted.is_empty() && normalize(&self.move_to_san(mv)) == wanted)
+        {
+            return Ok(mv.clone());
+        }
+
+        // 按棋子字母和终点找候选走法，判断错在哪里
+        let letter = match wanted.chars().next() {
+            Some(c @ ('K' | 'Q' | 'R' | 'B' | 'N')) => c.to_string(),
+            _ => String::new(),
+        };
+        let target = (0..wanted.len().saturating_sub(1))
+            .rev()
+            .filter_map(|i| wanted.get(i..i + 2))
+            .find_map(Position::from_notation)
+            .ok_or_else(|| format!("无效的移动格式: {}", text.trim()))?;
+        let mut candidates: Vec<&Move> = moves
+            .iter()
+            .filter(|mv| mv.to == target)
+            .filter(|mv| {
+                self.get(mv.from)
+                    .is_some_and(|piece| piece_letter(piece) == letter)
+            })
+            .collect();
+        // 升变有四种走法，按起点去重
+        candidates.dedup_by_key(|mv| mv.from);
+
+        let square = target.to_notation();
+        let suggestion = |mv: &Move| {
+            self.move_to_san(mv)
+                .trim_end_matches(['+', '#'])
+                .to_string()
+        };
+        match candidates.as_slice() {
+            [] => Err(format!(
+                "没有{}能走到{}: {}",
+                letter_name(&letter),
+                square,
+                text.trim()
+            )),
+            [mv] => Err(format!(
+                "{} 写法不对，是否想走 {}？",
+                text.trim(),
+                suggestion(mv)
+            )),
+            many => {
+                let count = match many.len() {
+                    2 => "两".to_string(),
+                    n => n.to_string(),
+                };
+                let options: Vec<String> = many.iter().map(|mv| suggestion(mv)).collect();
+                Err(format!(
+                    "有{}个{}都能走到{}，请写成 {}",
+                    count,
+                    letter_name(&letter),
+                    square,
+                    options.join(" 或 ")
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chessboard::Color;
+
+    fn san(fen: &str, notation: &str) -> String {
+        let board = Chessboard::from_minimal_fen(fen).unwrap();
+        let mv = board.parse_move(notation).unwrap();
+        board.move_to_san(&mv)
+    }
+
+    #[test]
+    fn san_for_each_kind_of_move() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -";
+        assert_eq!(san(start, "e2 e4"), "e4");
+        assert_eq!(san(start, "g1 f3"), "Nf3");
+
+        // 吃子、吃过路兵
+        assert_eq!(san("4k3/8/8/3p4/4P3/8/8/4K3 w - -", "e4 d5"), "exd5");
+        assert_eq!(san("4k3/8/8/3pP3/8/8/8/4K3 w - d6", "e5 d6"), "exd6");
+        assert_eq!(san("3qk3/8/8/8/8/8/8/3QK3 w - -", "d1 d8"), "Qxd8+");
+
+        // 易位
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R w KQkq -", "e1 g1"), "O-O");
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R b KQkq -", "e8 c8"), "O-O-O");
+
+        // 升变，可同时吃子和将军
+        assert_eq!(san("1n2k3/P7/8/8/8/8/8/4K3 w - -", "a7 a8 q"), "a8=Q");
+        assert_eq!(san("1n5k/P7/8/8/8/8/8/4K3 w - -", "a7 b8 q"), "axb8=Q+");
+        assert_eq!(san("4k3/8/8/8/8/8/p7/4K3 b - -", "a2 a1 n"), "a1=N");
+
+        // 消歧义：按列、按横排、列和横排都写
+        assert_eq!(san("4k3/8/8/8/8/8/8/1N2KN2 w - -", "b1 d2"), "Nbd2");
+        assert_eq!(san("4k3/R7/8/8/8/8/8/R3K3 w - -", "a1 a4"), "R1a4");
+        assert_eq!(san("1k6/8/8/8/4Q2Q/8/K7/7Q w - -", "h4 e1"), "Qh4e1");
+        // 被牵制的马不能走，不需要消歧义
+        assert_eq!(san("4k3/4r3/8/8/8/8/4N3/1N2K3 w - -", "b1 c3"), "Nc3");
+
+        // 将杀
+        let mut board = Chessboard::new();
+        for notation in ["f2 f3", "e7 e5", "g2 g4"] {
+            board
+                .make_move(&Move::from_notation(notation).unwrap())
+                .unwrap();
+        }
+        let mate = Move::from_notation("d8 h4").unwrap();
+        assert_eq!(board.move_to_san(&mate), "Qh4#");
+        board.make_move(&mate).unwrap();
+        assert_eq!(board.move_history(), ["f3", "e5", "g4", "Qh4#"]);
+    }
+
+    #[test]
+    fn san_is_read_back() {
+        let board = Chessboard::from_minimal_fen("4k3/P7/8/8/8/8/8/1N2KN2 w - -").unwrap();
+        let mv = board.parse_move("Nbd2").unwrap();
+        assert_eq!(mv.to_notation(), "b1 d2");
+        assert!(board.parse_move("Nd2").is_err());
+        assert_eq!(
+            board.parse_move("a8=R+").unwrap().promotion,
+            Some(Piece::Rook(Color::White, true))
+        );
+        assert_eq!(
+            board.parse_move("a8Q").unwrap().promotion,
+            Some(Piece::Queen(Color::White))
+        );
+        assert!(board.parse_move("Ke3").is_err());
+    }
+
+    #[test]
+    fn san_errors_explain_the_problem() {
+        let board = Chessboard::from_minimal_fen("4k3/P7/8/8/8/8/8/1N2KN2 w - -").unwrap();
+        assert_eq!(
+            board.parse_move("Nd2").unwrap_err(),
+            "有两个马都能走到d2，请写成 Nbd2 或 Nfd2"
+        );
+        assert_eq!(board.parse_move("Nd5").unwrap_err(), "没有马能走到d5: Nd5");
+        assert_eq!(
+            board.parse_move("Nxd2").unwrap_err(),
+            "有两个马都能走到d2，请写成 Nbd2 或 Nfd2"
+        );
+        assert!(board
+            .parse_move("a8")
+            .unwrap_err()
+            .starts_with("a8 写法不对"));
+        assert_eq!(
+            board.parse_move("hello").unwrap_err(),
+            "无效的移动格式: hello"
+        );
+
+        let start = Chessboard::new();
+        assert_eq!(
+            start.parse_move("Nxf3").unwrap_err(),
+            "Nxf3 写法不对，是否想走 Nf3？"
+        );
+        // 坐标记法不受SAN报错影响
+        assert_eq!(start.parse_move("g1 f3").unwrap().to_notation(), "g1 f3");
+    }
+}
@@ -0,0 +1,97 @@
+use super::Position;
+
+// 0..64 的紧凑格子编号，供按格子索引的表（Zobrist随机数等）使用；对外仍以 Position 为准
+// 编号顺序固定为 a1=0, b1=1, ..., h1=7, a2=8, ..., h8=63：
+// 先沿横排从a列到h列，再从第1横排（白方底线）到第8横排，即 index = (rank-1)*8 + file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Square(u8);
+
+impl Square {
+    // 全部64格，按上述编号顺序（a1..h8）
+    pub const ALL: [Square; 64] = {
+        let mut all = [Square(0); 64];
+        let mut index = 0;
+        while index < 64 {
+            all[index] = Square(index as u8);
+            index += 1;
+        }
+        all
+    };
+
+    pub fn new(index: u8) -> Option<Self> {
+        (index < 64).then_some(Square(index))
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    // 列号，0对应a列
+    pub fn file(self) -> usize {
+        self.index() % 8
+    }
+
+    // 横排号，1-8
+    pub fn rank(self) -> usize {
+        self.index() / 8 + 1
+    }
+
+    pub fn from_notation(notation: &str) -> Option<Self> {
+        Position::from_notation(notation).map(Square::from)
+    }
+
+    pub fn to_notation(self) -> String {
+        Position::from(self).to_notation()
+    }
+}
+
+impl From<Position> for Square {
+    fn from(pos: Position) -> Self {
+        Square(((pos.rank() - 1) * 8 + pos.file()) as u8)
+    }
+}
+
+impl From<Square> for Position {
+    fn from(square: Square) -> Self {
+        Position::from_file_rank(square.file(), square.rank()).expect("格子编号在0..64之内")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_and_position_agree_on_every_square() {
+        assert_eq!(Square::ALL.len(), 64);
+        for (index, square) in Square::ALL.into_iter().enumerate() {
+            assert_eq!(square.index(), index);
+            assert_eq!(Square::new(index as u8), Some(square));
+
+            let pos = Position::from(square);
+            assert_eq!(Square::from(pos), square);
+            assert_eq!((pos.file(), pos.rank()), (square.file(), square.rank()));
+            assert_eq!(square.to_notation(), pos.to_notation());
+            assert_eq!(Square::from_notation(&pos.to_notation()), Some(square));
+        }
+        for row in 0..8 {
+            for col in 0..8 {
+                let pos = Position::new(row, col).unwrap();
+                assert_eq!(Position::from(Square::from(pos)), pos);
+            }
+        }
+        assert_eq!(Square::new(64), None);
+        assert_eq!(Square::from_notation("i1"), None);
+    }
+
+    #[test]
+    fn documented_order_is_a1_to_h8() {
+        let names: Vec<String> = Square::ALL.iter().map(|sq| sq.to_notation()).collect();
+        assert_eq!(names[0], "a1");
+        assert_eq!(names[7], "h1");
+        assert_eq!(names[8], "a2");
+        assert_eq!(names[63], "h8");
+        assert_eq!(Square::from_notation("e4").unwrap().index(), 28);
+        assert!(Square::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
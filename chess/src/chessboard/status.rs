@@ -0,0 +1,356 @@
+use super::{Chessboard, Color, Piece, Position};
+
+// 状态描述使用的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Chinese,
+    English,
+}
+
+fn color_name(color: Color, lang: Language) -> String {
+    match (lang, color) {
+        (Language::Chinese, _) => color.to_string(),
+        (Language::English, Color::White) => "White".to_string(),
+        (Language::English, Color::Black) => "Black".to_string(),
+    }
+}
+
+// 与棋子名连用的颜色简称，如 "黑兵"
+fn short_color(color: Color) -> &'static str {
+    match color {
+        Color::White => "白",
+        Color::Black => "黑",
+    }
+}
+
+fn english_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::King(_, _) => "king",
+        Piece::Queen(_) => "queen",
+        Piece::Rook(_, _) => "rook",
+        Piece::Bishop(_) => "bishop",
+        Piece::Knight(_) => "knight",
+        Piece::Pawn(_, _) => "pawn",
+    }
+}
+
+// 和棋原因，按优先级排列：先是立即生效的和棋，后是可以提和的和棋
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DrawReason {
+    // 逼和（立即生效）
+    Stalemate,
+    // 子力不足，任何走法都不可能将杀（立即生效）
+    InsufficientMaterial,
+    // 同一局面出现三次（可提和，本程序自动提和）
+    ThreefoldRepetition,
+    // 五十回合内没有走兵或吃子（可提和，本程序自动提和）
+    FiftyMoveRule,
+}
+
+impl DrawReason {
+    // 是否只是可以提和（而非规则上立即结束）
+    pub fn is_claimable(self) -> bool {
+        matches!(
+            self,
+            DrawReason::ThreefoldRepetition | DrawReason::FiftyMoveRule
+        )
+    }
+
+    fn describe(self, lang: Language) -> &'static str {
+        match (lang, self) {
+            (Language::Chinese, DrawReason::Stalemate) => "僵局",
+            (Language::Chinese, DrawReason::InsufficientMaterial) => "双方子力不足以将杀",
+            (Language::Chinese, DrawReason::ThreefoldRepetition) => "三次重复局面",
+            (Language::Chinese, DrawReason::FiftyMoveRule) => "五十回合规则",
+            (Language::English, DrawReason::Stalemate) => "stalemate",
+            (Language::English, DrawReason::InsufficientMaterial) => "insufficient material",
+            (Language::English, DrawReason::ThreefoldRepetition) => "threefold repetition",
+            (Language::English, DrawReason::FiftyMoveRule) => "the fifty-move rule",
+        }
+    }
+}
+
+// 不由局面决定的结束方式，由 conclude 记录（将死、和棋见 GameStatus）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    // 该方认输（引擎裁决胜负时记为败方认输）
+    Resigned(Color),
+    // 协议和棋（包括引擎裁决和棋）
+    DrawAgreed,
+    // 该方超时，结果见 flag_fall_winner
+    TimeForfeit(Color),
+}
+
+// 棋局结果。判定优先级：
+// 1. 将死优先于一切（同一步造成的重复局面等都不再算和棋）
+// 2. 否则收集所有适用的和棋原因，立即生效的排在可提和的前面
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate { winner: Color },
+    Draw { reasons: Vec<DrawReason> },
+}
+
+impl Chessboard {
+    // 一次合法走法扫描（找到一步即停）得到当前结果（认输/协议和棋不在此列，见is_game_over）
+    pub fn status(&self) -> GameStatus {
+        let in_check = self.is_in_check(self.current_turn);
+        let no_moves = !self.has_legal_move();
+        if in_check && no_moves {
+            return GameStatus::Checkmate {
+                winner: self.current_turn.opposite(),
+            };
+        }
+
+        let mut reasons = Vec::new();
+        if no_moves {
+            reasons.push(DrawReason::Stalemate);
+        }
+        if self.is_insufficient_material() {
+            reasons.push(DrawReason::InsufficientMaterial);
+        }
+        if self.is_threefold_repetition() {
+            reasons.push(DrawReason::ThreefoldRepetition);
+        }
+        if self.is_fifty_move_draw() {
+            reasons.push(DrawReason::FiftyMoveRule);
+        }
+
+        if reasons.is_empty() {
+            GameStatus::Ongoing
+        } else {
+            GameStatus::Draw { reasons }
+        }
+    }
+
+    pub fn is_in_check(&self, color: Color) -> bool {
+        let king_pos = self.find_king(color);
+        self.is_square_attacked(king_pos, color.opposite())
+    }
+
+    // 对局是否已经结束（将死、和棋，或已认输/协议和棋/超时）
+    pub fn is_game_over(&self) -> bool {
+        self.concluded.is_some() || self.status() != GameStatus::Ongoing
+    }
+
+    // 以认输、协议和棋或超时结束对局，之后make_move会返回错误
+    pub fn conclude(&mut self, outcome: Outcome) {
+        self.concluded = Some(outcome);
+    }
+
+    // conclude 记录的结束方式；对局仍在进行或由局面本身结束时为None
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.concluded
+    }
+
+    // 从当前局面重新开放走子，用于终局后的复盘和分支探索
+    pub fn continue_from_here(&mut self) {
+        self.concluded = None;
+        self.analysis = true;
+    }
+
+    // color一方是否还有将杀的可能（只按子力判断的简单情形）：
+    // 单王、王+马、王+象都不可能将杀，其余子力（包括兵，可以升变）视为有可能
+    pub fn has_mating_material(&self, color: Color) -> bool {
+        let mut minor_pieces = 0;
+        for row in 0..8 {
+            for col in 0..8 {
+                match self.board[row][col] {
+                    Some(Piece::King(_, _)) | None => {}
+                    Some(piece) if piece.color() != color => {}
+                    Some(Piece::Knight(_)) | Some(Piece::Bishop(_)) => minor_pieces += 1,
+                    Some(_) => return true,
+                }
+            }
+        }
+        minor_pieces > 1
+    }
+
+    // 双方都不可能将杀的死局：王对王、王+象对王、王+马对王，
+    // 以及所有象都在同色格上（含王+象对王+同色格象）；王+马对王+马等仍有将杀可能，不算
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        for row in 0..8 {
+            for col in 0..8 {
+                match self.board[row][col] {
+                    Some(Piece::King(_, _)) | Some(Piece::Bishop(_)) | None => {}
+                    Some(Piece::Knight(_)) => knights += 1,
+                    Some(_) => return false,
+                }
+            }
+        }
+        let (white_light, white_dark) = self.bishops_on(Color::White);
+        let (black_light, black_dark) = self.bishops_on(Color::Black);
+        let (light, dark) = (white_light + black_light, white_dark + black_dark);
+        match knights {
+            0 => light == 0 || dark == 0,
+            1 => light + dark == 0,
+            _ => false,
+        }
+    }
+
+    // flagged一方超时后的胜方；对方没有将杀可能时判和，返回None
+    pub fn flag_fall_winner(&self, flagged: Color) -> Option<Color> {
+        let opponent = flagged.opposite();
+        if self.has_mating_material(opponent) {
+            Some(opponent)
+        } else {
+            None
+        }
+    }
+
+    // 分析模式下改由color一方走棋（任意一方都可以连续走子）；
+    // 换边后原有的过路兵目标失效
+    pub fn play_as(&mut self, color: Color) -> Result<(), String> {
+        if !self.analysis {
+            return Err("只有分析模式下才能切换走棋方".to_string());
+        }
+        if self.current_turn != color {
+            self.current_turn = color;
+            self.en_passant_target = None;
+        }
+        Ok(())
+    }
+
+    // 用完整句子描述当前棋局状态，终端和GUI共用同一套措辞
+    pub fn describe_status(&self, lang: Language) -> String {
+        let side = self.current_turn;
+        let color_name = |color: Color| color_name(color, lang);
+
+        match self.status() {
+            GameStatus::Checkmate { winner } => {
+                return match lang {
+                    Language::Chinese => format!("将死! {}获胜!", color_name(winner)),
+                    Language::English => format!("{} wins by checkmate", color_name(winner)),
+                };
+            }
+            GameStatus::Draw { reasons } => {
+                let reasons: Vec<&str> = reasons.iter().map(|r| r.describe(lang)).collect();
+                return match lang {
+                    Language::Chinese => format!("{}! 游戏平局!", reasons.join("、")),
+                    Language::English => format!("Draw by {}", reasons.join(" and ")),
+                };
+            }
+            GameStatus::Ongoing => {}
+        }
+
+        if self.is_in_check(side) {
+            match lang {
+                Language::Chinese => format!("{}被将军!", color_name(side)),
+                Language::English => format!("{} is in check", color_name(side)),
+            }
+        } else {
+            match lang {
+                Language::Chinese => format!("轮到{}走棋", color_name(side)),
+                Language::English => format!("{} to move", color_name(side)),
+            }
+        }
+    }
+
+    // 刚走的一步（make_move 记录的最后一步）的说明，标出吃子、吃过路兵、易位、升变和将军，例如
+    // "白方: exd5 吃掉黑兵"、"黑方: O-O-O 长易位"、"白方: e8=Q+ 升变为后并将军"；还没有走子时返回None
+    pub fn describe_last_move(&self, lang: Language) -> Option<String> {
+        let (mv, undo) = self.undo_stack.last()?;
+        let san = self.move_history.last()?;
+        let color = undo.piece.color();
+        let mut parts = Vec::new();
+
+        match undo.captured {
+            Some((square, _)) if square != mv.to => parts.push(match lang {
+                Language::Chinese => "吃过路兵".to_string(),
+                Language::English => "captures en passant".to_string(),
+            }),
+            Some((_, piece)) => parts.push(match lang {
+                Language::Chinese => format!("吃掉{}{}", short_color(piece.color()), piece.name()),
+                Language::English => format!(
+                    "captures {} {}",
+                    color_name(piece.color(), lang).to_lowercase(),
+                    english_name(piece)
+                ),
+            }),
+            None => {}
+        }
+        if let Some((_, rook_to)) = undo.rook_movement {
+            let kingside = rook_to.col == 5;
+            parts.push(
+                match (lang, kingside) {
+                    (Language::Chinese, true) => "短易位",
+                    (Language::Chinese, false) => "长易位",
+                    (Language::English, true) => "castles kingside",
+                    (Language::English, false) => "castles queenside",
+                }
+                .to_string(),
+            );
+        }
+        if let Some(piece) = mv.promotion {
+            parts.push(match lang {
+                Language::Chinese => format!("升变为{}", piece.name()),
+                Language::English => format!("promotes to {}", english_name(piece)),
+            });
+        }
+
+        // 将军/将死接在最后一项后面（"…并将军"、"… with check"），没有其他说明时单独成项
+        let check = match san.chars().last() {
+            Some('#') => Some(("将死", "checkmate")),
+            Some('+') => Some(("将军", "check")),
+            _ => None,
+        };
+        if let Some((chinese, english)) = check {
+            match (parts.last_mut(), lang) {
+                (Some(last), Language::Chinese) => last.push_str(&format!("并{}", chinese)),
+                (Some(last), Language::English) => last.push_str(&format!(" with {}", english)),
+                (None, Language::Chinese) => parts.push(chinese.to_string()),
+                (None, Language::English) => parts.push(english.to_string()),
+            }
+        }
+
+        let separator = match lang {
+            Language::Chinese => "，",
+            Language::English => ", ",
+        };
+        let mut text = format!("{}: {}", color_name(color, lang), san);
+        if !parts.is_empty() {
+            text.push(' ');
+            text.push_str(&parts.join(separator));
+        }
+        Some(text)
+    }
+
+    // 连续100个半回合（双方各50步）没有走兵或吃子；同一步将死时以将死为准（见status）
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    // 当前局面在本局中出现的次数（首次出现为1，达到3即三次重复）
+    pub fn repetition_count(&self) -> usize {
+        let key = self.position_hash();
+        self.position_history
+            .iter()
+            .filter(|&&history_key| history_key == key)
+            .count()
+    }
+
+    // 走子时记录下来的当前局面哈希（position_history至少含初始局面）
+    pub fn recorded_hash(&self) -> u64 {
+        *self.position_history.last().expect("局面历史不为空")
+    }
+
+    // 当前局面已是第三次出现
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    pub(super) fn find_king(&self, color: Color) -> Position {
+        for row in 0..8 {
+            for col in 0..8 {
+                if let Some(Piece::King(king_color, _)) = self.board[row][col] {
+                    if king_color == color {
+                        return Position { row, col };
+                    }
+                }
+            }
+        }
+        panic!("King not found!");
+    }
+}
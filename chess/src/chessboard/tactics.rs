@@ -0,0 +1,188 @@
+use super::{Chessboard, Color, Move, Piece, Position, SquareColor};
+
+impl Chessboard {
+    // color一方在浅色格和深色格上的象的数量 (浅, 深)
+    // 约定：双方只剩同色格象（含各一只同色象）视为死局和棋；异色象仍可能将杀，不算子力不足
+    pub fn bishops_on(&self, color: Color) -> (u8, u8) {
+        let mut light = 0;
+        let mut dark = 0;
+        for row in 0..8 {
+            for col in 0..8 {
+                let pos = Position::new(row, col).unwrap();
+                if self.get(pos) == Some(Piece::Bishop(color)) {
+                    match pos.square_color() {
+                        SquareColor::Light => light += 1,
+                        SquareColor::Dark => dark += 1,
+                    }
+                }
+            }
+        }
+        (light, dark)
+    }
+
+    // 列出color一方被对方攻击且保护不足的棋子（不含王）
+    pub fn hanging_pieces(&self, color: Color) -> Vec<Position> {
+        let mut hanging = Vec::new();
+
+        for row in 0..8 {
+            for col in 0..8 {
+                let pos = Position::new(row, col).unwrap();
+                match self.get(pos) {
+                    Some(Piece::King(_, _)) | None => continue,
+                    Some(piece) if piece.color() != color => continue,
+                    Some(_) => {}
+                }
+
+                if self.static_exchange(pos, color.opposite()) > 0 {
+                    hanging.push(pos);
+                }
+            }
+        }
+
+        hanging
+    }
+
+    // 静态交换评估（SEE）：by_color一方在pos上发起连续兑换时的最佳净收益（厘兵）
+    // 双方都按价值从低到高依次出子；被绝对牵制的防守方棋子不计入
+    pub fn static_exchange(&self, pos: Position, by_color: Color) -> i32 {
+        let target = match self.get(pos) {
+            Some(piece) => piece,
+            None => return 0,
+        };
+
+        let mut attackers = self.exchange_values(self.attackers_of(pos, by_color));
+        let defenders = self
+            .attackers_of(pos, target.color())
+            .into_iter()
+            .filter(|&defender| !self.is_pinned(defender))
+            .collect();
+        let mut defenders = self.exchange_values(defenders);
+
+        if attackers.is_empty() {
+            return 0;
+        }
+
+        // 兑换序列中每一步的累计收益
+        let mut gains = vec![target.value()];
+        let mut on_square = attackers.remove(0);
+        let sides = [&mut defenders, &mut attackers];
+        let mut side = 0;
+
+        while !sides[side].is_empty() {
+            let last = *gains.last().unwrap();
+            gains.push(on_square - last);
+            on_square = sides[side].remove(0);
+            side = 1 - side;
+        }
+
+        // 自后向前回溯：每一方都可以选择停止兑换
+        while gains.len() > 1 {
+            let gain = gains.pop().unwrap();
+            let previous = gains.last_mut().unwrap();
+            *previous = -(-*previous).max(gain);
+        }
+
+        gains[0].max(0)
+    }
+
+    // 判断pos上的棋子是否被绝对牵制（移开后己方王会遭到新的攻击）
+    pub fn is_pinned(&self, pos: Position) -> bool {
+        let piece = match self.get(pos) {
+            Some(Piece::King(_, _)) | None => return false,
+            Some(piece) => piece,
+        };
+
+        let king_pos = self.find_king(piece.color());
+        let before = self.attackers_of(king_pos, piece.color().opposite());
+
+        let mut test_board = self.clone();
+        test_board.set_square(pos, None);
+        test_board
+            .attackers_of(king_pos, piece.color().opposite())
+            .iter()
+            .any(|attacker| !before.contains(attacker))
+    }
+
+    // 辅助模式 info 命令：双方各一行，列出攻击（或保护，pos上是己方棋子时）该格的棋子，被牵制的棋子加注
+    pub fn square_report(&self, pos: Position) -> Vec<String> {
+        [Color::White, Color::Black]
+            .into_iter()
+            .map(|color| {
+                let verb = match self.get(pos) {
+                    Some(piece) if piece.color() == color => "保护",
+                    _ => "攻击",
+                };
+                let attackers = self.attackers_of(pos, color);
+                let mut line = format!(
+                    "{}{}{} {}次",
+                    color,
+                    verb,
+                    pos.to_notation(),
+                    attackers.len()
+                );
+                if !attackers.is_empty() {
+                    let names: Vec<String> = attackers
+                        .iter()
+                        .map(|&from| {
+                            let pinned = if self.is_pinned(from) {
+                                "(被牵制)"
+                            } else {
+                                ""
+                            };
+                            format!(
+                                "{}{}{}",
+                                self.get(from).unwrap().name(),
+                                from.to_notation(),
+                                pinned
+                            )
+                        })
+                        .collect();
+                    line.push_str(&format!(": {}", names.join(", ")));
+                }
+                line
+            })
+            .collect()
+    }
+
+    // 当前走棋方没有合法走法且未被将军
+    fn is_stalemated(&self) -> bool {
+        !self.is_in_check(self.current_turn) && !self.has_legal_move()
+    }
+
+    // 辅助模式的逼和提示：mv走后对方无子可动且未被将军
+    pub fn stalemates_opponent(&self, mv: &Move) -> bool {
+        let mut after = self.scratch_copy();
+        after.make_move_unchecked(mv).is_ok() && after.is_stalemated()
+    }
+
+    // 两层搜索：mv走后对方是否有一步应着，使我方无论怎么走都会逼和对方
+    // （典型是对方弃掉最后一个能动的子，吃掉它就逼和），或直接让我方被逼和；返回这步应着
+    pub fn stalemate_reply(&self, mv: &Move) -> Option<Move> {
+        let mut after = self.scratch_copy();
+        after.make_move_unchecked(mv).ok()?;
+        after.get_all_legal_moves().into_iter().find(|reply| {
+            let Ok(undo) = after.make_move_unchecked(reply) else {
+                return false;
+            };
+            let answers = after.get_all_legal_moves();
+            let forced = if answers.is_empty() {
+                !after.is_in_check(after.current_turn)
+            } else {
+                answers
+                    .iter()
+                    .all(|answer| after.stalemates_opponent(answer))
+            };
+            after.undo_move(reply, undo);
+            forced
+        })
+    }
+
+    fn exchange_values(&self, positions: Vec<Position>) -> Vec<i32> {
+        let mut values: Vec<i32> = positions
+            .into_iter()
+            .filter_map(|pos| self.get(pos).map(|piece| piece.value()))
+            .collect();
+        values.sort_unstable();
+        values
+    }
+}
@@ -0,0 +1,79 @@
+use super::{Chessboard, Color, Piece, Square};
+
+// Zobrist局面哈希：棋子布局、走棋方、易位权和过路兵列，不含回合计数，
+// 这样经由不同走法次序到达的同一局面哈希相同（用于重复局面判定）
+// 随机数表在编译期由固定种子的splitmix64生成，不同运行之间哈希值一致
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+// 12种棋子×64格（按Square编号），之后依次为走棋方、4个易位权、8个过路兵列
+const KEY_COUNT: usize = 12 * 64 + 1 + 4 + 8;
+const SIDE_KEY: usize = 12 * 64;
+const CASTLING_KEYS: usize = SIDE_KEY + 1;
+const EN_PASSANT_KEYS: usize = CASTLING_KEYS + 4;
+
+const KEYS: [u64; KEY_COUNT] = {
+    let mut keys = [0; KEY_COUNT];
+    let mut state = 0x5EED_C4E5_5B0A_4D00;
+    let mut i = 0;
+    while i < KEY_COUNT {
+        let (next, key) = splitmix64(state);
+        state = next;
+        keys[i] = key;
+        i += 1;
+    }
+    keys
+};
+
+fn piece_index(piece: Piece) -> usize {
+    let kind = match piece {
+        Piece::King(_, _) => 0,
+        Piece::Queen(_) => 1,
+        Piece::Rook(_, _) => 2,
+        Piece::Bishop(_) => 3,
+        Piece::Knight(_) => 4,
+        Piece::Pawn(_, _) => 5,
+    };
+    match piece.color() {
+        Color::White => kind,
+        Color::Black => kind + 6,
+    }
+}
+
+impl Chessboard {
+    pub fn position_hash(&self) -> u64 {
+        let mut hash = 0;
+        for square in Square::ALL {
+            if let Some(piece) = self.get(square.into()) {
+                hash ^= KEYS[piece_index(piece) * 64 + square.index()];
+            }
+        }
+        if self.current_turn == Color::Black {
+            hash ^= KEYS[SIDE_KEY];
+        }
+        let rights = self.castling_rights;
+        for (index, allowed) in [
+            rights.white_kingside,
+            rights.white_queenside,
+            rights.black_kingside,
+            rights.black_queenside,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if allowed {
+                hash ^= KEYS[CASTLING_KEYS + index];
+            }
+        }
+        if let Some(target) = self.en_passant_target {
+            hash ^= KEYS[EN_PASSANT_KEYS + target.col];
+        }
+        hash
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+// SiliconFlow API密钥的读取，优先级从高到低：
+// 1. --api-key-file <路径>（取文件第一行）
+// 2. --api-key <密钥>（不推荐，会留在shell历史中）
+// 3. 环境变量 SILICON_FLOW_API_KEY
+// 4. 配置文件 chess.cfg 中的 api_key = ...
+// 只有真正用到云端后端时才读取
+
+pub const ENV_VAR: &str = "SILICON_FLOW_API_KEY";
+pub const CONFIG_FILE: &str = "chess.cfg";
+
+// 密钥本身，Debug/Display只输出掩码，避免出现在日志、PGN或panic信息中
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey(***)")
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "***")
+    }
+}
+
+// 密钥来自哪里
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySource {
+    File,
+    Flag,
+    Env,
+    Config,
+}
+
+// 按优先级查找密钥；env和config分别是环境变量的值和配置文件内容（不存在时为None）
+pub fn resolve_api_key(
+    args: &[String],
+    env: Option<String>,
+    config: Option<String>,
+) -> Result<(ApiKey, KeySource), String> {
+    if let Some(path) = flag_value(args, "--api-key-file")? {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("无法读取 {}: {}", path, e))?;
+        let key = content.lines().next().unwrap_or("").trim();
+        if key.is_empty() {
+            return Err(format!("{} 的第一行没有密钥", path));
+        }
+        return Ok((ApiKey(key.to_string()), KeySource::File));
+    }
+
+    if let Some(key) = flag_value(args, "--api-key")? {
+        return Ok((ApiKey(key.to_string()), KeySource::Flag));
+    }
+
+    if let Some(key) = env.filter(|key| !key.trim().is_empty()) {
+        return Ok((ApiKey(key.trim().to_string()), KeySource::Env));
+    }
+
+    if let Some(key) = config.as_deref().and_then(config_api_key) {
+        return Ok((ApiKey(key), KeySource::Config));
+    }
+
+    Err(missing_key_message())
+}
+
+// 从当前环境和配置文件读取密钥，--api-key 时提醒命令行历史的风险
+pub fn load_api_key(args: &[String]) -> Result<ApiKey, String> {
+    let env = std::env::var(ENV_VAR).ok();
+    let config = std::fs::read_to_string(CONFIG_FILE).ok();
+    let (key, source) = resolve_api_key(args, env, config)?;
+    if source == KeySource::Flag {
+        eprintln!("警告: --api-key 会把密钥留在shell历史中，建议改用 --api-key-file 或环境变量");
+    }
+    Ok(key)
+}
+
+fn missing_key_message() -> String {
+    [
+        "未找到SiliconFlow API密钥，请任选一种方式提供:".to_string(),
+        "  --api-key-file <路径>   从文件第一行读取".to_string(),
+        "  --api-key <密钥>        直接传入（会留在shell历史中，不推荐）".to_string(),
+        format!("  环境变量 {}", ENV_VAR),
+        format!("  配置文件 {} 中写入 api_key = <密钥>", CONFIG_FILE),
+    ]
+    .join("\n")
+}
+
+// 配置文件每行 key = value，#开头为注释
+fn config_api_key(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "api_key")
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a str>, String> {
+    match args.iter().position(|arg| arg == flag) {
+        None => Ok(None),
+        Some(index) => args
+            .get(index + 1)
+            .map(|value| Some(value.as_str()))
+            .ok_or_else(|| format!("{} 需要一个参数", flag)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn sources_follow_precedence() {
+        let path = std::env::temp_dir().join("chess_api_key_test.txt");
+        std::fs::write(&path, "from-file\nsecond line\n").unwrap();
+        let path = path.to_string_lossy().to_string();
+        let env = || Some("from-env".to_string());
+        let config = || Some("# 注释\napi_key = from-config\n".to_string());
+
+        let all = args(&["chess", "--api-key", "from-flag", "--api-key-file", &path]);
+        let (key, source) = resolve_api_key(&all, env(), config()).unwrap();
+        assert_eq!((key.expose(), source), ("from-file", KeySource::File));
+
+        let flag = args(&["chess", "--api-key", "from-flag"]);
+        let (key, source) = resolve_api_key(&flag, env(), config()).unwrap();
+        assert_eq!((key.expose(), source), ("from-flag", KeySource::Flag));
+
+        let (key, source) = resolve_api_key(&args(&["chess"]), env(), config()).unwrap();
+        assert_eq!((key.expose(), source), ("from-env", KeySource::Env));
+
+        let (key, source) = resolve_api_key(&args(&["chess"]), None, config()).unwrap();
+        assert_eq!((key.expose(), source), ("from-config", KeySource::Config));
+        assert_eq!(format!("{:?} {}", key, key), "ApiKey(***) ***");
+
+        let missing = resolve_api_key(&args(&["chess"]), None, None).unwrap_err();
+        assert!(missing.contains("--api-key-file") && missing.contains(ENV_VAR));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+use crate::chessboard::{Chessboard, Move};
+use crate::search;
+use crate::search_limits::SearchLimits;
+use rand::Rng;
+
+// AI难度等级（1-10）：决定搜索深度，低等级时会以一定概率改走按搜索分值排名靠后的着法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difficulty {
+    level: u8,
+}
+
+impl Difficulty {
+    pub const MIN_LEVEL: u8 = 1;
+    pub const MAX_LEVEL: u8 = 10;
+    // 达到该等级后不再故意失误
+    const NO_BLUNDER_LEVEL: u8 = 7;
+
+    pub fn new(level: u8) -> Option<Self> {
+        if (Self::MIN_LEVEL..=Self::MAX_LEVEL).contains(&level) {
+            Some(Self { level })
+        } else {
+            None
+        }
+    }
+
+    // 从命令行参数中读取 --level N，缺省为最高等级
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        Ok(Self::from_flag(args, "--level")?.unwrap_or_default())
+    }
+
+    // 读取指定参数（如 --level-black N），未给出时返回None
+    pub fn from_flag(args: &[String], flag: &str) -> Result<Option<Self>, String> {
+        let index = match args.iter().position(|arg| arg == flag) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+
+        args.get(index + 1)
+            .and_then(|value| value.parse().ok())
+            .and_then(Self::new)
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "{} 需要 {}-{} 之间的整数",
+                    flag,
+                    Self::MIN_LEVEL,
+                    Self::MAX_LEVEL
+                )
+            })
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    // 搜索深度上限：1-2级为1层，10级为5层
+    pub fn search_depth(&self) -> u8 {
+        self.level.div_ceil(2)
+    }
+
+    // 故意失误的概率：1级为60%，每升一级减少10%，7级及以上为0
+    pub fn blunder_chance(&self) -> f64 {
+        if self.level >= Self::NO_BLUNDER_LEVEL {
+            0.0
+        } else {
+            f64::from(Self::NO_BLUNDER_LEVEL - self.level) / 10.0
+        }
+    }
+
+    // 本步走第几好的着法（0为最佳）：以 blunder_chance 的概率改走次优着法，
+    // 名次在 1 到 (7 - 等级) 之间随机，等级越低可能选到的着法越差
+    pub fn move_rank<R: Rng>(&self, rng: &mut R) -> usize {
+        if !rng.random_bool(self.blunder_chance()) {
+            return 0;
+        }
+        rng.random_range(1..=usize::from(Self::NO_BLUNDER_LEVEL - self.level))
+    }
+
+    // 按limits搜索后的分值排名取第rank好的着法（合法走法不够时取最差的一步），
+    // 返回着法和它的分值（轮到走棋的一方为正）
+    pub fn ranked_move(
+        board: &Chessboard,
+        limits: &SearchLimits,
+        rank: usize,
+    ) -> Option<(Move, i32)> {
+        let mut ranked = search::ranked_moves_with_limits(board, limits);
+        let index = rank.min(ranked.len().checked_sub(1)?);
+        Some(ranked.swap_remove(index))
+    }
+
+    // 本地搜索的深度：limits给出depth时用它，否则取 search_depth
+    pub fn local_depth(&self, limits: &SearchLimits) -> u8 {
+        limits.depth.unwrap_or(self.search_depth())
+    }
+
+    // 本地引擎走第rank好的着法（0为最佳）：深度取 local_depth，节点数和时间按limits限制
+    pub fn local_move(
+        &self,
+        board: &Chessboard,
+        limits: &SearchLimits,
+        rank: usize,
+    ) -> Option<(Move, i32)> {
+        let limits = SearchLimits {
+            depth: Some(self.local_depth(limits)),
+            ..*limits
+        };
+        match rank {
+            0 => search::best_move_with_limits(board, &limits),
+            rank => Self::ranked_move(board, &limits, rank),
+        }
+    }
+
+    // 本地引擎按该难度走一步：名次取 move_rank
+    pub fn choose_move<R: Rng>(
+        &self,
+        board: &Chessboard,
+        limits: &SearchLimits,
+        rng: &mut R,
+    ) -> Option<(Move, i32)> {
+        self.local_move(board, limits, self.move_rank(rng))
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            level: Self::MAX_LEVEL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn low_levels_blunder_and_search_shallower() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let weakest = Difficulty::new(1).unwrap();
+        let strongest = Difficulty::new(10).unwrap();
+
+        let weak_ranks: Vec<usize> = (0..100).map(|_| weakest.move_rank(&mut rng)).collect();
+        let strong_blunders = (0..100)
+            .filter(|_| strongest.move_rank(&mut rng) > 0)
+            .count();
+
+        assert!(weak_ranks.iter().filter(|&&rank| rank > 0).count() > 30);
+        assert!(weak_ranks.iter().all(|&rank| rank <= 6));
+        assert_eq!(strong_blunders, 0);
+        assert!(weakest.search_depth() < strongest.search_depth());
+        assert!(Difficulty::new(0).is_none());
+        assert!(Difficulty::new(11).is_none());
+    }
+
+    #[test]
+    fn only_the_top_level_finds_the_knight_fork() {
+        // Nc7+ 同时攻击王和后，要看三层才能发现；一层搜索时它排在十名之后
+        let board = Chessboard::from_fen("q3k3/pp6/8/3N4/8/8/6PP/6K1 w - - 0 1").unwrap();
+        let weakest = Difficulty::new(1).unwrap();
+        let strongest = Difficulty::new(10).unwrap();
+
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (weak, _) = weakest
+                .choose_move(&board, &SearchLimits::default(), &mut rng)
+                .unwrap();
+            assert_ne!(board.move_to_san(&weak), "Nc7+", "seed {}", seed);
+        }
+        let mut rng = StdRng::seed_from_u64(0);
+        let (strong, score) = strongest
+            .choose_move(&board, &SearchLimits::default(), &mut rng)
+            .unwrap();
+        assert_eq!(board.move_to_san(&strong), "Nc7+");
+        assert!(score > 0);
+        // 对局搜索限制中的深度优先于难度
+        let (shallow, _) = strongest
+            .choose_move(&board, &SearchLimits::depth(1), &mut rng)
+            .unwrap();
+        assert_ne!(board.move_to_san(&shallow), "Nc7+");
+
+        // 名次超出合法走法数时取最差的一步
+        let worst = Difficulty::ranked_move(&board, &SearchLimits::depth(1), usize::MAX).unwrap();
+        let ranked = search::ranked_moves(&board, 1);
+        assert_eq!(worst.1, ranked.last().unwrap().1);
+    }
+}
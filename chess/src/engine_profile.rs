@@ -0,0 +1,267 @@
+use crate::credentials::CONFIG_FILE;
+use crate::difficulty::Difficulty;
+use crate::search_limits::SearchLimits;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// 命名的引擎配置，写在 chess.cfg 中（TOML式的小节，其余设置仍是 key = value）：
+//   [profiles.blitz]
+//   depth = 2
+//   movetime = 500
+//   [profiles.sparring]
+//   level = 3
+// 对局时用 --ai profile:blitz 指定引擎一方的配置；
+// chess profile list / export <名称> / import <文件> 列出、导出、导入配置片段以便分享
+
+const SECTION_PREFIX: &str = "[profiles.";
+const KEYS: [&str; 4] = ["level", "depth", "nodes", "movetime"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EngineProfile {
+    pub name: String,
+    pub level: Option<Difficulty>,
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    // 配置中以毫秒书写
+    pub movetime: Option<Duration>,
+}
+
+impl EngineProfile {
+    // 在基础搜索限制上覆盖配置中给出的字段
+    pub fn apply(&self, base: SearchLimits) -> SearchLimits {
+        SearchLimits {
+            depth: self.depth.or(base.depth),
+            nodes: self.nodes.or(base.nodes),
+            movetime: self.movetime.or(base.movetime),
+            ..base
+        }
+    }
+
+    // 可以直接追加到 chess.cfg 的片段
+    pub fn to_snippet(&self) -> String {
+        let mut snippet = format!("{}{}]\n", SECTION_PREFIX, self.name);
+        if let Some(level) = self.level {
+            snippet.push_str(&format!("level = {}\n", level.level()));
+        }
+        if let Some(depth) = self.depth {
+            snippet.push_str(&format!("depth = {}\n", depth));
+        }
+        if let Some(nodes) = self.nodes {
+            snippet.push_str(&format!("nodes = {}\n", nodes));
+        }
+        if let Some(movetime) = self.movetime {
+            snippet.push_str(&format!("movetime = {}\n", movetime.as_millis()));
+        }
+        snippet
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("配置 {} 的 {} 无效: {}", self.name, key, value);
+        match key {
+            "level" => {
+                self.level = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .and_then(Difficulty::new)
+                        .ok_or_else(invalid)?,
+                )
+            }
+            "depth" => self.depth = Some(value.parse().map_err(|_| invalid())?),
+            "nodes" => self.nodes = Some(value.parse().map_err(|_| invalid())?),
+            "movetime" => {
+                self.movetime = Some(Duration::from_millis(value.parse().map_err(|_| invalid())?))
+            }
+            _ => {
+                return Err(format!(
+                    "配置 {} 中未知的设置: {} (可选: {})",
+                    self.name,
+                    key,
+                    KEYS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+// 读取配置文件中所有 [profiles.名称] 小节；小节外的行（api_key、glyph.* 等）不在这里处理
+pub fn parse_profiles(config: &str) -> Result<BTreeMap<String, EngineProfile>, String> {
+    let mut profiles = BTreeMap::new();
+    let mut current: Option<EngineProfile> = None;
+
+    for line in config.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            if let Some(profile) = current.take() {
+                insert(&mut profiles, profile)?;
+            }
+            if let Some(name) = line
+                .strip_prefix(SECTION_PREFIX)
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                let name = name.trim().trim_matches('"');
+                if name.is_empty() {
+                    return Err(format!("无效的配置小节: {}", line));
+                }
+                current = Some(EngineProfile {
+                    name: name.to_string(),
+                    ..EngineProfile::default()
+                });
+            }
+            continue;
+        }
+        if let Some(profile) = current.as_mut() {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("配置 {} 中无效的行: {}", profile.name, line))?;
+            profile.set(key.trim(), value.trim().trim_matches('"'))?;
+        }
+    }
+    if let Some(profile) = current {
+        insert(&mut profiles, profile)?;
+    }
+    Ok(profiles)
+}
+
+fn insert(
+    profiles: &mut BTreeMap<String, EngineProfile>,
+    profile: EngineProfile,
+) -> Result<(), String> {
+    if profiles.contains_key(&profile.name) {
+        return Err(format!("引擎配置 {} 重复定义", profile.name));
+    }
+    profiles.insert(profile.name.clone(), profile);
+    Ok(())
+}
+
+// 读取 chess.cfg（不存在时视为没有配置）
+pub fn load() -> Result<BTreeMap<String, EngineProfile>, String> {
+    parse_profiles(&std::fs::read_to_string(CONFIG_FILE).unwrap_or_default())
+}
+
+// 解析 profile:名称 形式的引擎设置；名称不存在时列出可用的配置
+pub fn resolve<'a>(
+    spec: &str,
+    profiles: &'a BTreeMap<String, EngineProfile>,
+) -> Result<&'a EngineProfile, String> {
+    let name = spec
+        .strip_prefix("profile:")
+        .ok_or_else(|| format!("无效的引擎设置: {} (格式: profile:名称)", spec))?;
+    profiles.get(name).ok_or_else(|| {
+        let available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        match available.as_slice() {
+            [] => format!(
+                "未知的引擎配置: {} ({} 中没有定义任何配置)",
+                name, CONFIG_FILE
+            ),
+            _ => format!("未知的引擎配置: {} (可选: {})", name, available.join(", ")),
+        }
+    })
+}
+
+// 子命令 chess profile list | export <名称> | import <文件>
+pub fn run(args: &[String]) -> Result<(), String> {
+    let usage = "用法: chess profile list | export <名称> | import <文件>";
+    let profiles = load()?;
+    match (
+        args.get(2).map(String::as_str),
+        args.get(3).map(String::as_str),
+    ) {
+        (Some("list"), _) => {
+            if profiles.is_empty() {
+                println!("{} 中没有定义引擎配置", CONFIG_FILE);
+            }
+            for profile in profiles.values() {
+                println!("{}", profile.to_snippet());
+            }
+            Ok(())
+        }
+        (Some("export"), Some(name)) => {
+            let profile = resolve(&format!("profile:{}", name), &profiles)?;
+            print!("{}", profile.to_snippet());
+            Ok(())
+        }
+        (Some("import"), Some(path)) => {
+            let content =
+                std::fs::read_to_string(path).map_err(|e| format!("无法读取 {}: {}", path, e))?;
+            let imported = parse_profiles(&content)?;
+            if imported.is_empty() {
+                return Err(format!("{} 中没有 [profiles.名称] 小节", path));
+            }
+            if let Some(name) = imported.keys().find(|name| profiles.contains_key(*name)) {
+                return Err(format!("引擎配置 {} 已存在，未导入", name));
+            }
+            let mut config = std::fs::read_to_string(CONFIG_FILE).unwrap_or_default();
+            if !config.is_empty() && !config.ends_with('\n') {
+                config.push('\n');
+            }
+            for profile in imported.values() {
+                config.push_str(&profile.to_snippet());
+            }
+            std::fs::write(CONFIG_FILE, config)
+                .map_err(|e| format!("无法写入 {}: {}", CONFIG_FILE, e))?;
+            let names: Vec<&str> = imported.keys().map(String::as_str).collect();
+            println!("已导入: {}", names.join(", "));
+            Ok(())
+        }
+        _ => Err(usage.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "api_key = sk-test\n\
+        glyphs = letters\n\
+        [profiles.blitz]\n\
+        depth = 2\n\
+        movetime = 500\n\
+        \n\
+        # 弱一些的陪练\n\
+        [profiles.\"sparring\"]\n\
+        level = 3\n";
+
+    #[test]
+    fn profiles_are_read_from_sections() {
+        let profiles = parse_profiles(CONFIG).unwrap();
+        assert_eq!(profiles.len(), 2);
+        let blitz = &profiles["blitz"];
+        assert_eq!(blitz.depth, Some(2));
+        assert_eq!(blitz.movetime, Some(Duration::from_millis(500)));
+        assert_eq!(profiles["sparring"].level, Difficulty::new(3));
+
+        let limits = blitz.apply(SearchLimits::depth(5));
+        assert_eq!(limits.depth, Some(2));
+        assert_eq!(limits.movetime, Some(Duration::from_millis(500)));
+
+        assert!(parse_profiles("[profiles.x]\nelo = 1400\n")
+            .unwrap_err()
+            .contains("未知的设置: elo"));
+        assert!(parse_profiles("[profiles.x]\nlevel = 11\n").is_err());
+        assert!(parse_profiles("[profiles.x]\n[profiles.x]\n").is_err());
+    }
+
+    #[test]
+    fn unknown_profile_lists_available_ones() {
+        let profiles = parse_profiles(CONFIG).unwrap();
+        assert_eq!(resolve("profile:blitz", &profiles).unwrap().name, "blitz");
+        assert_eq!(
+            resolve("profile:deep", &profiles).unwrap_err(),
+            "未知的引擎配置: deep (可选: blitz, sparring)"
+        );
+        assert!(resolve("blitz", &profiles).is_err());
+    }
+
+    #[test]
+    fn snippets_round_trip() {
+        let profiles = parse_profiles(CONFIG).unwrap();
+        for profile in profiles.values() {
+            let reread = parse_profiles(&profile.to_snippet()).unwrap();
+            assert_eq!(&reread[&profile.name], profile);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::chessboard::{Chessboard, Move};
+use crate::search;
+use crate::search_limits::SearchLimits;
+use std::collections::BTreeMap;
+
+// EPD（扩展局面描述）：四个FEN局面字段加若干操作，如 bm Qxf7+; id "WAC.001";
+// 未知操作码原样保留，带引号的操作数可以包含空格和分号
+
+pub type Opcodes = BTreeMap<String, Vec<String>>;
+
+pub struct EpdRecord {
+    pub board: Chessboard,
+    pub opcodes: Opcodes,
+}
+
+impl EpdRecord {
+    // 操作码的第一个操作数，例如 id
+    pub fn operand(&self, opcode: &str) -> Option<&str> {
+        self.opcodes
+            .get(opcode)
+            .and_then(|operands| operands.first())
+            .map(|operand| operand.as_str())
+    }
+}
+
+// 解析一行EPD
+pub fn parse_epd(line: &str) -> Result<EpdRecord, String> {
+    let line = line.trim();
+    let mut rest = line;
+    let mut fields = Vec::new();
+    for _ in 0..4 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return Err(format!("EPD缺少局面字段: {}", line));
+        }
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    let board = Chessboard::from_minimal_fen(&fields.join(" "))?;
+    let opcodes = parse_operations(rest)?;
+    Ok(EpdRecord { board, opcodes })
+}
+
+// 把局面和操作输出为一行EPD
+pub fn to_epd(board: &Chessboard, opcodes: &Opcodes) -> String {
+    let mut epd = board.minimal_fen();
+    for (opcode, operands) in opcodes {
+        epd.push(' ');
+        epd.push_str(opcode);
+        for operand in operands {
+            epd.push(' ');
+            if operand.is_empty() || operand.contains(|c: char| c.is_whitespace() || c == ';') {
+                epd.push_str(&format!("\"{}\"", operand));
+            } else {
+                epd.push_str(operand);
+            }
+        }
+        epd.push(';');
+    }
+    epd
+}
+
+// 解析操作部分：以分号分隔，每个操作由操作码和零个或多个操作数组成
+fn parse_operations(text: &str) -> Result<Opcodes, String> {
+    let mut opcodes = Opcodes::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut token = String::new();
+    let mut in_quotes = false;
+    let mut quoted = false;
+
+    for c in text.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            _ if in_quotes => token.push(c),
+            ';' => {
+                finish_token(&mut token, &mut quoted, &mut tokens);
+                if let Some((opcode, operands)) = tokens.split_first() {
+                    opcodes.insert(opcode.clone(), operands.to_vec());
+                }
+                tokens.clear();
+            }
+            _ if c.is_whitespace() => finish_token(&mut token, &mut quoted, &mut tokens),
+            _ => token.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(format!("EPD操作中的引号未闭合: {}", text.trim()));
+    }
+
+    // 最后一个操作允许省略分号
+    finish_token(&mut token, &mut quoted, &mut tokens);
+    if let Some((opcode, operands)) = tokens.split_first() {
+        opcodes.insert(opcode.clone(), operands.to_vec());
+    }
+
+    Ok(opcodes)
+}
+
+// 测试题子命令：chess epdtest <EPD文件> [--movetime 毫秒 | --depth N]
+// 本地引擎逐题搜索，走法在 bm 之中且不在 am 之中为通过；既没有 bm 也没有 am 的局面不计分
+pub fn run(args: &[String]) -> Result<(), String> {
+    let input = args
+        .get(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("用法: chess epdtest <EPD文件> [--movetime 毫秒 | --depth N]")?;
+    let content =
+        std::fs::read_to_string(input).map_err(|e| format!("无法读取 {}: {}", input, e))?;
+    let limits = SearchLimits::from_args(args, SearchLimits::default())?;
+
+    let outcomes = run_suite(&content, &limits)?;
+    for outcome in outcomes.iter().filter(|outcome| !outcome.passed) {
+        println!("{}", outcome.describe());
+    }
+    let passed = outcomes.iter().filter(|outcome| outcome.passed).count();
+    println!("{}/{}", passed, outcomes.len());
+    Ok(())
+}
+
+// 一道测试题的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpdOutcome {
+    // 题目所在行号（从1开始）
+    pub line: usize,
+    // id 操作数，没有时为空
+    pub id: String,
+    // 引擎选择的走法（SAN）
+    pub chosen: String,
+    pub best: Vec<String>,
+    pub avoid: Vec<String>,
+    pub passed: bool,
+}
+
+impl EpdOutcome {
+    // 未通过时的说明，如 "#4 knight fork: 引擎走 Nf4，期望 bm Nc7+"
+    pub fn describe(&self) -> String {
+        let mut text = format!("#{}", self.line);
+        if !self.id.is_empty() {
+            text.push_str(&format!(" {}", self.id));
+        }
+        text.push_str(&format!(": 引擎走 {}", self.chosen));
+        if !self.best.is_empty() {
+            text.push_str(&format!("，期望 bm {}", self.best.join(" ")));
+        }
+        if !self.avoid.is_empty() {
+            text.push_str(&format!("，应避免 am {}", self.avoid.join(" ")));
+        }
+        text
+    }
+}
+
+// 依次解答每行EPD（空行和 # 开头的行跳过），返回计分题目的结果
+pub fn run_suite(content: &str, limits: &SearchLimits) -> Result<Vec<EpdOutcome>, String> {
+    let mut outcomes = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let at = |e: String| format!("第{}行: {}", index + 1, e);
+        let record = parse_epd(line).map_err(at)?;
+        let expected = |opcode: &str| -> Result<Vec<Move>, String> {
+            record
+                .opcodes
+                .get(opcode)
+                .map_or(Ok(Vec::new()), |operands| {
+                    operands
+                        .iter()
+                        .map(|san| record.board.parse_san(san.trim_end_matches(['!', '?'])))
+                        .collect()
+                })
+        };
+        let (best, avoid) = (expected("bm").map_err(at)?, expected("am").map_err(at)?);
+        if best.is_empty() && avoid.is_empty() {
+            continue;
+        }
+
+        let chosen = match search::best_move_with_limits(&record.board, limits) {
+            Some((chosen, _)) => chosen,
+            None => return Err(at("局面没有合法走法".to_string())),
+        };
+        let same = |mv: &Move| mv.to_uci() == chosen.to_uci();
+        let san = |moves: &[Move]| -> Vec<String> {
+            moves
+                .iter()
+                .map(|mv| record.board.move_to_san(mv))
+                .collect()
+        };
+        outcomes.push(EpdOutcome {
+            line: index + 1,
+            id: record.operand("id").unwrap_or_default().to_string(),
+            chosen: record.board.move_to_san(&chosen),
+            best: san(&best),
+            avoid: san(&avoid),
+            passed: (best.is_empty() || best.iter().any(same)) && !avoid.iter().any(same),
+        });
+    }
+    Ok(outcomes)
+}
+
+// 结束当前操作数；带引号的空字符串也算一个操作数
+fn finish_token(token: &mut String, quoted: &mut bool, tokens: &mut Vec<String>) {
+    if !token.is_empty() || *quoted {
+        tokens.push(std::mem::take(token));
+    }
+    *quoted = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_emit_epd_with_opcodes() {
+        let line = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - \
+                    bm Qg6; id \"WAC.001; mate\"; xyz 1 2;";
+        let record = parse_epd(line).unwrap();
+
+        assert_eq!(record.operand("bm"), Some("Qg6"));
+        assert_eq!(record.operand("id"), Some("WAC.001; mate"));
+        assert_eq!(record.opcodes["xyz"], vec!["1", "2"]);
+        assert_eq!(
+            record.board.minimal_fen(),
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - -"
+        );
+
+        let emitted = to_epd(&record.board, &record.opcodes);
+        let reparsed = parse_epd(&emitted).unwrap();
+        assert_eq!(reparsed.opcodes, record.opcodes);
+        assert!(reparsed.board.position_eq(&record.board));
+
+        assert!(parse_epd("8/8/8/8 w - -").is_err());
+        assert!(parse_epd("4k3/8/8/8/8/8/8/4K3 w - - id \"open").is_err());
+    }
+
+    #[test]
+    fn test_suite_counts_bm_and_am() {
+        let suite = include_str!("../tests/fixtures/epdtest.epd");
+
+        // 三层能看到马的捉双，四道计分题全部通过；没有 bm/am 的局面不计分
+        let outcomes = run_suite(suite, &SearchLimits::depth(3)).unwrap();
+        assert_eq!(outcomes.len(), 4);
+        assert!(
+            outcomes.iter().all(|outcome| outcome.passed),
+            "{:?}",
+            outcomes
+        );
+
+        // 一层只看到眼前的吃子：捉双没有找到，毒兵被吃
+        let outcomes = run_suite(suite, &SearchLimits::depth(1)).unwrap();
+        let failed: Vec<&str> = outcomes
+            .iter()
+            .filter(|outcome| !outcome.passed)
+            .map(|outcome| outcome.id.as_str())
+            .collect();
+        assert_eq!(failed, ["poisoned pawn", "knight fork"]);
+        assert!(outcomes[2]
+            .describe()
+            .starts_with("#3 poisoned pawn: 引擎走 Qxb7"));
+
+        assert!(run_suite("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5;", &SearchLimits::depth(1)).is_err());
+    }
+}
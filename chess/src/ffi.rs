@@ -0,0 +1,233 @@
+// C ABI（ffi 特性）：供 Python ctypes 等外部程序调用规则引擎，头文件见 include/chess.h。
+// 构建动态库：cargo rustc --release --lib --features ffi --crate-type cdylib
+// 约定：
+// - 棋盘由 chess_board_new 创建、chess_board_free 释放，其余函数只借用
+// - 字符串参数是以NUL结尾的UTF-8；输出字符串写入调用方的缓冲区并以NUL结尾，
+//   返回字符串长度（不含NUL）；缓冲区不够时不写入，调用方可按返回值+1重新分配
+// - 出错返回负数（CHESS_ERROR），任何panic都在边界内被捕获，不会跨越C ABI展开
+use crate::chessboard::{Chessboard, Color, GameStatus, Move};
+use std::ffi::{c_char, c_int, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+pub const CHESS_OK: c_int = 0;
+pub const CHESS_ERROR: c_int = -1;
+
+// chess_board_status 的返回值
+pub const CHESS_ONGOING: c_int = 0;
+pub const CHESS_WHITE_WINS: c_int = 1;
+pub const CHESS_BLACK_WINS: c_int = 2;
+pub const CHESS_DRAW: c_int = 3;
+
+// 在 catch_unwind 中运行，panic 时返回 fallback
+fn guarded<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or(fallback)
+}
+
+unsafe fn board_mut<'a>(board: *mut Chessboard) -> Option<&'a mut Chessboard> {
+    board.as_mut()
+}
+
+unsafe fn string_arg<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+// 把text连同结尾的NUL写入缓冲区（放得下时），返回text的长度
+unsafe fn write_string(text: &str, buffer: *mut c_char, capacity: usize) -> isize {
+    if !buffer.is_null() && text.len() < capacity {
+        ptr::copy_nonoverlapping(text.as_ptr(), buffer.cast::<u8>(), text.len());
+        *buffer.add(text.len()) = 0;
+    }
+    text.len() as isize
+}
+
+/// 创建初始局面的棋盘；用完必须调用 chess_board_free 释放。
+#[no_mangle]
+pub extern "C" fn chess_board_new() -> *mut Chessboard {
+    guarded(ptr::null_mut(), || {
+        Box::into_raw(Box::new(Chessboard::new()))
+    })
+}
+
+/// 释放棋盘。
+///
+/// # Safety
+/// board 必须是 chess_board_new 返回且尚未释放的指针，或为空指针（忽略）。
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_free(board: *mut Chessboard) {
+    if !board.is_null() {
+        guarded((), || drop(Box::from_raw(board)));
+    }
+}
+
+/// 把棋盘设为 FEN 描述的局面；FEN 无效时返回 CHESS_ERROR，棋盘不变。
+///
+/// # Safety
+/// board 必须是有效的棋盘指针，fen 必须是以NUL结尾的字符串。
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_set_fen(board: *mut Chessboard, fen: *const c_char) -> c_int {
+    guarded(CHESS_ERROR, || {
+        let (Some(board), Some(fen)) = (board_mut(board), string_arg(fen)) else {
+            return CHESS_ERROR;
+        };
+        match Chessboard::from_fen(fen) {
+            Ok(parsed) => {
+                *board = parsed;
+                CHESS_OK
+            }
+            Err(_) => CHESS_ERROR,
+        }
+    })
+}
+
+/// 当前局面的 FEN 写入 buffer，返回其长度。
+///
+/// # Safety
+/// board 必须是有效的棋盘指针，buffer 至少有 capacity 字节可写（capacity 为0时可为空）。
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_get_fen(
+    board: *mut Chessboard,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> isize {
+    guarded(CHESS_ERROR as isize, || match board_mut(board) {
+        Some(board) => write_string(&board.to_fen(), buffer, capacity),
+        None => CHESS_ERROR as isize,
+    })
+}
+
+/// 全部合法走法的 UCI 记法，以空格分隔写入 buffer，返回其长度（没有合法走法时为0）。
+///
+/// # Safety
+/// 同 chess_board_get_fen。
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_legal_moves(
+    board: *mut Chessboard,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> isize {
+    guarded(CHESS_ERROR as isize, || match board_mut(board) {
+        Some(board) => {
+            let moves: Vec<String> = board
+                .get_all_legal_moves()
+                .iter()
+                .map(Move::to_uci)
+                .collect();
+            write_string(&moves.join(" "), buffer, capacity)
+        }
+        None => CHESS_ERROR as isize,
+    })
+}
+
+/// 走一步 UCI 记法的走法（如 "e2e4"、"e7e8q"）；不合法时返回 CHESS_ERROR，棋盘不变。
+///
+/// # Safety
+/// board 必须是有效的棋盘指针，uci 必须是以NUL结尾的字符串。
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_push_uci(board: *mut Chessboard, uci: *const c_char) -> c_int {
+    guarded(CHESS_ERROR, || {
+        let (Some(board), Some(uci)) = (board_mut(board), string_arg(uci)) else {
+            return CHESS_ERROR;
+        };
+        match Move::from_uci(uci).map(|mv| board.make_move(&mv)) {
+            Some(Ok(())) => CHESS_OK,
+            _ => CHESS_ERROR,
+        }
+    })
+}
+
+/// 对局状态：CHESS_ONGOING、CHESS_WHITE_WINS、CHESS_BLACK_WINS 或 CHESS_DRAW。
+///
+/// # Safety
+/// board 必须是有效的棋盘指针。
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_status(board: *mut Chessboard) -> c_int {
+    guarded(CHESS_ERROR, || {
+        match board_mut(board).map(|board| board.status()) {
+            Some(GameStatus::Ongoing) => CHESS_ONGOING,
+            Some(GameStatus::Checkmate {
+                winner: Color::White,
+            }) => CHESS_WHITE_WINS,
+            Some(GameStatus::Checkmate {
+                winner: Color::Black,
+            }) => CHESS_BLACK_WINS,
+            Some(GameStatus::Draw { .. }) => CHESS_DRAW,
+            None => CHESS_ERROR,
+        }
+    })
+}
+
+// 这些测试也可以在 Miri 下运行以检查指针使用：cargo +nightly miri test --features ffi ffi
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    fn read(call: impl Fn(*mut c_char, usize) -> isize) -> String {
+        let needed = call(ptr::null_mut(), 0);
+        assert!(needed >= 0);
+        let mut buffer = vec![0 as c_char; needed as usize + 1];
+        assert_eq!(call(buffer.as_mut_ptr(), buffer.len()), needed);
+        unsafe { CStr::from_ptr(buffer.as_ptr()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn board_is_driven_through_the_c_abi() {
+        let board = chess_board_new();
+        unsafe {
+            let fen = read(|buffer, capacity| chess_board_get_fen(board, buffer, capacity));
+            assert_eq!(fen, Chessboard::new().to_fen());
+            let moves = read(|buffer, capacity| chess_board_legal_moves(board, buffer, capacity));
+            assert_eq!(moves.split(' ').count(), 20);
+
+            // 缓冲区不够时不写入
+            let mut small = [1 as c_char; 8];
+            assert_eq!(
+                chess_board_get_fen(board, small.as_mut_ptr(), small.len()),
+                fen.len() as isize
+            );
+            assert_eq!(small, [1; 8]);
+
+            for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+                let uci = CString::new(uci).unwrap();
+                assert_eq!(chess_board_push_uci(board, uci.as_ptr()), CHESS_OK);
+            }
+            assert_eq!(chess_board_status(board), CHESS_BLACK_WINS);
+            assert_eq!(
+                read(|buffer, capacity| chess_board_legal_moves(board, buffer, capacity)),
+                ""
+            );
+
+            let fen = CString::new("4k3/8/4K3/8/8/8/8/8 w - - 0 1").unwrap();
+            assert_eq!(chess_board_set_fen(board, fen.as_ptr()), CHESS_OK);
+            assert_eq!(chess_board_status(board), CHESS_DRAW);
+            chess_board_free(board);
+        }
+    }
+
+    #[test]
+    fn bad_input_is_reported_not_panicked() {
+        let board = chess_board_new();
+        unsafe {
+            let illegal = CString::new("e2e5").unwrap();
+            let garbage = CString::new("zz").unwrap();
+            assert_eq!(chess_board_push_uci(board, illegal.as_ptr()), CHESS_ERROR);
+            assert_eq!(chess_board_push_uci(board, garbage.as_ptr()), CHESS_ERROR);
+            assert_eq!(chess_board_push_uci(board, ptr::null()), CHESS_ERROR);
+            assert_eq!(chess_board_set_fen(board, garbage.as_ptr()), CHESS_ERROR);
+            assert_eq!(chess_board_status(board), CHESS_ONGOING);
+
+            let null = ptr::null_mut();
+            assert_eq!(chess_board_status(null), CHESS_ERROR);
+            assert_eq!(chess_board_get_fen(null, ptr::null_mut(), 0), -1);
+            chess_board_free(null);
+            chess_board_free(board);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::chessboard::{Color, Piece};
+use crate::credentials::CONFIG_FILE;
+use unicode_width::UnicodeWidthStr;
+
+// 终端棋盘的棋子字形，可在 chess.cfg 中配置：
+//   glyphs = letters                 选择内置方案（unicode / letters / figurine）
+//   glyph.black.knight = [n]         单独覆盖某方某个棋子
+// 命令行 --glyphs <方案> 优先于配置文件中的方案，单项覆盖仍然生效
+// 字形可以是多列宽的文字，所有格子按最宽的字形补齐，棋盘保持对齐
+
+pub const PRESETS: [&str; 3] = ["unicode", "letters", "figurine"];
+
+// 字形允许的最大显示宽度
+const MAX_GLYPH_WIDTH: usize = 4;
+
+const PIECE_NAMES: [&str; 6] = ["king", "queen", "rook", "bishop", "knight", "pawn"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceGlyphs {
+    // 按 PIECE_NAMES 的顺序：王、后、车、象、马、兵
+    white: [String; 6],
+    black: [String; 6],
+}
+
+impl PieceGlyphs {
+    // 内置方案
+    pub fn preset(name: &str) -> Option<Self> {
+        let (white, black) = match name {
+            "unicode" => (
+                ["♔", "♕", "♖", "♗", "♘", "♙"],
+                ["♚", "♛", "♜", "♝", "♞", "♟"],
+            ),
+            "letters" => (
+                ["K", "Q", "R", "B", "N", "P"],
+                ["k", "q", "r", "b", "n", "p"],
+            ),
+            // 双方都用实心符号，白方亮色、黑方暗色
+            "figurine" => (
+                [
+                    "\x1b[97m♚\x1b[0m",
+                    "\x1b[97m♛\x1b[0m",
+                    "\x1b[97m♜\x1b[0m",
+                    "\x1b[97m♝\x1b[0m",
+                    "\x1b[97m♞\x1b[0m",
+                    "\x1b[97m♟\x1b[0m",
+                ],
+                [
+                    "\x1b[90m♚\x1b[0m",
+                    "\x1b[90m♛\x1b[0m",
+                    "\x1b[90m♜\x1b[0m",
+                    "\x1b[90m♝\x1b[0m",
+                    "\x1b[90m♞\x1b[0m",
+                    "\x1b[90m♟\x1b[0m",
+                ],
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            white: white.map(str::to_string),
+            black: black.map(str::to_string),
+        })
+    }
+
+    // 按配置文件内容和命令行方案构造；任一字形不合法时报错
+    pub fn from_config(config: &str, preset: Option<&str>) -> Result<Self, String> {
+        let settings: Vec<(&str, &str)> = config
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+
+        let name = preset
+            .or_else(|| {
+                settings
+                    .iter()
+                    .find(|(key, _)| *key == "glyphs")
+                    .map(|(_, value)| *value)
+            })
+            .unwrap_or("unicode");
+        let mut glyphs = Self::preset(name)
+            .ok_or_else(|| format!("未知的字形方案: {} (可选: {})", name, PRESETS.join(", ")))?;
+
+        for (key, value) in settings {
+            let Some(path) = key.strip_prefix("glyph.") else {
+                continue;
+            };
+            let (color, piece) = path
+                .split_once('.')
+                .ok_or_else(|| format!("无效的字形设置: {}", key))?;
+            let index = PIECE_NAMES
+                .iter()
+                .position(|name| *name == piece)
+                .ok_or_else(|| {
+                    format!("未知的棋子: {} (可选: {})", piece, PIECE_NAMES.join(", "))
+                })?;
+            let slot = match color {
+                "white" => &mut glyphs.white[index],
+                "black" => &mut glyphs.black[index],
+                _ => return Err(format!("未知的颜色: {} (可选: white, black)", color)),
+            };
+            *slot = value.to_string();
+        }
+
+        glyphs.validate()?;
+        Ok(glyphs)
+    }
+
+    // 从 chess.cfg（不存在时视为空）和 --glyphs 参数读取
+    pub fn load(args: &[String]) -> Result<Self, String> {
+        let preset = match args.iter().position(|arg| arg == "--glyphs") {
+            Some(index) => Some(args.get(index + 1).ok_or("--glyphs 需要一个参数")?.as_str()),
+            None => None,
+        };
+        let config = std::fs::read_to_string(CONFIG_FILE).unwrap_or_default();
+        Self::from_config(&config, preset)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (color, glyphs) in [("white", &self.white), ("black", &self.black)] {
+            for (piece, glyph) in PIECE_NAMES.iter().zip(glyphs) {
+                let width = display_width(glyph);
+                if width == 0 || width > MAX_GLYPH_WIDTH {
+                    return Err(format!(
+                        "glyph.{}.{} 的字形 {:?} 宽度为{}，应为1到{}列",
+                        color, piece, glyph, width, MAX_GLYPH_WIDTH
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 每个格子的显示宽度（最宽字形的宽度）
+    pub fn cell_width(&self) -> usize {
+        self.white
+            .iter()
+            .chain(&self.black)
+            .map(|glyph| display_width(glyph))
+            .max()
+            .unwrap_or(1)
+    }
+
+    // 格子内容，已补齐到 cell_width 列
+    pub fn cell(&self, square: Option<Piece>) -> String {
+        let glyph = match square {
+            None => "",
+            Some(piece) => {
+                let index = match piece {
+                    Piece::King(_, _) => 0,
+                    Piece::Queen(_) => 1,
+                    Piece::Rook(_, _) => 2,
+                    Piece::Bishop(_) => 3,
+                    Piece::Knight(_) => 4,
+                    Piece::Pawn(_, _) => 5,
+                };
+                match piece.color() {
+                    Color::White => &self.white[index],
+                    Color::Black => &self.black[index],
+                }
+            }
+        };
+        let padding = self.cell_width() - display_width(glyph);
+        format!("{}{}", glyph, " ".repeat(padding))
+    }
+}
+
+impl Default for PieceGlyphs {
+    fn default() -> Self {
+        Self::preset("unicode").expect("内置方案")
+    }
+}
+
+// 显示宽度，不计ANSI颜色转义序列
+pub fn display_width(text: &str) -> usize {
+    let mut visible = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // 跳过 ESC [ ... 字母
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if !c.is_control() {
+            visible.push(c);
+        }
+    }
+    visible.width()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chessboard::Chessboard;
+
+    #[test]
+    fn presets_and_overrides_keep_the_board_aligned() {
+        let letters = PieceGlyphs::from_config("", Some("letters")).unwrap();
+        let board = Chessboard::new().display(&letters);
+        assert!(board.contains("8|r n b q k b n r|8"), "{}", board);
+        assert!(board.contains("4|               |4"), "{}", board);
+
+        // 配置文件选择方案并给黑方加括号，宽字形按最宽的补齐
+        let config = "glyphs = letters\nglyph.black.pawn = [p]\nglyph.black.king = [k]\n";
+        let bracketed = PieceGlyphs::from_config(config, None).unwrap();
+        assert_eq!(bracketed.cell_width(), 3);
+        assert_eq!(bracketed.cell(Some(Piece::Queen(Color::White))), "Q  ");
+        let lines: Vec<usize> = Chessboard::new()
+            .display(&bracketed)
+            .lines()
+            .map(display_width)
+            .collect();
+        assert!(
+            lines[2..10].iter().all(|&width| width == lines[2]),
+            "{:?}",
+            lines
+        );
+
+        let figurine = PieceGlyphs::preset("figurine").unwrap();
+        assert_eq!(figurine.cell_width(), 1);
+        // --glyphs 优先于配置文件的方案
+        assert_eq!(
+            PieceGlyphs::from_config("glyphs = figurine", Some("unicode")).unwrap(),
+            PieceGlyphs::default()
+        );
+
+        assert!(PieceGlyphs::from_config("", Some("wooden")).is_err());
+        assert!(PieceGlyphs::from_config("glyph.white.king = ", None).is_err());
+        assert!(PieceGlyphs::from_config("glyph.white.kong = K", None).is_err());
+        assert!(PieceGlyphs::from_config("glyph.red.king = K", None).is_err());
+        assert!(PieceGlyphs::from_config("glyph.white.king = KING!", None).is_err());
+    }
+}
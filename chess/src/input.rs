@@ -0,0 +1,106 @@
+use std::io::{self, BufRead};
+
+// 交互式输入的统一读取：所有提示（走法、升变选择、确认）都经过这里
+// - EOF（如从空文件重定向输入）返回None，调用方按退出或否定处理，避免无限打印提示
+// - 非UTF-8字节替换为U+FFFD，不会panic
+// - 去掉控制字符（终端转义序列、粘贴带来的\r等）
+// - 过长的行截断到 MAX_LINE_CHARS 个字符
+
+pub const MAX_LINE_CHARS: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputLine {
+    // 清理后、去掉首尾空白的文本
+    pub text: String,
+    // 原始行超过长度上限被截断
+    pub truncated: bool,
+}
+
+// 从reader读取一行，EOF或读取错误时返回None
+pub fn read_line_from(reader: &mut impl BufRead) -> Option<InputLine> {
+    let mut bytes = Vec::new();
+    match reader.read_until(b'\n', &mut bytes) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(sanitize(&bytes)),
+    }
+}
+
+// 从标准输入读取一行；行被截断时提示一次
+pub fn read_line() -> Option<InputLine> {
+    let line = read_line_from(&mut io::stdin().lock())?;
+    if line.truncated {
+        println!("输入过长，只保留前{}个字符", MAX_LINE_CHARS);
+    }
+    Some(line)
+}
+
+pub fn sanitize(bytes: &[u8]) -> InputLine {
+    let text = String::from_utf8_lossy(bytes);
+    let cleaned: String = text.chars().filter(|c| !c.is_control()).collect();
+    let cleaned = cleaned.trim();
+    let truncated = cleaned.chars().count() > MAX_LINE_CHARS;
+    InputLine {
+        text: cleaned
+            .chars()
+            .take(MAX_LINE_CHARS)
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chessboard::Chessboard;
+    use std::io::Cursor;
+
+    // 模拟交互循环：逐行读取并尝试解析走法，直到EOF；返回(成功走子数, 出错提示数)
+    fn drive(input: &[u8]) -> (usize, usize) {
+        let mut reader = Cursor::new(input.to_vec());
+        let mut board = Chessboard::new();
+        let (mut moves, mut errors) = (0, 0);
+        while let Some(line) = read_line_from(&mut reader) {
+            match board
+                .parse_move(&line.text)
+                .and_then(|mv| board.make_move(&mv).map_err(String::from))
+            {
+                Ok(_) => moves += 1,
+                Err(e) => {
+                    assert!(!e.is_empty());
+                    errors += 1;
+                }
+            }
+        }
+        (moves, errors)
+    }
+
+    #[test]
+    fn eof_ends_the_loop() {
+        assert_eq!(read_line_from(&mut Cursor::new(Vec::new())), None);
+        // 最后一行没有换行符也能读到
+        assert_eq!(drive(b"e2 e4\ne7 e5"), (2, 0));
+    }
+
+    #[test]
+    fn pathological_lines_are_cleaned_and_rejected() {
+        let long = "e".repeat(10_000);
+        let line = sanitize(long.as_bytes());
+        assert!(line.truncated);
+        assert_eq!(line.text.chars().count(), MAX_LINE_CHARS);
+
+        assert_eq!(sanitize(b"\x1b[Ae2\x07 e4\r\n").text, "[Ae2 e4");
+        assert_eq!(sanitize(b"e2 e4\r\n").text, "e2 e4");
+        assert_eq!(sanitize(&[0xff, 0xfe, b'e']).text, "\u{fffd}\u{fffd}e");
+
+        // PGN片段、emoji、超长行和非UTF-8都只产生错误提示，之后的正常走法照常执行
+        let mut input = Vec::new();
+        input.extend_from_slice(b"1. e4 e5 2. Nf3 Nc6\n");
+        input.extend_from_slice("\u{265e}\u{1f600}\n".as_bytes());
+        input.extend_from_slice(long.as_bytes());
+        input.extend_from_slice(b"\n\xc3\x28\xa0\n\x00\x01\n");
+        input.extend_from_slice(b"e2 e4\n");
+        assert_eq!(drive(&input), (1, 5));
+    }
+}
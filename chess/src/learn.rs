@@ -0,0 +1,408 @@
+use crate::chessboard::Chessboard;
+use crate::glyphs::PieceGlyphs;
+use crate::input;
+use serde::{Deserialize, Serialize};
+
+// 新手教程子命令：chess learn
+// 课程是数据而不是代码：lessons/lessons.json 编译时嵌入，每课由若干步组成，
+// 每一步给出局面、说明、可接受的走法和提示；这里的小引擎负责逐步执行。
+// 新增课程只需编辑JSON（测试会检查每一课的局面和走法都合法），GUI以后也可以读同一份文件
+// 学习进度保存在当前目录的 chess_learn.json（与 chess.cfg 放在一起）
+
+const LESSONS: &str = include_str!("../lessons/lessons.json");
+pub const PROGRESS_FILE: &str = "chess_learn.json";
+// 连续走错这么多次后自动给出下一条提示
+const HINT_AFTER_FAILURES: usize = 2;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lesson {
+    pub id: String,
+    pub title: String,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Step {
+    pub fen: String,
+    pub instruction: String,
+    // 可接受的走法（SAN）；走对其中任意一个即完成这一步
+    pub expected: Vec<String>,
+    // 为true时每个走法都要走一遍，每走对一个局面回到起点（如"走到每一个标记的格子"）
+    #[serde(default)]
+    pub all: bool,
+    // 按顺序给出的提示
+    #[serde(default)]
+    pub hints: Vec<String>,
+    // 完成这一步后的讲解
+    #[serde(default)]
+    pub explanation: Option<String>,
+}
+
+pub fn lessons() -> Result<Vec<Lesson>, String> {
+    serde_json::from_str(LESSONS).map_err(|e| format!("课程文件格式错误: {}", e))
+}
+
+// 一次尝试的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Attempt {
+    // 输入不是这个局面下的合法走法
+    Illegal(String),
+    // 合法但不是要求的走法；走错次数够多时附带下一条提示
+    Wrong { hint: Option<String> },
+    // 这一步要求每个走法都走一遍，这个已经走过了
+    Repeated,
+    Correct,
+}
+
+// 正在进行的一步
+pub struct StepRun<'a> {
+    step: &'a Step,
+    start: Chessboard,
+    // 可接受的走法，统一为本程序生成的SAN（"Nf3"、"O-O"、"Ra8#"）
+    expected: Vec<String>,
+    played: Vec<bool>,
+    failures: usize,
+    hints_shown: usize,
+}
+
+impl<'a> StepRun<'a> {
+    pub fn new(step: &'a Step) -> Result<Self, String> {
+        let start = Chessboard::from_fen(&step.fen)?;
+        // 例如只剩王和马时子力不足，局面一开始就已结束，无法走子
+        if start.is_game_over() {
+            return Err(format!("局面 {} 已经结束", step.fen));
+        }
+        let expected = step
+            .expected
+            .iter()
+            .map(|san| match start.parse_san(san) {
+                Ok(mv) => Ok(start.move_to_san(&mv)),
+                Err(e) => Err(format!("局面 {} 下{}", step.fen, e)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if expected.is_empty() {
+            return Err(format!("局面 {} 没有给出要求的走法", step.fen));
+        }
+        Ok(Self {
+            step,
+            played: vec![false; expected.len()],
+            start,
+            expected,
+            failures: 0,
+            hints_shown: 0,
+        })
+    }
+
+    pub fn board(&self) -> &Chessboard {
+        &self.start
+    }
+
+    // 还需要走到的格子（用于提示标记）
+    pub fn marked_squares(&self) -> Vec<String> {
+        self.expected
+            .iter()
+            .zip(&self.played)
+            .filter(|(_, &played)| !played)
+            .filter_map(|(san, _)| self.start.parse_san(san).ok())
+            .map(|mv| mv.to.to_notation())
+            .collect()
+    }
+
+    pub fn attempt(&mut self, text: &str) -> Attempt {
+        let mut board = self.start.clone();
+        let played = board
+            .parse_move(text)
+            .and_then(|mv| board.make_move(&mv).map_err(String::from));
+        if let Err(e) = played {
+            return Attempt::Illegal(e);
+        }
+        let san = board.move_history().last().expect("刚走过一步");
+
+        match self.expected.iter().position(|expected| expected == san) {
+            Some(index) if self.played[index] => Attempt::Repeated,
+            Some(index) => {
+                self.played[index] = true;
+                self.failures = 0;
+                Attempt::Correct
+            }
+            None => {
+                self.failures += 1;
+                let hint = if self.failures.is_multiple_of(HINT_AFTER_FAILURES) {
+                    self.next_hint()
+                } else {
+                    None
+                };
+                Attempt::Wrong { hint }
+            }
+        }
+    }
+
+    // 下一条还没看过的提示；都看过后重复最后一条
+    pub fn next_hint(&mut self) -> Option<String> {
+        let hint = self
+            .step
+            .hints
+            .get(self.hints_shown)
+            .or(self.step.hints.last())?;
+        self.hints_shown = (self.hints_shown + 1).min(self.step.hints.len());
+        Some(hint.clone())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        if self.step.all {
+            self.played.iter().all(|&played| played)
+        } else {
+            self.played.iter().any(|&played| played)
+        }
+    }
+}
+
+// 已完成的课程（按id记录，课程顺序调整后仍然有效）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub completed: Vec<String>,
+}
+
+impl Progress {
+    // 文件不存在或无法解析时从头开始
+    pub fn load(path: &str) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("无法保存学习进度到 {}: {}", path, e))
+    }
+
+    pub fn is_completed(&self, lesson: &Lesson) -> bool {
+        self.completed.contains(&lesson.id)
+    }
+
+    pub fn complete(&mut self, lesson: &Lesson) {
+        if !self.is_completed(lesson) {
+            self.completed.push(lesson.id.clone());
+        }
+    }
+}
+
+// 一课结束的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Completed,
+    // 跳过了某一步，不记为完成
+    Skipped,
+    // 输入结束或 quit
+    Quit,
+}
+
+// 交互执行一课
+fn run_lesson(lesson: &Lesson, glyphs: &PieceGlyphs) -> Result<Outcome, String> {
+    let mut outcome = Outcome::Completed;
+    println!("\n=== {} ===", lesson.title);
+    for (index, step) in lesson.steps.iter().enumerate() {
+        let mut run = StepRun::new(step)?;
+        if lesson.steps.len() > 1 {
+            println!("\n第{}/{}步", index + 1, lesson.steps.len());
+        }
+        while !run.is_complete() {
+            println!("{}", run.board().display(glyphs));
+            println!("{}", step.instruction);
+            if step.all {
+                println!("标记的格子: {}", run.marked_squares().join(" "));
+            }
+            println!("请输入走法 ('hint' 提示, 'skip' 跳过这一步, 'quit' 退出):");
+
+            let Some(line) = input::read_line() else {
+                return Ok(Outcome::Quit);
+            };
+            match line.text.as_str() {
+                "quit" | "exit" => return Ok(Outcome::Quit),
+                "skip" => {
+                    outcome = Outcome::Skipped;
+                    break;
+                }
+                "hint" => {
+                    match run.next_hint() {
+                        Some(hint) => println!("提示: {}", hint),
+                        None => println!("这一步没有提示"),
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            match run.attempt(&line.text) {
+                Attempt::Illegal(e) => println!("{}", e),
+                Attempt::Wrong { hint } => {
+                    println!("这步棋合法，但不是这里要求的走法，再试一次");
+                    if let Some(hint) = hint {
+                        println!("提示: {}", hint);
+                    }
+                }
+                Attempt::Repeated => println!("这个已经走过了，试试其他标记的格子"),
+                Attempt::Correct => println!("正确!"),
+            }
+        }
+        if let Some(explanation) = &step.explanation {
+            println!("{}", explanation);
+        }
+    }
+    if outcome == Outcome::Completed {
+        println!("完成课程: {}", lesson.title);
+    }
+    Ok(outcome)
+}
+
+pub fn run(args: &[String], glyphs: &PieceGlyphs) -> Result<(), String> {
+    let lessons = lessons()?;
+    let mut progress = Progress::load(PROGRESS_FILE);
+    if args.iter().any(|arg| arg == "--reset") {
+        progress = Progress::default();
+        progress.save(PROGRESS_FILE)?;
+    }
+
+    println!("国际象棋入门课程:");
+    for (index, lesson) in lessons.iter().enumerate() {
+        let mark = if progress.is_completed(lesson) {
+            "✓"
+        } else {
+            " "
+        };
+        println!("  [{}] {}. {}", mark, index + 1, lesson.title);
+    }
+    println!("输入课程编号开始，直接回车从第一个未完成的课程开始:");
+
+    let Some(choice) = input::read_line() else {
+        return Ok(());
+    };
+    let first = match choice.text.as_str() {
+        "" => match lessons
+            .iter()
+            .position(|lesson| !progress.is_completed(lesson))
+        {
+            Some(first) => first,
+            None => {
+                println!("所有课程都已完成! 用 chess learn --reset 重新开始");
+                return Ok(());
+            }
+        },
+        text => match text.parse::<usize>() {
+            Ok(n) if (1..=lessons.len()).contains(&n) => n - 1,
+            _ => return Err(format!("课程编号应为1到{}", lessons.len())),
+        },
+    };
+
+    for lesson in &lessons[first..] {
+        match run_lesson(lesson, glyphs)? {
+            Outcome::Completed => {
+                progress.complete(lesson);
+                progress.save(PROGRESS_FILE)?;
+            }
+            Outcome::Skipped => {}
+            Outcome::Quit => break,
+        }
+    }
+    let done = lessons
+        .iter()
+        .filter(|lesson| progress.is_completed(lesson))
+        .count();
+    println!("学习进度: {}/{} 课", done, lessons.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_lesson(id: &str) -> Lesson {
+        lessons()
+            .unwrap()
+            .into_iter()
+            .find(|lesson| lesson.id == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn embedded_lessons_are_valid() {
+        let lessons = lessons().unwrap();
+        assert!(!lessons.is_empty());
+        for lesson in &lessons {
+            assert!(!lesson.steps.is_empty(), "{}", lesson.id);
+            for step in &lesson.steps {
+                // 局面和每个要求的走法都合法
+                StepRun::new(step).unwrap_or_else(|e| panic!("{}: {}", lesson.id, e));
+            }
+        }
+        let mut ids: Vec<&str> = lessons.iter().map(|lesson| lesson.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), lessons.len());
+    }
+
+    #[test]
+    fn knight_must_visit_every_marked_square() {
+        let lesson = find_lesson("knight-moves");
+        let mut run = StepRun::new(&lesson.steps[0]).unwrap();
+        assert_eq!(run.marked_squares().len(), 8);
+
+        assert!(matches!(run.attempt("d4 d5"), Attempt::Illegal(_)));
+        assert_eq!(run.attempt("a1 a2"), Attempt::Wrong { hint: None });
+        assert_eq!(run.attempt("d4 c6"), Attempt::Correct);
+        assert_eq!(run.attempt("Nc6"), Attempt::Repeated);
+        assert!(!run.is_complete());
+        for target in ["b3", "b5", "c2", "e2", "e6", "f3", "f5"] {
+            assert_eq!(run.attempt(&format!("d4 {}", target)), Attempt::Correct);
+        }
+        assert!(run.is_complete());
+        assert!(run.marked_squares().is_empty());
+    }
+
+    #[test]
+    fn hints_follow_repeated_failures() {
+        let lesson = find_lesson("back-rank-mate");
+        let mut run = StepRun::new(&lesson.steps[0]).unwrap();
+        let hints = &lesson.steps[0].hints;
+
+        assert_eq!(run.attempt("a1 a2"), Attempt::Wrong { hint: None });
+        assert_eq!(
+            run.attempt("a1 a3"),
+            Attempt::Wrong {
+                hint: Some(hints[0].clone())
+            }
+        );
+        assert_eq!(run.next_hint(), Some(hints[1].clone()));
+        // 提示用完后重复最后一条
+        assert_eq!(run.next_hint(), Some(hints[1].clone()));
+
+        // 任意记法都行，带不带 # 都算
+        assert_eq!(run.attempt("Ra8"), Attempt::Correct);
+        assert!(run.is_complete());
+
+        let castling = find_lesson("castling");
+        let mut run = StepRun::new(&castling.steps[0]).unwrap();
+        assert_eq!(run.attempt("e1 c1"), Attempt::Correct);
+        assert!(run.is_complete());
+    }
+
+    #[test]
+    fn progress_round_trip() {
+        let path = std::env::temp_dir().join(format!("chess_learn_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let lessons = lessons().unwrap();
+
+        let mut progress = Progress::load(path);
+        assert_eq!(progress, Progress::default());
+        progress.complete(&lessons[0]);
+        progress.complete(&lessons[0]);
+        progress.save(path).unwrap();
+
+        let loaded = Progress::load(path);
+        assert_eq!(loaded.completed, vec![lessons[0].id.clone()]);
+        assert!(loaded.is_completed(&lessons[0]));
+        assert!(!loaded.is_completed(&lessons[1]));
+        std::fs::remove_file(path).unwrap();
+    }
+}
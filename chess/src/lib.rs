@@ -0,0 +1,28 @@
+// 国际象棋库：棋盘与规则、搜索、PGN/EPD、AI客户端等；命令行程序(main.rs)只是其上的一层
+pub mod adjudication;
+pub mod analysis;
+pub mod api_client;
+pub mod bench;
+pub mod bulk_analysis;
+pub mod chessboard;
+pub mod credentials;
+pub mod difficulty;
+pub mod engine_profile;
+pub mod epd;
+// C ABI，供其他语言调用（ffi 特性）
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod glyphs;
+pub mod input;
+pub mod learn;
+pub mod move_quality;
+pub mod pgn;
+pub mod player_config;
+pub mod progress;
+pub mod promotion;
+pub mod search;
+pub mod search_limits;
+pub mod session_log;
+pub mod verify;
+
+pub use chessboard::{CastlingRights, Chessboard, Color, Move, Piece, Position, RenderOptions};
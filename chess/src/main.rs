@@ -1,1062 +1,708 @@
-use rand::Rng;
+use std::collections::BTreeMap;
 use std::env;
-use std::fmt;
-use std::io;
-use tokio;
-
-// 导入自定义模块
-mod api_client;
-mod fen_converter;
-use crate::api_client::SiliconFlowClient;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Color {
-    White,
-    Black,
-}
-
-impl Color {
-    pub fn opposite(&self) -> Color {
-        match self {
-            Color::White => Color::Black,
-            Color::Black => Color::White,
-        }
-    }
-}
-
-impl fmt::Display for Color {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Color::White => write!(f, "白方"),
-            Color::Black => write!(f, "黑方"),
-        }
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+
+use chess::adjudication::{Adjudication, AdjudicationConfig, Adjudicator};
+use chess::api_client::{CloudEngine, SiliconFlowClient};
+use chess::chessboard::{
+    Chessboard, Color, GameStatus, Language, MoveError, Outcome, Piece, Position, RenderOptions,
+};
+use chess::difficulty::Difficulty;
+use chess::glyphs::PieceGlyphs;
+use chess::player_config::{Clocks, Controller, Players};
+use chess::promotion::{resolve_promotion, PromotionMode};
+use chess::search_limits::SearchLimits;
+use chess::session_log::{Action, Recorder};
+use chess::{
+    analysis, bench, bulk_analysis, credentials, engine_profile, epd, input, learn, move_quality,
+    pgn, session_log, verify,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// 引擎评分以走棋方为视角，裁决需要白方视角
+fn white_view(mover: Color, score: i32) -> i32 {
+    match mover {
+        Color::White => score,
+        Color::Black => -score,
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Piece {
-    King(Color, bool),
-    Queen(Color),
-    Rook(Color, bool),
-    Bishop(Color),
-    Knight(Color),
-    Pawn(Color, bool),
+// 计时对局中双方的剩余时间，如 "剩余时间: 白方 4:58 黑方 5:00"；都不计时时为None
+fn clock_line(remaining: impl Fn(Color) -> Option<Duration>) -> Option<String> {
+    let sides: Vec<String> = [Color::White, Color::Black]
+        .into_iter()
+        .filter_map(|color| {
+            let seconds = remaining(color)?.as_secs();
+            Some(format!("{} {}:{:02}", color, seconds / 60, seconds % 60))
+        })
+        .collect();
+    (!sides.is_empty()).then(|| format!("剩余时间: {}", sides.join(" ")))
 }
 
-impl Piece {
-    pub fn color(&self) -> Color {
-        match self {
-            Piece::King(color, _) => *color,
-            Piece::Queen(color) => *color,
-            Piece::Rook(color, _) => *color,
-            Piece::Bishop(color) => *color,
-            Piece::Knight(color) => *color,
-            Piece::Pawn(color, _) => *color,
+// 录制模式下记录一项操作，写入失败只提示不中断对局
+fn record(recorder: &mut Option<Recorder>, action: Action, board: &Chessboard) {
+    if let Some(recorder) = recorder {
+        if let Err(e) = recorder.record(action, board) {
+            println!("录制失败: {}", e);
         }
     }
-
-    pub fn name(&self) -> &str {
-        match self {
-            Piece::King(_, _) => "王",
-            Piece::Queen(_) => "后",
-            Piece::Rook(_, _) => "车",
-            Piece::Bishop(_) => "象",
-            Piece::Knight(_) => "马",
-            Piece::Pawn(_, _) => "兵",
-        }
-    }
-}
-
-pub type Square = Option<Piece>;
-
-#[derive(Debug, Clone)]
-pub struct Chessboard {
-    board: [[Square; 8]; 8],
-    current_turn: Color,
-    castling_rights: CastlingRights,
-    en_passant_target: Option<Position>,
-    move_history: Vec<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct CastlingRights {
-    pub white_kingside: bool,
-    pub white_queenside: bool,
-    pub black_kingside: bool,
-    pub black_queenside: bool,
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
 }
 
-impl CastlingRights {
-    pub fn new() -> Self {
-        Self {
-            white_kingside: true,
-            white_queenside: true,
-            black_kingside: true,
-            black_queenside: true,
-        }
-    }
+// 读取 y/n 回答，EOF视为否
+fn confirmed() -> bool {
+    input::read_line().is_some_and(|line| matches!(line.text.as_str(), "y" | "Y"))
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Position {
-    pub row: usize,
-    pub col: usize,
-}
+fn handle_promotion(color: Color) -> Piece {
+    println!("兵升变! 请选择升变的棋子:");
+    println!("1. 后 (Q)");
+    println!("2. 车 (R)");
+    println!("3. 象 (B)");
+    println!("4. 马 (N)");
 
-impl Position {
-    pub fn new(row: usize, col: usize) -> Option<Self> {
-        if row < 8 && col < 8 {
-            Some(Self { row, col })
-        } else {
-            None
-        }
-    }
+    // EOF时按缺省升变为后
+    let input = input::read_line().map(|line| line.text).unwrap_or_default();
 
-    pub fn from_notation(notation: &str) -> Option<Self> {
-        if notation.len() != 2 {
-            return None;
+    match input.as_str() {
+        "1" | "Q" | "q" => Piece::Queen(color),
+        "2" | "R" | "r" => Piece::Rook(color, true),
+        "3" | "B" | "b" => Piece::Bishop(color),
+        "4" | "N" | "n" => Piece::Knight(color),
+        _ => {
+            println!("无效选择，默认升变为后");
+            Piece::Queen(color)
         }
-        let mut chars = notation.chars();
-        let col_char = chars.next()?;
-        let row_char = chars.next()?;
-
-        let col = match col_char {
-            'a'..='h' => (col_char as usize) - ('a' as usize),
-            _ => return None,
-        };
-
-        let row = match row_char {
-            '1'..='8' => 8 - (row_char as usize - '1' as usize) - 1,
-            _ => return None,
-        };
-
-        Some(Self { row, col })
     }
-
-    pub fn to_notation(&self) -> String {
-        format!("{}{}", (b'a' + self.col as u8) as char, 8 - self.row)
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct Move {
-    pub from: Position,
-    pub to: Position,
-    pub promotion: Option<Piece>,
 }
 
-impl Move {
-    pub fn from_notation(notation: &str) -> Option<Self> {
-        let parts: Vec<&str> = notation.split_whitespace().collect();
-        if parts.len() < 2 {
-            return None;
+#[tokio::main] // 正确：使用Tokio宏包装同步main函数
+async fn main() {
+    let mut board = Chessboard::new();
+    let args: Vec<String> = env::args().collect();
+    // --strict：内部状态不一致时只中止本局并报告，调试构建中也不panic
+    board.set_strict(args.iter().any(|arg| arg == "--strict"));
+    // 结束提示语言：--lang en 使用英文，缺省中文
+    let language = if args
+        .windows(2)
+        .any(|pair| pair[0] == "--lang" && pair[1] == "en")
+    {
+        Language::English
+    } else {
+        Language::Chinese
+    };
+    let difficulty = match Difficulty::from_args(&args) {
+        Ok(difficulty) => difficulty,
+        Err(e) => {
+            println!("{}", e);
+            return;
         }
-
-        let from = Position::from_notation(parts[0])?;
-        let to = Position::from_notation(parts[1])?;
-
-        Some(Move {
-            from,
-            to,
-            promotion: None,
-        })
-    }
-
-    pub fn to_notation(&self) -> String {
-        format!("{} {}", self.from.to_notation(), self.to.to_notation())
-    }
-}
-
-impl Chessboard {
-    pub fn new() -> Self {
-        let mut board = [[None; 8]; 8];
-
-        // 初始化兵
-        for col in 0..8 {
-            board[1][col] = Some(Piece::Pawn(Color::Black, false));
-            board[6][col] = Some(Piece::Pawn(Color::White, false));
+    };
+    // 难度决定默认深度，--depth/--nodes/--movetime 可单独覆盖；
+    // 用于批量分析、复盘和走法评估，对局中的引擎走法按该方自己的设置
+    let limits =
+        match SearchLimits::from_args(&args, SearchLimits::depth(difficulty.search_depth())) {
+            Ok(limits) => limits,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+    // 双方各自的名称、难度、时间控制和辅助设置；--ai profile:名称 引用 chess.cfg 中的引擎配置
+    let config = std::fs::read_to_string(credentials::CONFIG_FILE).unwrap_or_default();
+    let players = match engine_profile::parse_profiles(&config)
+        .and_then(|profiles| Players::with_config(&args, &profiles, &config))
+    {
+        Ok(players) => players,
+        Err(e) => {
+            println!("{}", e);
+            return;
         }
-
-        // 初始化其他棋子 - 黑方
-        board[0][0] = Some(Piece::Rook(Color::Black, false));
-        board[0][1] = Some(Piece::Knight(Color::Black));
-        board[0][2] = Some(Piece::Bishop(Color::Black));
-        board[0][3] = Some(Piece::Queen(Color::Black));
-        board[0][4] = Some(Piece::King(Color::Black, false));
-        board[0][5] = Some(Piece::Bishop(Color::Black));
-        board[0][6] = Some(Piece::Knight(Color::Black));
-        board[0][7] = Some(Piece::Rook(Color::Black, false));
-
-        // 初始化其他棋子 - 白方
-        board[7][0] = Some(Piece::Rook(Color::White, false));
-        board[7][1] = Some(Piece::Knight(Color::White));
-        board[7][2] = Some(Piece::Bishop(Color::White));
-        board[7][3] = Some(Piece::Queen(Color::White));
-        board[7][4] = Some(Piece::King(Color::White, false));
-        board[7][5] = Some(Piece::Bishop(Color::White));
-        board[7][6] = Some(Piece::Knight(Color::White));
-        board[7][7] = Some(Piece::Rook(Color::White, false));
-
-        Chessboard {
-            board,
-            current_turn: Color::White,
-            castling_rights: CastlingRights::new(),
-            en_passant_target: None,
-            move_history: Vec::new(),
+    };
+    // 双方时钟；搜索限制每步按当时的剩余时间计算，这里先检查参数是否有效
+    let mut clocks = Clocks::start(&players);
+    for color in [Color::White, Color::Black] {
+        if let Err(e) = players.search_limits(color, &args, &clocks) {
+            println!("{}", e);
+            return;
         }
     }
-
-    pub fn get(&self, pos: Position) -> Square {
-        self.board[pos.row][pos.col]
-    }
-
-    pub fn current_turn(&self) -> Color {
-        self.current_turn
-    }
-
-    // 获取所有合法移动
-    pub fn get_legal_moves(&self, from: Position) -> Vec<Move> {
-        let mut moves = Vec::new();
-
-        let piece = match self.get(from) {
-            Some(piece) => piece,
-            None => return moves,
-        };
-
-        if piece.color() != self.current_turn {
-            return moves;
+    // 兵升变方式：--promotion / --auto-queen，或 chess.cfg 中的设置
+    let promotion_mode = match PromotionMode::load(&args) {
+        Ok(mode) => mode,
+        Err(e) => {
+            println!("{}", e);
+            return;
         }
-
-        match piece {
-            Piece::Pawn(color, _) => self.pawn_moves(from, color, &mut moves),
-            Piece::Knight(color) => self.knight_moves(from, color, &mut moves),
-            Piece::Bishop(color) => self.bishop_moves(from, color, &mut moves),
-            Piece::Rook(color, _) => self.rook_moves(from, color, &mut moves),
-            Piece::Queen(color) => self.queen_moves(from, color, &mut moves),
-            Piece::King(color, _) => self.king_moves(from, color, &mut moves),
+    };
+    // 终端棋盘的棋子字形：--glyphs 方案，或 chess.cfg 中的设置
+    let glyphs = match PieceGlyphs::load(&args) {
+        Ok(glyphs) => glyphs,
+        Err(e) => {
+            println!("{}", e);
+            return;
         }
+    };
+    // 输入来自管道或脚本时不弹出升变菜单
+    let interactive = io::stdin().is_terminal();
+    // 随机数种子写入录制文件，便于复现
+    let seed: u64 = rand::random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut engine_usage: BTreeMap<String, usize> = BTreeMap::new();
 
-        // 过滤掉会导致自己被将军的移动
-        moves
-            .into_iter()
-            .filter(|mv| {
-                let mut test_board = self.clone();
-                test_board.make_move_unchecked(mv);
-                !test_board.is_in_check(piece.color())
-            })
-            .collect()
-    }
-
-    // 随机合法走法（新增方法）
-    pub fn get_random_legal_move(&self) -> Option<Move> {
-        let mut all_legal_moves = Vec::new();
-
-        // 收集所有合法走法
-        for row in 0..8 {
-            for col in 0..8 {
-                let pos = Position::new(row, col).unwrap();
-                let moves = self.get_legal_moves(pos);
-                all_legal_moves.extend(moves);
-            }
+    // 子命令：固定工作量的性能基准，不需要API
+    if args.get(1).map(String::as_str) == Some("bench") {
+        if let Err(e) = bench::run() {
+            println!("{}", e);
         }
+        return;
+    }
 
-        if all_legal_moves.is_empty() {
-            return None;
+    // 子命令：新手教程，不需要API
+    if args.get(1).map(String::as_str) == Some("learn") {
+        if let Err(e) = learn::run(&args, &glyphs) {
+            println!("{}", e);
         }
-
-        // 随机选择一个走法
-        let mut rng = rand::thread_rng();
-        let random_index = rng.gen_range(0..all_legal_moves.len());
-        Some(all_legal_moves[random_index].clone())
+        return;
     }
 
-    // 兵的移动逻辑
-    fn pawn_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
-        let direction = match color {
-            Color::White => -1,
-            Color::Black => 1,
-        };
-
-        let new_row = from.row as i32 + direction;
-        if new_row < 0 || new_row >= 8 {
-            return;
+    // 子命令：perft分项计数，调试走法生成用，不需要API
+    if args.get(1).map(String::as_str) == Some("perft") {
+        if let Err(e) = bench::run_perft(&args) {
+            println!("{}", e);
         }
+        return;
+    }
 
-        let new_row = new_row as usize;
-
-        // 前进一格
-        if self.board[new_row][from.col].is_none() {
-            self.add_pawn_move(from, new_row, from.col, color, moves);
-
-            // 前进两格（初始位置）
-            let start_row = match color {
-                Color::White => 6,
-                Color::Black => 1,
-            };
-            if from.row == start_row {
-                let double_row = (from.row as i32 + 2 * direction) as usize;
-                if self.board[double_row][from.col].is_none() {
-                    moves.push(Move {
-                        from,
-                        to: Position {
-                            row: double_row,
-                            col: from.col,
-                        },
-                        promotion: None,
-                    });
-                }
-            }
+    // 子命令：列出、导出、导入命名的引擎配置，不需要API
+    if args.get(1).map(String::as_str) == Some("profile") {
+        if let Err(e) = engine_profile::run(&args) {
+            println!("{}", e);
         }
+        return;
+    }
 
-        // 吃子（左侧）
-        if from.col > 0 {
-            let left_col = from.col - 1;
-            if self.can_capture(Position::new(new_row, left_col).unwrap(), color) {
-                self.add_pawn_move(from, new_row, left_col, color, moves);
-            }
+    // 子命令：用EPD测试题检验本地引擎，不需要API
+    if args.get(1).map(String::as_str) == Some("epdtest") {
+        if let Err(e) = epd::run(&args) {
+            println!("{}", e);
         }
+        return;
+    }
 
-        // 吃子（右侧）
-        if from.col < 7 {
-            let right_col = from.col + 1;
-            if self.can_capture(Position::new(new_row, right_col).unwrap(), color) {
-                self.add_pawn_move(from, new_row, right_col, color, moves);
-            }
+    // 子命令：PGN/FEN往返一致性检查，不需要API
+    if args.get(1).map(String::as_str) == Some("verify") {
+        if let Err(e) = verify::run(&args) {
+            println!("{}", e);
         }
+        return;
+    }
 
-        // 吃过路兵
-        if let Some(en_passant_pos) = self.en_passant_target {
-            if en_passant_pos.row == new_row
-                && (en_passant_pos.col as i32 - from.col as i32).abs() == 1
-            {
-                let en_passant_direction = match color {
-                    Color::White => -1,
-                    Color::Black => 1,
-                };
-                let pawn_behind_row = (en_passant_pos.row as i32 - en_passant_direction) as usize;
-
-                if let Some(Piece::Pawn(opponent_color, _)) =
-                    self.board[pawn_behind_row][en_passant_pos.col]
-                {
-                    if opponent_color != color {
-                        moves.push(Move {
-                            from,
-                            to: en_passant_pos,
-                            promotion: None,
-                        });
-                    }
-                }
+    // 回放录制的对局（10倍速），每步核对局面，不需要API
+    if let Some(path) = flag_value(&args, "--replay") {
+        let result = session_log::load(path).and_then(|log| {
+            println!("回放 {} (种子 {})", path, log.seed);
+            session_log::replay(&log, 10, |index, board| {
+                println!("第{}步", index + 1);
+                println!("{}", board.display(&glyphs));
+            })
+        });
+        match result {
+            Ok(board) => {
+                println!("回放完成，局面与录制一致");
+                board.display_move_history();
             }
+            Err(e) => println!("回放失败: {}", e),
         }
+        return;
     }
+    let mut recorder = flag_value(&args, "--record").map(|path| Recorder::new(path, seed));
 
-    fn add_pawn_move(
-        &self,
-        from: Position,
-        to_row: usize,
-        to_col: usize,
-        color: Color,
-        moves: &mut Vec<Move>,
-    ) {
-        let promotion_row = match color {
-            Color::White => 0,
-            Color::Black => 7,
+    // 子命令：批量分析FEN文件，只能用云端后端，此时才读取API密钥
+    if args.get(1).map(String::as_str) == Some("analyze-file") {
+        let result = match credentials::load_api_key(&args) {
+            Ok(key) => bulk_analysis::run(&args, SiliconFlowClient::new(key), limits).await,
+            Err(e) => Err(e),
         };
-
-        if to_row == promotion_row {
-            // 升变选择
-            let promotions = [
-                Piece::Queen(color),
-                Piece::Rook(color, true),
-                Piece::Bishop(color),
-                Piece::Knight(color),
-            ];
-            for &promotion in &promotions {
-                moves.push(Move {
-                    from,
-                    to: Position {
-                        row: to_row,
-                        col: to_col,
-                    },
-                    promotion: Some(promotion),
-                });
-            }
-        } else {
-            moves.push(Move {
-                from,
-                to: Position {
-                    row: to_row,
-                    col: to_col,
-                },
-                promotion: None,
-            });
+        if let Err(e) = result {
+            println!("{}", e);
         }
+        return;
     }
 
-    // 马的移动逻辑
-    fn knight_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
-        let knight_moves = [
-            (-2, -1),
-            (-2, 1),
-            (-1, -2),
-            (-1, 2),
-            (1, -2),
-            (1, 2),
-            (2, -1),
-            (2, 1),
-        ];
-
-        for &(dr, dc) in &knight_moves {
-            let new_row = from.row as i32 + dr;
-            let new_col = from.col as i32 + dc;
-
-            if new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                let new_row = new_row as usize;
-                let new_col = new_col as usize;
-                let to_pos = Position::new(new_row, new_col).unwrap();
+    // 对局中第一次用到云端引擎时才读取API密钥，没有密钥时改用本地搜索
+    let mut cloud = CloudEngine::new(&args);
 
-                if self.can_move_to(to_pos, color) {
-                    moves.push(Move {
-                        from,
-                        to: to_pos,
-                        promotion: None,
-                    });
-                }
-            }
+    println!("欢迎来到国际象棋!");
+    for color in [Color::White, Color::Black] {
+        let player = players.get(color);
+        if player.controller == Controller::Engine {
+            println!(
+                "{} AI难度: {}级 (可用 --level 或 --level-white/--level-black 调整)",
+                color,
+                player.difficulty.level()
+            );
         }
     }
+    println!("输入格式: 起始位置 目标位置 (例如: e2 e4)");
+    println!("兵升变时可在末尾加升变棋子 (例如: e7 e8 n)");
+    println!("特殊命令:");
+    println!("  'history' - 显示移动历史");
+    println!("  'pgn' - 显示本局PGN, 'save <文件>' - 保存PGN");
+    println!("  'load <文件>' - 载入PGN对局并从最终局面继续");
+    println!("  'info e4' - 显示双方攻击/保护该格的棋子 (需开启 --assist)");
+    println!("  'undo' - 悔棋 (人机对局时连同AI的应着一起撤销)");
+    println!("  'resign' - 认输");
+    println!("  'quit' - 退出游戏");
+    println!("  'help' - 显示帮助");
 
-    // 象的移动逻辑
-    fn bishop_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
-        let directions = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
-        self.sliding_moves(from, color, &directions, moves);
-    }
-
-    // 车的移动逻辑
-    fn rook_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
-        let directions = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-        self.sliding_moves(from, color, &directions, moves);
-    }
-
-    // 后的移动逻辑
-    fn queen_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
-        let directions = [
-            (-1, -1),
-            (-1, 1),
-            (1, -1),
-            (1, 1),
-            (-1, 0),
-            (1, 0),
-            (0, -1),
-            (0, 1),
-        ];
-        self.sliding_moves(from, color, &directions, moves);
-    }
-
-    // 王的移动逻辑（包括王车易位）
-    fn king_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
-        let king_moves = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-
-        for &(dr, dc) in &king_moves {
-            let new_row = from.row as i32 + dr;
-            let new_col = from.col as i32 + dc;
-
-            if new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                let new_row = new_row as usize;
-                let new_col = new_col as usize;
-                let to_pos = Position::new(new_row, new_col).unwrap();
+    // 终局视角：只有黑方由人操作时黑方在下方
+    let final_view = RenderOptions {
+        glyphs: glyphs.clone(),
+        bottom: match (
+            players.get(Color::White).controller,
+            players.get(Color::Black).controller,
+        ) {
+            (Controller::Engine, Controller::Human) => Color::Black,
+            _ => Color::White,
+        },
+        footer: true,
+    };
+
+    // 双方都是引擎时按两边的评分裁决，避免无意义地下完胜负已定或死和的对局
+    let mut adjudicator = Adjudicator::for_players(
+        AdjudicationConfig::default(),
+        players.get(Color::White).controller == Controller::Engine,
+        players.get(Color::Black).controller == Controller::Engine,
+    );
+    let mut adjudication: Option<Adjudication> = None;
+    // 超时的一方
+    let mut flagged: Option<Color> = None;
+    // 本回合开始思考的时刻，走完一步后从该方时钟扣除用时
+    let mut turn_started = Instant::now();
+    // 每步走完后走子方的剩余时间，导出带注释的PGN时写成 [%clk]
+    let mut move_clocks: Vec<Option<Duration>> = Vec::new();
 
-                if self.can_move_to(to_pos, color) {
-                    moves.push(Move {
-                        from,
-                        to: to_pos,
-                        promotion: None,
-                    });
-                }
-            }
+    loop {
+        if let Err(e) = board.check_consistency() {
+            println!("对局中止: {}", e);
+            break;
         }
-
-        // 王车易位
-        self.castling_moves(from, color, moves);
-    }
-
-    // 王车易位逻辑
-    fn castling_moves(&self, from: Position, color: Color, moves: &mut Vec<Move>) {
-        if self.is_in_check(color) {
-            return;
+        // 将死或和棋（多种和棋原因同时成立时一并列出），最终局面按终局视角显示
+        if board.status() != GameStatus::Ongoing {
+            println!("{}", board.render(&final_view));
+            println!("{}", board.describe_status(language));
+            break;
         }
-
-        let (kingside_right, queenside_right, back_rank) = match color {
-            Color::White => (
-                self.castling_rights.white_kingside,
-                self.castling_rights.white_queenside,
-                7,
-            ),
-            Color::Black => (
-                self.castling_rights.black_kingside,
-                self.castling_rights.black_queenside,
-                0,
-            ),
-        };
-
-        // 短易位（王翼易位）
-        if kingside_right {
-            if self.board[back_rank][5].is_none()
-                && self.board[back_rank][6].is_none()
-                && !self.is_square_attacked(Position::new(back_rank, 4).unwrap(), color.opposite())
-                && !self.is_square_attacked(Position::new(back_rank, 5).unwrap(), color.opposite())
-                && !self.is_square_attacked(Position::new(back_rank, 6).unwrap(), color.opposite())
-            {
-                moves.push(Move {
-                    from,
-                    to: Position {
-                        row: back_rank,
-                        col: 6,
-                    },
-                    promotion: None,
-                });
-            }
+        println!("{}", board.display(&glyphs));
+        if let Some(line) = clock_line(|color| clocks.remaining(color)) {
+            println!("{}", line);
         }
 
-        // 长易位（后翼易位）
-        if queenside_right {
-            if self.board[back_rank][1].is_none()
-                && self.board[back_rank][2].is_none()
-                && self.board[back_rank][3].is_none()
-                && !self.is_square_attacked(Position::new(back_rank, 2).unwrap(), color.opposite())
-                && !self.is_square_attacked(Position::new(back_rank, 3).unwrap(), color.opposite())
-                && !self.is_square_attacked(Position::new(back_rank, 4).unwrap(), color.opposite())
-            {
-                moves.push(Move {
-                    from,
-                    to: Position {
-                        row: back_rank,
-                        col: 2,
-                    },
-                    promotion: None,
-                });
-            }
+        let repetitions = board.repetition_count();
+        if repetitions > 1 {
+            println!("当前局面已出现{}次", repetitions);
         }
-    }
 
-    // 滑动棋子（象、车、后）的通用移动逻辑
-    fn sliding_moves(
-        &self,
-        from: Position,
-        color: Color,
-        directions: &[(i32, i32)],
-        moves: &mut Vec<Move>,
-    ) {
-        for &(dr, dc) in directions {
-            let mut new_row = from.row as i32 + dr;
-            let mut new_col = from.col as i32 + dc;
-
-            while new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                let new_row_usize = new_row as usize;
-                let new_col_usize = new_col as usize;
-                let to_pos = Position::new(new_row_usize, new_col_usize).unwrap();
-
-                if self.board[new_row_usize][new_col_usize].is_none() {
-                    moves.push(Move {
-                        from,
-                        to: to_pos,
-                        promotion: None,
-                    });
-                } else {
-                    if self.can_capture(to_pos, color) {
-                        moves.push(Move {
-                            from,
-                            to: to_pos,
-                            promotion: None,
+        // 本步AI走法的来源（哪个后端给出的）
+        let mut ai_source: Option<String> = None;
+        // 本步引擎给出的评分（白方视角），供裁决使用
+        let mut engine_score: Option<i32> = None;
+        let mover = board.current_turn();
+        let player = players.get(board.current_turn());
+        let mv = if player.controller == Controller::Engine {
+            // AI回合，使用该方自己的难度和搜索限制
+            println!("AI思考中...");
+            let fen = board.to_fen();
+            let limits = &players
+                .search_limits(mover, &args, &clocks)
+                .expect("搜索参数已在开局时检查");
+
+            let depth = player.difficulty.local_depth(limits);
+
+            let rank = player.difficulty.move_rank(&mut rng);
+            if rank > 0 {
+                // 低难度时按本地搜索的分值排名改走次优着法
+                let (mv, score) = player
+                    .difficulty
+                    .local_move(&board, limits, rank)
+                    .expect("无合法走法");
+                engine_score = Some(white_view(mover, score));
+                ai_source = Some(format!("local-rank{}-d{}", rank + 1, depth));
+                mv
+            } else {
+                let from_api = match cloud.client() {
+                    Some(client) => Some(client.analyze(&fen, limits).await),
+                    None => None,
+                };
+                match from_api {
+                    Some(Ok((move_from_api, score))) => {
+                        engine_score = Some(white_view(mover, score));
+                        ai_source = Some(match limits.depth {
+                            Some(depth) => format!("siliconflow-d{}", depth),
+                            None => "siliconflow".to_string(),
                         });
+                        move_from_api
+                    }
+                    failed => {
+                        if let Some(Err(e)) = failed {
+                            println!("API调用失败: {:?}, 使用本地搜索", e);
+                        }
+                        ai_source = Some(format!("local-search-d{}", depth));
+                        let (mv, score) = player
+                            .difficulty
+                            .local_move(&board, limits, 0)
+                            .expect("无合法走法");
+                        engine_score = Some(white_view(mover, score));
+                        mv
                     }
-                    break;
                 }
-
-                new_row += dr;
-                new_col += dc;
             }
-        }
-    }
-
-    fn can_move_to(&self, to: Position, color: Color) -> bool {
-        match self.board[to.row][to.col] {
-            Some(piece) => piece.color() != color,
-            None => true,
-        }
-    }
-
-    fn can_capture(&self, to: Position, color: Color) -> bool {
-        match self.board[to.row][to.col] {
-            Some(piece) => piece.color() != color,
-            None => false,
-        }
-    }
-
-    pub fn make_move(&mut self, mv: &Move) -> Result<(), String> {
-        let legal_moves = self.get_legal_moves(mv.from);
-        if !legal_moves
-            .iter()
-            .any(|legal_move| legal_move.from == mv.from && legal_move.to == mv.to)
-        {
-            return Err("非法的移动".to_string());
-        }
-
-        let move_notation = mv.to_notation();
-        if let Some(promotion) = mv.promotion {
-            let promotion_symbol = match promotion {
-                Piece::Queen(_) => "Q",
-                Piece::Rook(_, _) => "R",
-                Piece::Bishop(_) => "B",
-                Piece::Knight(_) => "N",
-                _ => "",
-            };
-            self.move_history
-                .push(format!("{}{}", move_notation, promotion_symbol));
         } else {
-            self.move_history.push(move_notation);
-        }
-
-        self.make_move_unchecked(mv);
-        Ok(())
-    }
+            // 玩家回合
+            println!("\n{}的回合，请输入移动:", board.current_turn());
 
-    fn make_move_unchecked(&mut self, mv: &Move) {
-        let piece = self.board[mv.from.row][mv.from.col].take().unwrap();
+            // 输入结束（EOF）视同退出
+            let input = match input::read_line() {
+                Some(line) => line.text,
+                None => "quit".to_string(),
+            };
+            let input = input.as_str();
 
-        // 处理王车易位
-        if let Piece::King(color, _) = piece {
-            if (mv.from.col as i32 - mv.to.col as i32).abs() == 2 {
-                if mv.to.col == 6 {
-                    let rook = self.board[mv.from.row][7].take().unwrap();
-                    self.board[mv.from.row][5] = Some(rook);
-                } else if mv.to.col == 2 {
-                    let rook = self.board[mv.from.row][0].take().unwrap();
-                    self.board[mv.from.row][3] = Some(rook);
+            match input {
+                "quit" | "exit" => {
+                    record(&mut recorder, Action::Quit, &board);
+                    println!("游戏结束!");
+                    break;
                 }
-            }
-
-            match color {
-                Color::White => {
-                    self.castling_rights.white_kingside = false;
-                    self.castling_rights.white_queenside = false;
+                "resign" => {
+                    board.conclude(Outcome::Resigned(board.current_turn()));
+                    record(&mut recorder, Action::Resign, &board);
+                    println!(
+                        "{}认输, {}获胜!",
+                        board.current_turn(),
+                        board.current_turn().opposite()
+                    );
+                    break;
                 }
-                Color::Black => {
-                    self.castling_rights.black_kingside = false;
-                    self.castling_rights.black_queenside = false;
+                "history" => {
+                    board.display_move_history();
+                    continue;
                 }
-            }
-        }
-
-        // 处理车移动（更新易位权利）
-        if let Piece::Rook(color, _) = piece {
-            match color {
-                Color::White => {
-                    if mv.from.col == 0 {
-                        self.castling_rights.white_queenside = false;
-                    } else if mv.from.col == 7 {
-                        self.castling_rights.white_kingside = false;
-                    }
+                "pgn" => {
+                    print!("{}", pgn::game(&board, &players, &move_clocks));
+                    continue;
                 }
-                Color::Black => {
-                    if mv.from.col == 0 {
-                        self.castling_rights.black_queenside = false;
-                    } else if mv.from.col == 7 {
-                        self.castling_rights.black_kingside = false;
+                "undo" => {
+                    // 人机对局时连同AI的应着一起撤销，回到自己走棋
+                    let mut undone = 0;
+                    while board.undo_last().is_ok() {
+                        record(&mut recorder, Action::Undo, &board);
+                        undone += 1;
+                        if players.get(board.current_turn()).controller == Controller::Human {
+                            break;
+                        }
                     }
+                    turn_started = Instant::now();
+                    move_clocks.truncate(board.move_history().len());
+                    match undone {
+                        0 => println!("没有可以悔棋的走法"),
+                        n => println!("已悔棋{}步", n),
+                    }
+                    continue;
                 }
-            }
-        }
-
-        // 处理兵的移动
-        let mut is_en_passant = false;
-        if let Piece::Pawn(_color, _) = piece {
-            if let Some(en_passant_pos) = self.en_passant_target {
-                if mv.to.row == en_passant_pos.row && mv.to.col == en_passant_pos.col {
-                    is_en_passant = true;
-                    let capture_row = mv.from.row;
-                    self.board[capture_row][mv.to.col] = None;
+                "help" => {
+                    println!("输入格式: 起始位置 目标位置 (例如: e2 e4)");
+                    println!("兵升变时可在末尾加升变棋子 (例如: e7 e8 n)");
+                    println!("特殊命令:");
+                    println!("  'history' - 显示移动历史");
+                    println!("  'pgn' - 显示本局PGN, 'save <文件>' - 保存PGN");
+                    println!("  'load <文件>' - 载入PGN对局并从最终局面继续");
+                    println!("  'info e4' - 显示双方攻击/保护该格的棋子 (需开启 --assist)");
+                    println!("  'undo' - 悔棋 (人机对局时连同AI的应着一起撤销)");
+                    println!("  'resign' - 认输");
+                    println!("  'quit' - 退出游戏");
+                    println!("  'help' - 显示帮助");
+                    continue;
                 }
+                _ => {}
             }
 
-            if (mv.from.row as i32 - mv.to.row as i32).abs() == 2 {
-                let en_passant_row = (mv.from.row + mv.to.row) / 2;
-                self.en_passant_target = Some(Position::new(en_passant_row, mv.from.col).unwrap());
-            } else {
-                self.en_passant_target = None;
-            }
-
-            if let Some(promotion) = mv.promotion {
-                self.board[mv.to.row][mv.to.col] = Some(promotion);
-                self.current_turn = self.current_turn.opposite();
-                return;
+            if let Some(path) = input.strip_prefix("save ") {
+                match std::fs::write(path.trim(), pgn::game(&board, &players, &move_clocks)) {
+                    Ok(_) => println!("PGN已保存到 {}", path.trim()),
+                    Err(e) => println!("保存失败: {}", e),
+                }
+                continue;
             }
-        } else {
-            self.en_passant_target = None;
-        }
-
-        if !is_en_passant {
-            self.board[mv.to.row][mv.to.col] = None;
-        }
-
-        self.board[mv.to.row][mv.to.col] = Some(piece);
-        self.current_turn = self.current_turn.opposite();
-    }
-
-    pub fn is_in_check(&self, color: Color) -> bool {
-        let king_pos = self.find_king(color);
-        self.is_square_attacked(king_pos, color.opposite())
-    }
 
-    pub fn is_checkmate(&self) -> bool {
-        if !self.is_in_check(self.current_turn) {
-            return false;
-        }
-
-        for row in 0..8 {
-            for col in 0..8 {
-                let pos = Position::new(row, col).unwrap();
-                if let Some(piece) = self.get(pos) {
-                    if piece.color() == self.current_turn {
-                        if !self.get_legal_moves(pos).is_empty() {
-                            return false;
+            // 载入PGN对局，从最终局面继续下；录制中的对局无法回放载入的局面，不允许载入
+            if let Some(path) = input.strip_prefix("load ") {
+                let loaded = if recorder.is_some() {
+                    Err("录制中不能载入对局".to_string())
+                } else {
+                    std::fs::read_to_string(path.trim())
+                        .map_err(|e| format!("无法读取 {}: {}", path.trim(), e))
+                        .and_then(|text| Chessboard::from_pgn(&text))
+                };
+                match loaded {
+                    Ok((loaded, moves, loaded_clocks)) => {
+                        board = loaded;
+                        board.set_strict(args.iter().any(|arg| arg == "--strict"));
+                        turn_started = Instant::now();
+                        move_clocks = loaded_clocks;
+                        println!("已载入{}步，从最终局面继续", moves.len());
+                        // PGN里记录的用时：每方最后一次 [%clk] 的剩余时间
+                        let last_mover = board.current_turn().opposite();
+                        let recorded = |color: Color| {
+                            move_clocks
+                                .iter()
+                                .rev()
+                                .enumerate()
+                                .filter(|(back, _)| (back % 2 == 0) == (color == last_mover))
+                                .find_map(|(_, clock)| *clock)
+                        };
+                        if let Some(line) = clock_line(recorded) {
+                            println!("{}", line);
                         }
                     }
+                    Err(e) => println!("载入失败: {}", e),
                 }
+                // 回到主循环开头显示棋盘，轮到引擎时由引擎走
+                continue;
             }
-        }
-
-        true
-    }
-
-    pub fn is_stalemate(&self) -> bool {
-        if self.is_in_check(self.current_turn) {
-            return false;
-        }
 
-        for row in 0..8 {
-            for col in 0..8 {
-                let pos = Position::new(row, col).unwrap();
-                if let Some(piece) = self.get(pos) {
-                    if piece.color() == self.current_turn {
-                        if !self.get_legal_moves(pos).is_empty() {
-                            return false;
-                        }
+            // 辅助模式：查看某格被双方攻击/保护的情况；关闭辅助时不提供，保证对局公平
+            if let Some(square) = input.strip_prefix("info ") {
+                if !player.assist {
+                    println!("未开启辅助模式 (--assist)，info 命令不可用");
+                } else if let Some(pos) = Position::from_notation(square.trim()) {
+                    for line in board.square_report(pos) {
+                        println!("{}", line);
                     }
+                } else {
+                    println!("无效的格子: {}", square.trim());
                 }
+                continue;
             }
-        }
-
-        true
-    }
 
-    fn find_king(&self, color: Color) -> Position {
-        for row in 0..8 {
-            for col in 0..8 {
-                if let Some(Piece::King(king_color, _)) = self.board[row][col] {
-                    if king_color == color {
-                        return Position { row, col };
-                    }
+            // 也接受 e2e4、O-O、e1h1 等写法
+            let mut mv = match board.parse_move(input) {
+                Ok(mv) => mv,
+                Err(e) => {
+                    println!("{}，请使用格式: e2 e4", e);
+                    continue;
                 }
-            }
-        }
-        panic!("King not found!");
-    }
+            };
 
-    fn is_square_attacked(&self, pos: Position, by_color: Color) -> bool {
-        // 检查被马攻击
-        let knight_moves = [
-            (-2, -1),
-            (-2, 1),
-            (-1, -2),
-            (-1, 2),
-            (1, -2),
-            (1, 2),
-            (2, -1),
-            (2, 1),
-        ];
+            // 兵升变：走法后没有带升变字母（如 "e7 e8 n"）时按升变方式决定
+            if board.is_promotion(&mv) && mv.promotion.is_none() {
+                let color = board.current_turn();
+                mv.promotion = Some(
+                    resolve_promotion(promotion_mode, interactive, color)
+                        .unwrap_or_else(|| handle_promotion(color)),
+                );
+            }
 
-        for &(dr, dc) in &knight_moves {
-            let new_row = pos.row as i32 + dr;
-            let new_col = pos.col as i32 + dc;
+            // 辅助模式：走子前提示会被悬挂的己方棋子，以及直接逼和或给对方留下逼和手段
+            if player.assist {
+                let mut preview = board.clone();
+                if preview.make_move(&mv).is_ok() {
+                    let hanging = preview.hanging_pieces(board.current_turn());
+                    if !hanging.is_empty() {
+                        let squares: Vec<String> = hanging
+                            .iter()
+                            .map(|&pos| {
+                                format!("{}{}", preview.get(pos).unwrap().name(), pos.to_notation())
+                            })
+                            .collect();
+                        println!("警告: 这步棋后以下棋子保护不足: {}", squares.join(", "));
+                        println!("仍要走这步吗? (y/n)");
+
+                        if !confirmed() {
+                            continue;
+                        }
+                    }
 
-            if new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                if let Some(Piece::Knight(color)) = self.board[new_row as usize][new_col as usize] {
-                    if color == by_color {
-                        return true;
+                    if board.stalemates_opponent(&mv) {
+                        println!("这步会造成逼和（和棋）！确认吗? (y/n)");
+                        if !confirmed() {
+                            continue;
+                        }
+                    } else if let Some(reply) = board.stalemate_reply(&mv) {
+                        println!(
+                            "警告: 这步棋后对方可以走 {} 强制逼和（和棋）！确认吗? (y/n)",
+                            preview.move_to_san(&reply)
+                        );
+                        if !confirmed() {
+                            continue;
+                        }
                     }
                 }
             }
-        }
 
-        // 检查被兵攻击
-        let pawn_direction = match by_color {
-            Color::White => 1,
-            Color::Black => -1,
+            mv
         };
 
-        for &dc in &[-1, 1] {
-            let new_row = pos.row as i32 + pawn_direction;
-            let new_col = pos.col as i32 + dc;
-
-            if new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                if let Some(Piece::Pawn(color, _)) = self.board[new_row as usize][new_col as usize]
-                {
-                    if color == by_color {
-                        return true;
-                    }
+        let plies = board.move_history().len();
+        match board.make_move(&mv) {
+            Ok(_) => println!(
+                "{}",
+                board.describe_last_move(language).expect("刚走过一步")
+            ),
+            Err(e @ MoveError::InternalInconsistency(_)) => {
+                println!("对局中止: {}", e);
+                break;
+            }
+            Err(e) => {
+                println!("移动失败: {}", e);
+                if player.controller == Controller::Engine {
+                    // AI走法非法时改用本地搜索
+                    println!("AI走法非法，使用本地搜索");
+                    let depth = player.difficulty.search_depth();
+                    let (backup_move, score) = player
+                        .difficulty
+                        .local_move(&board, &SearchLimits::depth(depth), 0)
+                        .expect("无合法走法");
+                    engine_score = Some(white_view(mover, score));
+                    board.make_move(&backup_move).unwrap();
+                    println!(
+                        "{}",
+                        board.describe_last_move(language).expect("刚走过一步")
+                    );
+                    ai_source = Some(format!("local-search-d{}", depth));
                 }
             }
         }
 
-        // 检查被滑动棋子攻击
-        let sliding_directions = [
-            (-1, -1),
-            (-1, 1),
-            (1, -1),
-            (1, 1),
-            (-1, 0),
-            (1, 0),
-            (0, -1),
-            (0, 1),
-        ];
+        if board.move_history().len() > plies {
+            clocks.spend(mover, turn_started.elapsed());
+            turn_started = Instant::now();
+            move_clocks.push(clocks.remaining(mover));
+            let notation = board.move_history()[plies].clone();
+            record(&mut recorder, Action::Move { notation }, &board);
+        }
 
-        for &(dr, dc) in &sliding_directions {
-            let mut new_row = pos.row as i32 + dr;
-            let mut new_col = pos.col as i32 + dc;
+        if let Some(source) = ai_source {
+            println!("AI走法来源: {}", source);
+            *engine_usage.entry(source).or_insert(0) += 1;
+        }
 
-            while new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                let new_row_usize = new_row as usize;
-                let new_col_usize = new_col as usize;
+        if clocks.flagged(mover) {
+            board.conclude(Outcome::TimeForfeit(mover));
+            println!("{}", board.render(&final_view));
+            match board.flag_fall_winner(mover) {
+                Some(winner) => println!("{}超时, {}获胜!", mover, winner),
+                None => println!("{}超时, 但{}没有将杀可能, 和棋!", mover, mover.opposite()),
+            }
+            flagged = Some(mover);
+            break;
+        }
 
-                if let Some(piece) = self.board[new_row_usize][new_col_usize] {
-                    if piece.color() == by_color {
-                        match piece {
-                            Piece::Queen(_) => return true,
-                            Piece::Rook(_, _) if dr == 0 || dc == 0 => return true,
-                            Piece::Bishop(_) if dr != 0 && dc != 0 => return true,
-                            _ => (),
-                        }
+        if let (Some(adjudicator), Some(score)) = (adjudicator.as_mut(), engine_score) {
+            if let Some(verdict) = adjudicator.record_move(mover, score) {
+                board.conclude(verdict.outcome());
+                println!("{}", board.render(&final_view));
+                match verdict {
+                    Adjudication::Draw => println!("双方引擎长期评估为均势，裁决和棋"),
+                    Adjudication::Win(winner) => {
+                        println!("双方引擎一致评估{}胜势，裁决{}获胜", winner, winner)
                     }
-                    break;
                 }
-                new_row += dr;
-                new_col += dc;
+                adjudication = Some(verdict);
+                break;
             }
         }
+    } // 游戏主循环结束（loop {} 闭合）
 
-        // 检查被王攻击
-        let king_moves = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-
-        for &(dr, dc) in &king_moves {
-            let new_row = pos.row as i32 + dr;
-            let new_col = pos.col as i32 + dc;
-
-            if new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                if let Some(Piece::King(color, _)) = self.board[new_row as usize][new_col as usize]
-                {
-                    if color == by_color {
-                        return true;
+    // 裁决或超时结束的对局已把结束方式记录在棋盘上
+    let result = pgn::result_tag(&board);
+
+    // 终局后可进入分析棋盘，exit 后回到对局总结
+    if interactive && !board.move_history().is_empty() {
+        println!("是否进入分析模式复盘终局局面? (y/n)");
+        if confirmed() {
+            let client = cloud.client();
+            analysis::run(&board, result, client, &limits, language, &players, &glyphs).await;
+        }
+    }
+
+    // 可选：引擎逐步评估本局，给出走法质量报告并导出带注释的PGN
+    let mut quality_report = Vec::new();
+    let mut score_graph = String::new();
+    if interactive && !board.move_history().is_empty() {
+        println!("是否让引擎逐步评估本局并生成走法质量报告? (y/n)");
+        if let Some(client) = confirmed().then(|| cloud.client()).flatten() {
+            let evals = move_quality::evaluate_game(board.move_history(), |fen| async move {
+                client
+                    .analyze(&fen, &limits)
+                    .await
+                    .map(|(_, score)| score)
+                    .map_err(|e| e.to_string())
+            })
+            .await;
+            match evals {
+                Ok(evals) => {
+                    let mut records = move_quality::classify_game(board.move_history(), &evals);
+                    for (record, &clock) in records.iter_mut().zip(&move_clocks) {
+                        record.clock = clock;
+                    }
+                    quality_report = move_quality::report_lines(&records);
+                    score_graph = move_quality::sparkline(&records);
+                    let text = pgn::annotated_game(&records, result, &players);
+                    match std::fs::write("annotated.pgn", text) {
+                        Ok(_) => println!("带注释的PGN已保存到 annotated.pgn"),
+                        Err(e) => println!("保存失败: {}", e),
                     }
                 }
+                Err(e) => println!("评估失败: {}", e),
             }
         }
-
-        false
     }
 
-    pub fn display(&self) {
-        println!("  a b c d e f g h");
-        println!("  ----------------");
-
-        for row in 0..8 {
-            print!("{}|", 8 - row);
-            for col in 0..8 {
-                let symbol = match self.board[row][col] {
-                    Some(Piece::King(Color::White, _)) => "♔",
-                    Some(Piece::Queen(Color::White)) => "♕",
-                    Some(Piece::Rook(Color::White, _)) => "♖",
-                    Some(Piece::Bishop(Color::White)) => "♗",
-                    Some(Piece::Knight(Color::White)) => "♘",
-                    Some(Piece::Pawn(Color::White, _)) => "♙",
-                    Some(Piece::King(Color::Black, _)) => "♚",
-                    Some(Piece::Queen(Color::Black)) => "♛",
-                    Some(Piece::Rook(Color::Black, _)) => "♜",
-                    Some(Piece::Bishop(Color::Black)) => "♝",
-                    Some(Piece::Knight(Color::Black)) => "♞",
-                    Some(Piece::Pawn(Color::Black, _)) => "♟",
-                    None => " ",
-                };
-                print!("{}", symbol);
-                if col < 7 {
-                    print!(" ");
+    // 游戏结束后显示对局结果、移动历史和AI走法来源统计
+    println!("{}", players.result_line(result));
+    board.display_move_history();
+    if interactive && !board.move_history().is_empty() {
+        println!("输入文件名保存本局PGN (直接回车跳过):");
+        if let Some(line) = input::read_line().filter(|line| !line.text.trim().is_empty()) {
+            let path = line.text.trim();
+            let text = match (adjudication, flagged) {
+                (Some(verdict), _) => {
+                    pgn::adjudicated_game(&board, &players, &move_clocks, verdict)
                 }
+                (None, Some(color)) => pgn::flag_fall_game(&board, &players, &move_clocks, color),
+                (None, None) => pgn::game(&board, &players, &move_clocks),
+            };
+            match std::fs::write(path, text) {
+                Ok(_) => println!("PGN已保存到 {}", path),
+                Err(e) => println!("保存失败: {}", e),
             }
-            println!("|{}", 8 - row);
-        }
-
-        println!("  ----------------");
-        println!("  a b c d e f g h");
-        println!("当前回合: {}", self.current_turn);
-
-        if self.is_in_check(self.current_turn) {
-            println!("{}被将军!", self.current_turn);
         }
     }
-
-    pub fn display_move_history(&self) {
-        println!("移动历史:");
-        for (i, mv) in self.move_history.iter().enumerate() {
-            println!("{}. {}", i + 1, mv);
+    if !engine_usage.is_empty() {
+        println!("AI走法来源统计:");
+        for (source, count) in &engine_usage {
+            println!("  {}: {}步", source, count);
         }
     }
-}
-
-fn handle_promotion(color: Color) -> Piece {
-    println!("兵升变! 请选择升变的棋子:");
-    println!("1. 后 (Q)");
-    println!("2. 车 (R)");
-    println!("3. 象 (B)");
-    println!("4. 马 (N)");
-
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("读取输入失败");
-
-    match input.trim() {
-        "1" | "Q" | "q" => Piece::Queen(color),
-        "2" | "R" | "r" => Piece::Rook(color, true),
-        "3" | "B" | "b" => Piece::Bishop(color),
-        "4" | "N" | "n" => Piece::Knight(color),
-        _ => {
-            println!("无效选择，默认升变为后");
-            Piece::Queen(color)
+    if !quality_report.is_empty() {
+        println!("走法质量:");
+        for line in &quality_report {
+            println!("  {}", line);
         }
     }
-}
-
-#[tokio::main] // 正确：使用Tokio宏包装同步main函数
-async fn main() {
-    let mut board = Chessboard::new();
-    let ai_client = SiliconFlowClient::new(
-        env::var("SILICON_FLOW_API_KEY").expect("请设置环境变量 SILICON_FLOW_API_KEY"),
-    );
-
-    println!("欢迎来到国际象棋!");
-    println!("输入格式: 起始位置 目标位置 (例如: e2 e4)");
-    println!("特殊命令:");
-    println!("  'history' - 显示移动历史");
-    println!("  'quit' - 退出游戏");
-    println!("  'help' - 显示帮助");
-
-    loop {
-        board.display();
-
-        if board.is_checkmate() {
-            println!("将死! {}获胜!", board.current_turn().opposite());
-            break;
-        }
-
-        if board.is_stalemate() {
-            println!("僵局! 游戏平局!");
-            break;
-        }
-
-        let mv = if board.current_turn() == Color::Black {
-            // AI回合
-            println!("AI思考中...");
-            let fen = board.to_fen();
-
-            match ai_client.get_best_move(&fen).await {
-                Ok(move_from_api) => move_from_api,
-                Err(e) => {
-                    println!("API调用失败: {:?}, 使用备用AI", e);
-                    board.get_random_legal_move().expect("无合法走法")
-                }
-            }
-        } else {
-            // 玩家回合
-            println!("\n{}的回合，请输入移动:", board.current_turn());
-
-            let mut input = String::new();
-            io::stdin().read_line(&mut input).expect("读取输入失败");
-            let input = input.trim();
-
-            match input {
-                "quit" | "exit" => {
-                    println!("游戏结束!");
-                    break;
-                }
-                "history" => {
-                    board.display_move_history();
-                    continue;
-                }
-                "help" => {
-                    println!("输入格式: 起始位置 目标位置 (例如: e2 e4)");
-                    println!("特殊命令:");
-                    println!("  'history' - 显示移动历史");
-                    println!("  'quit' - 退出游戏");
-                    println!("  'help' - 显示帮助");
-                    continue;
-                }
-                _ => {}
-            }
-
-            let mut mv = match Move::from_notation(input) {
-                Some(mv) => mv,
-                None => {
-                    println!("无效的移动格式，请使用格式: e2 e4");
-                    continue;
-                }
-            };
-
-            // 检查是否是兵升变
-            if let Some(Piece::Pawn(color, _)) = board.get(mv.from) {
-                let promotion_row = match color {
-                    Color::White => 0,
-                    Color::Black => 7,
-                };
-                if mv.to.row == promotion_row {
-                    let promotion_piece = handle_promotion(color);
-                    mv.promotion = Some(promotion_piece);
-                }
-            }
-
-            mv
-        };
-
-        match board.make_move(&mv) {
-            Ok(_) => println!("移动成功: {}", mv.to_notation()),
-            Err(e) => {
-                println!("移动失败: {}", e);
-                if board.current_turn() == Color::Black {
-                    // AI走法非法时使用备用随机走法
-                    println!("AI走法非法，使用备用随机走法");
-                    let backup_move = board.get_random_legal_move().expect("无合法走法");
-                    board.make_move(&backup_move).unwrap();
-                }
-            }
-        }
-    } // 游戏主循环结束（loop {} 闭合）
-
-    // 游戏结束后显示移动历史
-    board.display_move_history();
+    if !score_graph.is_empty() {
+        println!("评分走势 (上方白优，下方黑优):");
+        println!("  {}", score_graph);
+    }
     println!("感谢游戏!");
 }
@@ -0,0 +1,280 @@
+use crate::chessboard::{Chessboard, Color};
+use std::time::Duration;
+
+// 走法质量分类（仿lichess）：按每步的厘兵损失分为最佳/良好/疑问/错误/漏着，
+// 并计算每方的平均厘兵损失和准确率
+// 评分一律用白方视角的厘兵值；将杀评分（远大于EVAL_CAP）
+// 先截断到 ±EVAL_CAP 再计算损失，这样"杀棋步数变长"不会被算成漏着，
+// 而丢掉必杀（从将杀评分跌到普通优势）仍会按损失计为错误或漏着
+
+const EVAL_CAP: i32 = 1_000;
+
+// 评分走势图的最大宽度（字符），更长的对局均匀抽样
+const SPARKLINE_WIDTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveQuality {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveQuality {
+    pub fn from_loss(cp_loss: i32) -> Self {
+        match cp_loss {
+            i32::MIN..=10 => MoveQuality::Best,
+            11..=49 => MoveQuality::Good,
+            50..=99 => MoveQuality::Inaccuracy,
+            100..=299 => MoveQuality::Mistake,
+            _ => MoveQuality::Blunder,
+        }
+    }
+
+    // PGN中的数字注释符号：$6 ?!、$2 ?、$4 ??
+    pub fn nag(self) -> Option<u8> {
+        match self {
+            MoveQuality::Best | MoveQuality::Good => None,
+            MoveQuality::Inaccuracy => Some(6),
+            MoveQuality::Mistake => Some(2),
+            MoveQuality::Blunder => Some(4),
+        }
+    }
+}
+
+// 对局中的一步及其评估
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveRecord {
+    // 走子历史中的记法(SAN)，如 "Nf3"
+    pub notation: String,
+    pub color: Color,
+    // 走子前后的评分（白方视角厘兵）
+    pub eval_before: i32,
+    pub eval_after: i32,
+    pub quality: MoveQuality,
+    // 走完这步后走子方时钟的剩余时间（不计时时为None），导出为 [%clk]
+    pub clock: Option<Duration>,
+}
+
+impl MoveRecord {
+    // 这步棋让走子方损失的厘兵数（不为负）
+    pub fn cp_loss(&self) -> i32 {
+        let sign = match self.color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        let before = sign * capped(self.eval_before);
+        let after = sign * capped(self.eval_after);
+        (before - after).max(0)
+    }
+}
+
+fn capped(eval: i32) -> i32 {
+    eval.clamp(-EVAL_CAP, EVAL_CAP)
+}
+
+// 给整局的每一步分类；evals[i]是走完i步后局面的评分（白方视角），长度为步数+1
+pub fn classify_game(history: &[String], evals: &[i32]) -> Vec<MoveRecord> {
+    history
+        .iter()
+        .zip(evals.windows(2))
+        .enumerate()
+        .map(|(ply, (notation, pair))| {
+            let color = if ply.is_multiple_of(2) {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let mut record = MoveRecord {
+                notation: notation.clone(),
+                color,
+                eval_before: pair[0],
+                eval_after: pair[1],
+                quality: MoveQuality::Best,
+                clock: None,
+            };
+            record.quality = MoveQuality::from_loss(record.cp_loss());
+            record
+        })
+        .collect()
+}
+
+// 白方视角评分对应的走子方胜率（0-100），lichess公式
+fn win_percent(eval: i32, color: Color) -> f64 {
+    let eval = match color {
+        Color::White => capped(eval),
+        Color::Black => -capped(eval),
+    } as f64;
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * eval).exp()) - 1.0)
+}
+
+// 一方的走法质量统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerReport {
+    pub moves: usize,
+    pub average_cp_loss: f64,
+    // 准确率（0-100）
+    pub accuracy: f64,
+    pub inaccuracies: usize,
+    pub mistakes: usize,
+    pub blunders: usize,
+}
+
+pub fn player_report(records: &[MoveRecord], color: Color) -> PlayerReport {
+    let moves: Vec<&MoveRecord> = records.iter().filter(|r| r.color == color).collect();
+    let count = |quality| moves.iter().filter(|r| r.quality == quality).count();
+    let (average_cp_loss, accuracy) = if moves.is_empty() {
+        (0.0, 100.0)
+    } else {
+        let total_loss: i32 = moves.iter().map(|r| r.cp_loss()).sum();
+        let total_accuracy: f64 = moves
+            .iter()
+            .map(|r| {
+                let drop =
+                    (win_percent(r.eval_before, color) - win_percent(r.eval_after, color)).max(0.0);
+                (103.1668 * (-0.04354 * drop).exp() - 3.1669).clamp(0.0, 100.0)
+            })
+            .sum();
+        (
+            total_loss as f64 / moves.len() as f64,
+            total_accuracy / moves.len() as f64,
+        )
+    };
+
+    PlayerReport {
+        moves: moves.len(),
+        average_cp_loss,
+        accuracy,
+        inaccuracies: count(MoveQuality::Inaccuracy),
+        mistakes: count(MoveQuality::Mistake),
+        blunders: count(MoveQuality::Blunder),
+    }
+}
+
+// 报告文字，每方一行，CLI总结和GUI结束界面共用
+pub fn report_lines(records: &[MoveRecord]) -> Vec<String> {
+    [Color::White, Color::Black]
+        .iter()
+        .map(|&color| {
+            let report = player_report(records, color);
+            format!(
+                "{}: 准确率 {:.1}%  平均损失 {:.0} 厘兵  疑问 {}  错误 {}  漏着 {}",
+                color,
+                report.accuracy,
+                report.average_cp_loss,
+                report.inaccuracies,
+                report.mistakes,
+                report.blunders
+            )
+        })
+        .collect()
+}
+
+// 评分走势：开局局面及每步之后的白方视角评分，将杀评分截断到 ±EVAL_CAP
+// CLI走势图和GUI结束界面的图表都用这组数据
+pub fn score_series(records: &[MoveRecord]) -> Vec<i32> {
+    records
+        .first()
+        .map(|first| capped(first.eval_before))
+        .into_iter()
+        .chain(records.iter().map(|r| capped(r.eval_after)))
+        .collect()
+}
+
+// 一行字符走势图：中间的 ▄ 为均势，越高白方越好，越低黑方越好
+pub fn sparkline(records: &[MoveRecord]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let series = score_series(records);
+    let width = series.len().min(SPARKLINE_WIDTH);
+    (0..width)
+        .map(|i| {
+            let eval = series[i * series.len() / width];
+            let level = (eval + EVAL_CAP) as usize * (BARS.len() - 1) / (2 * EVAL_CAP) as usize;
+            BARS[level]
+        })
+        .collect()
+}
+
+// 逐个局面的白方视角评分：从初始局面重放history，对每个局面调用evaluate
+// （evaluate返回走子方视角的评分，与UCI和API的约定一致）
+pub async fn evaluate_game<F, Fut>(history: &[String], mut evaluate: F) -> Result<Vec<i32>, String>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<i32, String>>,
+{
+    let mut board = Chessboard::new();
+    let mut evals = Vec::with_capacity(history.len() + 1);
+    for index in 0..=history.len() {
+        let score = evaluate(board.to_fen()).await?;
+        evals.push(match board.current_turn() {
+            Color::White => score,
+            Color::Black => -score,
+        });
+        if let Some(notation) = history.get(index) {
+            let mv = board.parse_move(notation)?;
+            board.make_move(&mv)?;
+        }
+    }
+    Ok(evals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATE_SCORE: i32 = 10_000;
+
+    fn history(moves: &[&str]) -> Vec<String> {
+        moves.iter().map(|mv| mv.to_string()).collect()
+    }
+
+    #[test]
+    fn losses_are_classified_from_the_movers_side() {
+        let moves = history(&["e2 e4", "e7 e5", "d1 h5", "g8 f6"]);
+        // 白方第二步丢了60厘兵，黑方最后一步丢了后（约900厘兵）
+        let records = classify_game(&moves, &[20, 30, 25, -35, 880]);
+        let qualities: Vec<MoveQuality> = records.iter().map(|r| r.quality).collect();
+        assert_eq!(
+            qualities,
+            vec![
+                MoveQuality::Best,
+                MoveQuality::Best,
+                MoveQuality::Inaccuracy,
+                MoveQuality::Blunder
+            ]
+        );
+        assert_eq!(records[3].quality.nag(), Some(4));
+
+        let white = player_report(&records, Color::White);
+        assert_eq!(white.inaccuracies, 1);
+        assert_eq!(white.average_cp_loss, 30.0);
+        let black = player_report(&records, Color::Black);
+        assert!(black.accuracy < white.accuracy);
+    }
+
+    #[test]
+    fn score_series_and_sparkline() {
+        let moves = history(&["e2 e4", "e7 e5", "d1 h5"]);
+        let records = classify_game(&moves, &[0, 30, -1_000, MATE_SCORE]);
+        assert_eq!(score_series(&records), vec![0, 30, -1_000, 1_000]);
+        assert_eq!(sparkline(&records), "▄▄▁█");
+        assert_eq!(sparkline(&[]), "");
+
+        let long = history(&["e2 e4"; 200]);
+        let records = classify_game(&long, &[0; 201]);
+        assert_eq!(sparkline(&records).chars().count(), SPARKLINE_WIDTH);
+    }
+
+    #[test]
+    fn mate_scores_are_capped() {
+        let moves = history(&["e2 e4", "e7 e5"]);
+        // 白方从杀棋评分变为"更慢的杀棋"，不算漏着；黑方走后仍是杀棋
+        let records = classify_game(&moves, &[MATE_SCORE - 3, MATE_SCORE - 9, MATE_SCORE - 8]);
+        assert_eq!(records[0].quality, MoveQuality::Best);
+
+        // 丢掉必杀，只剩小优
+        let lost = classify_game(&moves[..1], &[MATE_SCORE - 3, 150]);
+        assert_eq!(lost[0].quality, MoveQuality::Blunder);
+    }
+}
@@ -0,0 +1,791 @@
+use crate::adjudication::Adjudication;
+use crate::chessboard::{Chessboard, Color, GameStatus, Move, Outcome};
+use crate::move_quality::MoveRecord;
+use crate::player_config::Players;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 着法部分每行不超过80列（PGN导出格式的要求）
+const LINE_WIDTH: usize = 80;
+
+// PGN注释中的时钟标签（lichess风格 [%clk 0:04:31]）：
+// 带注释导出时写在每步之后，读入时取回每步走完后的剩余时间
+
+// 每步走完后的剩余时间，没有时钟标签的着法为None
+pub type MoveClocks = Vec<Option<Duration>>;
+
+// 走子历史中的一步转为PGN着法：历史记录已是SAN，原样使用；
+// 坐标记法的历史（如 "e7 e8Q"）去掉空格写成长坐标记法 "e7e8Q"
+pub fn coordinate_move(history_entry: &str) -> String {
+    history_entry.split_whitespace().collect()
+}
+
+// 变着树的一个节点：一步分析走法的着法（可能带空着 "--"）及其后的各个续着，
+// 第一个续着是主线，其余作为变着(RAV)写在主线着法之后
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variation {
+    pub moves: Vec<String>,
+    pub continuations: Vec<Variation>,
+}
+
+impl Variation {
+    // 没有分支的一串着法
+    pub fn chain(moves: &[String]) -> Vec<Variation> {
+        match moves.split_first() {
+            Some((first, rest)) => vec![Variation {
+                moves: vec![first.clone()],
+                continuations: Self::chain(rest),
+            }],
+            None => Vec::new(),
+        }
+    }
+}
+
+// 从第ply个半回合（0为白方第一步）开始的着法文本，黑方开头时用 "N..." 编号
+fn movetext(moves: &[String], first_ply: usize) -> String {
+    let mut parts = Vec::new();
+    push_moves(&mut parts, moves, first_ply, true);
+    parts.join(" ")
+}
+
+// numbered为true时黑方开头的着法也写编号（变着开头或变着结束后回到主线时）
+fn push_moves(parts: &mut Vec<String>, moves: &[String], first_ply: usize, numbered: bool) {
+    for (offset, mv) in moves.iter().enumerate() {
+        let ply = first_ply + offset;
+        if ply.is_multiple_of(2) {
+            parts.push(format!("{}.", ply / 2 + 1));
+        } else if offset == 0 && numbered {
+            parts.push(format!("{}...", ply / 2 + 1));
+        }
+        parts.push(mv.clone());
+    }
+}
+
+// 变着树的着法：主线着法之后依次写出其余分支的变着，再接主线的续着
+fn push_tree(parts: &mut Vec<String>, branches: &[Variation], ply: usize, numbered: bool) {
+    let Some((main, others)) = branches.split_first() else {
+        return;
+    };
+    push_moves(parts, &main.moves, ply, numbered);
+    for other in others {
+        let mut inner = Vec::new();
+        push_moves(&mut inner, &other.moves, ply, true);
+        push_tree(
+            &mut inner,
+            &other.continuations,
+            ply + other.moves.len(),
+            false,
+        );
+        parts.push(format!("({})", inner.join(" ")));
+    }
+    push_tree(
+        parts,
+        &main.continuations,
+        ply + main.moves.len(),
+        !others.is_empty(),
+    );
+}
+
+// 对局结果标签：将死和认输判胜负，逼和为和棋，其余（中途退出）为 *
+pub fn result_tag(game: &Chessboard) -> &'static str {
+    // 认输、协议和棋或超时结束的对局以记录的结束方式为准
+    let winner = match game.outcome() {
+        Some(Outcome::Resigned(loser)) => loser.opposite(),
+        Some(Outcome::DrawAgreed) => return "1/2-1/2",
+        Some(Outcome::TimeForfeit(flagged)) => return flag_fall_result(game, flagged),
+        None => match game.status() {
+            GameStatus::Checkmate { winner } => winner,
+            GameStatus::Draw { .. } => return "1/2-1/2",
+            GameStatus::Ongoing => return "*",
+        },
+    };
+    match winner {
+        Color::White => "1-0",
+        Color::Black => "0-1",
+    }
+}
+
+// 七个必备标签(Seven Tag Roster)按规定顺序，地点和轮次未知时写 "?" 和 "-"
+fn headers(result: &str, players: &Players) -> String {
+    headers_on(&today(), result, players)
+}
+
+fn headers_on(date: &str, result: &str, players: &Players) -> String {
+    let mut text = format!(
+        "[Event \"Casual Game\"]\n[Site \"?\"]\n[Date \"{}\"]\n[Round \"-\"]\n\
+         [White \"{}\"]\n[Black \"{}\"]\n[Result \"{}\"]\n",
+        date, players.white.name, players.black.name, result
+    );
+    for (tag, value) in players
+        .elo_tags()
+        .into_iter()
+        .chain(players.time_control_tags())
+    {
+        text.push_str(&format!("[{} \"{}\"]\n", tag, value));
+    }
+    text.push('\n');
+    text
+}
+
+// 当天日期（UTC），PGN格式 YYYY.MM.DD
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+    // 由1970-01-01起的天数换算公历日期
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+// 按空格折行，每行不超过LINE_WIDTH列（单个着法本身超长时独占一行）
+fn wrap(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split(' ') {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= LINE_WIDTH => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines.join("\n")
+}
+
+// 导出对局PGN：走子历史已是SAN，结果按终局状态（中途导出为 *）；
+// clocks 是每步走完后走子方的剩余时间，有记录的写成 {[%clk]} 注释
+pub fn game(board: &Chessboard, players: &Players, clocks: &[Option<Duration>]) -> String {
+    game_on(&today(), board, players, clocks)
+}
+
+fn game_on(
+    date: &str,
+    board: &Chessboard,
+    players: &Players,
+    clocks: &[Option<Duration>],
+) -> String {
+    game_text(date, board, players, clocks, result_tag(board), None)
+}
+
+// 引擎对局被裁决时导出PGN：结果取裁决结果，结果前写裁决注释
+pub fn adjudicated_game(
+    board: &Chessboard,
+    players: &Players,
+    clocks: &[Option<Duration>],
+    adjudication: Adjudication,
+) -> String {
+    game_text(
+        &today(),
+        board,
+        players,
+        clocks,
+        adjudication.result(),
+        Some(adjudication.pgn_comment()),
+    )
+}
+
+// 一方超时后的对局结果：对方还有将杀可能时对方胜，否则和棋
+pub fn flag_fall_result(board: &Chessboard, flagged: Color) -> &'static str {
+    match board.flag_fall_winner(flagged) {
+        Some(Color::White) => "1-0",
+        Some(Color::Black) => "0-1",
+        None => "1/2-1/2",
+    }
+}
+
+// 超时结束的对局导出PGN：结果按超时判定，结果前注明超时方
+pub fn flag_fall_game(
+    board: &Chessboard,
+    players: &Players,
+    clocks: &[Option<Duration>],
+    flagged: Color,
+) -> String {
+    let comment = match flagged {
+        Color::White => "{White ran out of time}",
+        Color::Black => "{Black ran out of time}",
+    };
+    game_text(
+        &today(),
+        board,
+        players,
+        clocks,
+        flag_fall_result(board, flagged),
+        Some(comment),
+    )
+}
+
+fn game_text(
+    date: &str,
+    board: &Chessboard,
+    players: &Players,
+    clocks: &[Option<Duration>],
+    result: &str,
+    comment: Option<&str>,
+) -> String {
+    let moves: Vec<String> = board
+        .move_history()
+        .iter()
+        .enumerate()
+        .map(|(ply, mv)| {
+            let mut mv = coordinate_move(mv);
+            if let Some(clock) = clocks.get(ply).copied().flatten() {
+                mv.push_str(&format!(" {}", format_clock_comment(clock)));
+            }
+            mv
+        })
+        .collect();
+    let mut movetext = movetext(&moves, 0);
+    for part in comment.into_iter().chain([result]) {
+        if !movetext.is_empty() {
+            movetext.push(' ');
+        }
+        movetext.push_str(part);
+    }
+    format!("{}{}\n", headers_on(date, result, players), wrap(&movetext))
+}
+
+impl Chessboard {
+    // 使用缺省棋手名称（人类一方 Player，引擎一方按难度命名）的完整PGN
+    pub fn to_pgn(&self) -> String {
+        game(self, &Players::default(), &[])
+    }
+
+    // 读入PGN中的第一盘棋：有 [FEN] 标签时从该局面开始，着法逐步经 make_move 检查，
+    // 返回最终局面、走过的着法和每步注释中的时钟（与着法一一对应，没有时钟标签的为None）
+    pub fn from_pgn(pgn: &str) -> Result<(Chessboard, Vec<Move>, MoveClocks), String> {
+        let game = split_games(pgn).into_iter().next().ok_or("PGN中没有对局")?;
+        let mut board = match &game.fen {
+            Some(fen) => Chessboard::from_fen(fen).map_err(|e| format!("FEN标签无效: {}", e))?,
+            None => Chessboard::new(),
+        };
+
+        let mut moves = Vec::new();
+        for token in &game.moves {
+            let number = board.fullmove_number();
+            let at = match board.current_turn() {
+                Color::White => format!("{}.", number),
+                Color::Black => format!("{}...", number),
+            };
+            let mv = board
+                .parse_san(token.trim_end_matches(['!', '?']))
+                .and_then(|mv| board.make_move(&mv).map(|_| mv).map_err(String::from))
+                .map_err(|e| format!("{} {}: {}", at, token, e))?;
+            moves.push(mv);
+        }
+        Ok((board, moves, game.clocks))
+    }
+}
+
+// 导出带走法质量注释的PGN：疑问/错误/漏着分别标注 $6/$2/$4，记有时钟的着法后写 {[%clk]}
+pub fn annotated_game(records: &[MoveRecord], result: &str, players: &Players) -> String {
+    let moves: Vec<String> = records
+        .iter()
+        .map(|record| {
+            let mut mv = coordinate_move(&record.notation);
+            if let Some(nag) = record.quality.nag() {
+                mv.push_str(&format!(" ${}", nag));
+            }
+            if let Some(clock) = record.clock {
+                mv.push_str(&format!(" {}", format_clock_comment(clock)));
+            }
+            mv
+        })
+        .collect();
+    format!(
+        "{}{}\n",
+        headers(result, players),
+        wrap(&format!("{} {}", movetext(&moves, 0), result))
+    )
+}
+
+// 导出对局PGN，并把分析线作为最后一步的变着(RAV)附在其后：
+// 变着以最后一步开头，再接分析走法；对局本身的着法不变
+pub fn game_with_variation(
+    history: &[String],
+    variation: &[String],
+    result: &str,
+    players: &Players,
+) -> String {
+    game_with_variations(history, &Variation::chain(variation), result, players)
+}
+
+// 同上，但附上整棵分析树：终局后的每个分支各成一个变着，分支内部的分叉嵌套为子变着
+pub fn game_with_variations(
+    history: &[String],
+    variations: &[Variation],
+    result: &str,
+    players: &Players,
+) -> String {
+    let moves: Vec<String> = history.iter().map(|mv| coordinate_move(mv)).collect();
+    let mut text = movetext(&moves, 0);
+
+    if let Some((last, _)) = moves.split_last() {
+        for variation in variations {
+            let mut parts = Vec::new();
+            push_moves(
+                &mut parts,
+                std::slice::from_ref(last),
+                moves.len() - 1,
+                true,
+            );
+            push_tree(
+                &mut parts,
+                std::slice::from_ref(variation),
+                moves.len(),
+                false,
+            );
+            text.push_str(&format!(" ({})", parts.join(" ")));
+        }
+    } else {
+        let mut parts = Vec::new();
+        push_tree(&mut parts, variations, 0, true);
+        text.push_str(&parts.join(" "));
+    }
+
+    text.push_str(&format!(" {}", result));
+    format!("{}{}\n", headers(result, players), wrap(&text))
+}
+
+// 一盘棋：标签和着法（已去掉注释、变着、NAG和编号），以及每步注释中的时钟
+#[derive(Debug, Default)]
+pub struct PgnGame {
+    pub fen: Option<String>,
+    pub result: Option<String>,
+    pub moves: Vec<String>,
+    // 与moves一一对应
+    pub clocks: MoveClocks,
+}
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// 拆分为各盘棋：结果记号结束一盘，着法之后出现的标签行开始新的一盘
+pub fn split_games(content: &str) -> Vec<PgnGame> {
+    let mut games = Vec::new();
+    let mut game = PgnGame::default();
+    let mut movetext = String::new();
+
+    let finish = |game: &mut PgnGame, movetext: &mut String, games: &mut Vec<PgnGame>| {
+        for (token, clock) in movetext_tokens(movetext) {
+            if !RESULTS.contains(&token.as_str()) {
+                game.moves.push(token);
+                game.clocks.push(clock);
+            }
+        }
+        movetext.clear();
+        if game.fen.is_some() || game.result.is_some() || !game.moves.is_empty() {
+            games.push(std::mem::take(game));
+        }
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some((name, value)) = parse_tag(trimmed) {
+            if !movetext.trim().is_empty() {
+                finish(&mut game, &mut movetext, &mut games);
+            }
+            match name {
+                "FEN" => game.fen = Some(value.to_string()),
+                "Result" => game.result = Some(value.to_string()),
+                _ => {}
+            }
+            continue;
+        }
+        movetext.push_str(line);
+        movetext.push('\n');
+        let ends_game = movetext_tokens(&movetext)
+            .last()
+            .is_some_and(|(token, _)| RESULTS.contains(&token.as_str()));
+        if ends_game {
+            finish(&mut game, &mut movetext, &mut games);
+        }
+    }
+    finish(&mut game, &mut movetext, &mut games);
+    games
+}
+
+// [Name "value"]
+fn parse_tag(line: &str) -> Option<(&str, &str)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name, value))
+}
+
+// 着法记号：去掉 {注释}、; 行尾注释、(变着)、$NAG 和着法编号；
+// 主线注释中的 [%clk] 归到它前面的着法上
+fn movetext_tokens(movetext: &str) -> Vec<(String, Option<Duration>)> {
+    let mut tokens: Vec<(String, Option<Duration>)> = Vec::new();
+    let mut word = String::new();
+    let mut comment = String::new();
+    let mut depth = 0;
+    let mut in_comment = false;
+    let mut in_line_comment = false;
+
+    let flush = |word: &mut String, tokens: &mut Vec<(String, Option<Duration>)>| {
+        if let Some(token) = plain_token(word) {
+            tokens.push((token, None));
+        }
+        word.clear();
+    };
+
+    for c in movetext.chars() {
+        match c {
+            _ if in_line_comment => in_line_comment = c != '\n',
+            '}' if in_comment => {
+                in_comment = false;
+                if depth == 0 {
+                    if let (Some(clock), Some(last)) =
+                        (parse_clock_comment(&comment).0, tokens.last_mut())
+                    {
+                        last.1 = Some(clock);
+                    }
+                }
+                comment.clear();
+            }
+            _ if in_comment => comment.push(c),
+            '{' | ';' | '(' | ')' => {
+                flush(&mut word, &mut tokens);
+                match c {
+                    '{' => in_comment = true,
+                    ';' => in_line_comment = true,
+                    '(' => depth += 1,
+                    _ => depth = (depth - 1).max(0),
+                }
+            }
+            _ if depth > 0 => {}
+            _ if c.is_whitespace() => flush(&mut word, &mut tokens),
+            _ => word.push(c),
+        }
+    }
+    flush(&mut word, &mut tokens);
+    tokens
+}
+
+// 一个以空白分隔的记号去掉NAG和着法编号后的着法；只有编号或NAG时为None
+fn plain_token(word: &str) -> Option<String> {
+    if word.starts_with('$') {
+        return None;
+    }
+    let token = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match token {
+        "" => None,
+        // 去编号时会把结果记号的数字也去掉，这里复原
+        "-0" | "-1" | "/2-1/2" => Some(movetext_result(token)),
+        _ => Some(token.to_string()),
+    }
+}
+
+fn movetext_result(stripped: &str) -> String {
+    match stripped {
+        "-0" => "1-0",
+        "-1" => "0-1",
+        _ => "1/2-1/2",
+    }
+    .to_string()
+}
+
+// 生成 [%clk H:MM:SS] 标签
+pub fn format_clock(remaining: Duration) -> String {
+    let total = remaining.as_secs();
+    format!(
+        "[%clk {}:{:02}:{:02}]",
+        total / 3600,
+        (total / 60) % 60,
+        total % 60
+    )
+}
+
+// 生成只含时钟标签的完整注释 {[%clk H:MM:SS]}
+pub fn format_clock_comment(remaining: Duration) -> String {
+    format!("{{{}}}", format_clock(remaining))
+}
+
+// 从注释正文（不含花括号）中取出时钟标签，返回剩余时间和去掉标签后的其余文字
+pub fn parse_clock_comment(comment: &str) -> (Option<Duration>, String) {
+    let start = match comment.find("[%clk") {
+        Some(start) => start,
+        None => return (None, comment.trim().to_string()),
+    };
+    let end = match comment[start..].find(']') {
+        Some(offset) => start + offset,
+        None => return (None, comment.trim().to_string()),
+    };
+
+    let clock = parse_clock_value(comment[start + "[%clk".len()..end].trim());
+    if clock.is_none() {
+        return (None, comment.trim().to_string());
+    }
+
+    let rest = format!("{} {}", comment[..start].trim(), comment[end + 1..].trim());
+    (clock, rest.trim().to_string())
+}
+
+// 解析 H:MM:SS 或 H:MM:SS.f 格式
+fn parse_clock_value(value: &str) -> Option<Duration> {
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+
+    let hours: u64 = parts[0].parse().ok()?;
+    let minutes: u64 = parts[1].parse().ok()?;
+    let seconds: f64 = parts[2].parse().ok()?;
+    if minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+
+    Some(Duration::from_secs(hours * 3600 + minutes * 60) + Duration::from_secs_f64(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_comment_round_trip() {
+        let remaining = Duration::from_secs(4 * 60 + 31);
+        assert_eq!(format_clock_comment(remaining), "{[%clk 0:04:31]}");
+
+        let (clock, rest) = parse_clock_comment("好棋 [%clk 0:04:31] 保持压力");
+        assert_eq!(clock, Some(remaining));
+        assert_eq!(rest, "好棋 保持压力");
+
+        let (clock, rest) = parse_clock_comment("no clock here");
+        assert_eq!(clock, None);
+        assert_eq!(rest, "no clock here");
+    }
+
+    #[test]
+    fn analysis_line_is_appended_as_variation() {
+        let history: Vec<String> = ["f2 f3", "e7 e5", "g2 g4", "d8 h4"]
+            .map(String::from)
+            .to_vec();
+        let variation: Vec<String> = ["--", "b8c6"].map(String::from).to_vec();
+
+        let pgn = game_with_variation(&history, &variation, "0-1", &Players::default());
+        assert!(pgn.contains("[Result \"0-1\"]"));
+        assert!(pgn.contains("[TimeControl \"-\"]"));
+        assert!(pgn.ends_with("1. f2f3 e7e5 2. g2g4 d8h4 (2... d8h4 3. -- b8c6) 0-1\n"));
+    }
+
+    #[test]
+    fn variation_tree_is_nested() {
+        let moves = |list: &[&str]| list.iter().map(|mv| mv.to_string()).collect::<Vec<_>>();
+        // 没有对局着法时整棵树直接作为着法：1. e2e4 之后黑方有两种应法，变着之后主线继续
+        let tree = vec![Variation {
+            moves: moves(&["e2e4"]),
+            continuations: vec![
+                Variation {
+                    moves: moves(&["e7e5"]),
+                    continuations: Variation::chain(&moves(&["g1f3", "b8c6"])),
+                },
+                Variation {
+                    moves: moves(&["c7c5"]),
+                    continuations: Vec::new(),
+                },
+            ],
+        }];
+        let pgn = game_with_variations(&[], &tree, "*", &Players::default());
+        assert!(
+            pgn.ends_with("1. e2e4 e7e5 (1... c7c5) 2. g1f3 b8c6 *\n"),
+            "{}",
+            pgn
+        );
+    }
+
+    #[test]
+    fn finished_game_is_exported() {
+        let mut board = Chessboard::new();
+        for notation in ["f3", "e5", "g4", "Qh4#"] {
+            let mv = board.parse_move(notation).unwrap();
+            board.make_move(&mv).unwrap();
+        }
+        assert_eq!(
+            game_on("2024.05.01", &board, &Players::default(), &[]),
+            "[Event \"Casual Game\"]\n[Site \"?\"]\n[Date \"2024.05.01\"]\n[Round \"-\"]\n\
+             [White \"Player\"]\n[Black \"siliconflow (level 10)\"]\n[Result \"0-1\"]\n[TimeControl \"-\"]\n\n\
+             1. f3 e5 2. g4 Qh4# 0-1\n"
+        );
+        assert!(Chessboard::new().to_pgn().ends_with("\n\n*\n"));
+        assert_eq!(today().len(), "2024.05.01".len());
+    }
+
+    #[test]
+    fn flag_fall_is_exported() {
+        // 黑方超时，白方有后：白胜
+        let board = Chessboard::from_minimal_fen("4k3/8/8/8/8/8/8/Q3K3 w - -").unwrap();
+        assert_eq!(flag_fall_result(&board, Color::Black), "1-0");
+        let pgn = flag_fall_game(&board, &Players::default(), &[], Color::Black);
+        assert!(pgn.contains("[Result \"1-0\"]"), "{}", pgn);
+        assert!(pgn.ends_with("{Black ran out of time} 1-0\n"), "{}", pgn);
+
+        // 白方超时，但黑方只剩单王：和棋
+        assert_eq!(flag_fall_result(&board, Color::White), "1/2-1/2");
+        let pgn = flag_fall_game(&board, &Players::default(), &[], Color::White);
+        assert!(
+            pgn.ends_with("{White ran out of time} 1/2-1/2\n"),
+            "{}",
+            pgn
+        );
+    }
+
+    #[test]
+    fn concluded_games_keep_their_outcome() {
+        let mut board = Chessboard::new();
+        let mv = board.parse_move("e4").unwrap();
+        board.make_move(&mv).unwrap();
+
+        board.conclude(Outcome::DrawAgreed);
+        assert_eq!(result_tag(&board), "1/2-1/2");
+        let pgn = game(&board, &Players::default(), &[]);
+        assert!(pgn.contains("[Result \"1/2-1/2\"]"), "{}", pgn);
+
+        // 认输方不一定是轮到走棋的一方
+        board.conclude(Outcome::Resigned(Color::White));
+        assert_eq!(result_tag(&board), "0-1");
+
+        board.conclude(Outcome::TimeForfeit(Color::Black));
+        assert_eq!(result_tag(&board), "1-0");
+    }
+
+    #[test]
+    fn scholars_mate_is_exported() {
+        let mut board = Chessboard::new();
+        for notation in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"] {
+            let mv = board.parse_move(notation).unwrap();
+            board.make_move(&mv).unwrap();
+        }
+        assert_eq!(
+            game_on("2024.05.01", &board, &Players::default(), &[]),
+            "[Event \"Casual Game\"]\n[Site \"?\"]\n[Date \"2024.05.01\"]\n[Round \"-\"]\n\
+             [White \"Player\"]\n[Black \"siliconflow (level 10)\"]\n[Result \"1-0\"]\n[TimeControl \"-\"]\n\n\
+             1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"
+        );
+    }
+
+    #[test]
+    fn games_are_loaded_from_pgn() {
+        let text = "[Event \"Club match\"]\n[White \"A\"]\n[Black \"B\"]\n[Result \"*\"]\n\n\
+                    1. e4 d5 2. e5 f5 3. exf6 {吃过路兵} Nxf6 $1 4. Nf3 e6 5. Be2 Bd6\n\
+                    6. O-O O-O *\n";
+        let (board, moves, clocks) = Chessboard::from_pgn(text).unwrap();
+        assert_eq!(moves.len(), 12);
+        assert_eq!(clocks, vec![None; 12]);
+        assert_eq!(moves[4].to_uci(), "e5f6");
+        assert_eq!(
+            board.to_fen(),
+            "rnbq1rk1/ppp3pp/3bpn2/3p4/8/5N2/PPPPBPPP/RNBQ1RK1 w - - 4 7"
+        );
+
+        // 从设定局面开始，含升变
+        let text = "[SetUp \"1\"]\n[FEN \"4k3/1P6/8/8/8/8/8/4K3 w - - 0 1\"]\n\n\
+                    1. b8=Q+ Kd7 2. Qb5+ *\n";
+        let (board, moves, _) = Chessboard::from_pgn(text).unwrap();
+        assert_eq!(moves[0].to_uci(), "b7b8q");
+        assert_eq!(board.to_fen(), "8/3k4/8/1Q6/8/8/8/4K3 b - - 2 2");
+
+        assert_eq!(
+            Chessboard::from_pgn("1. e4 e5 2. Ke3 *").unwrap_err(),
+            "2. Ke3: 没有王能走到e3: Ke3"
+        );
+        assert!(Chessboard::from_pgn("").is_err());
+    }
+
+    #[test]
+    fn exported_game_is_imported_back() {
+        // 含易位、吃子、将军，最后是长易位
+        let mut board = Chessboard::new();
+        for notation in [
+            "e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5", "Nf3", "Nf6", "Bc4", "Bg4", "O-O", "Nc6",
+            "h3", "Bxf3", "Qxf3", "e6", "Bb5", "Bd6", "Bxc6+", "bxc6", "d3", "O-O-O",
+        ] {
+            let mv = board.parse_move(notation).unwrap();
+            board.make_move(&mv).unwrap();
+        }
+
+        let (imported, moves, _) = Chessboard::from_pgn(&board.to_pgn()).unwrap();
+        assert_eq!(moves.len(), board.move_history().len());
+        assert_eq!(imported.move_history(), board.move_history());
+        assert_eq!(imported.to_fen(), board.to_fen());
+    }
+
+    #[test]
+    fn movetext_is_wrapped_at_80_columns() {
+        let moves: Vec<String> = (0..40).map(|i| format!("Nc{}", i % 8 + 1)).collect();
+        let text = format!("{} *", movetext(&moves, 0));
+        let wrapped = wrap(&text);
+        assert!(wrapped.lines().count() > 1);
+        assert!(wrapped.lines().all(|line| line.len() <= LINE_WIDTH));
+        assert_eq!(wrapped.replace('\n', " "), text);
+    }
+
+    #[test]
+    fn move_quality_is_exported_as_nags() {
+        let history: Vec<String> = ["e2 e4", "e7 e5", "d1 h5", "g8 f6"]
+            .map(String::from)
+            .to_vec();
+        let records = crate::move_quality::classify_game(&history, &[20, 30, 25, -35, 880]);
+        let pgn = annotated_game(&records, "*", &Players::default());
+        assert!(pgn.ends_with("1. e2e4 e7e5 2. d1h5 $6 g8f6 $4 *\n"));
+    }
+
+    #[test]
+    fn move_clocks_round_trip() {
+        let history: Vec<String> = ["e4", "e5", "Qh5", "Nf6"].map(String::from).to_vec();
+        let mut records = crate::move_quality::classify_game(&history, &[20, 30, 25, -35, 880]);
+        let clocks =
+            [Some(298), Some(297), Some(291), None].map(|secs| secs.map(Duration::from_secs));
+        for (record, clock) in records.iter_mut().zip(clocks) {
+            record.clock = clock;
+        }
+
+        let pgn = annotated_game(&records, "*", &Players::default());
+        assert!(
+            pgn.contains(
+                "1. e4 {[%clk 0:04:58]} e5 {[%clk 0:04:57]} 2. Qh5 $6 {[%clk 0:04:51]} Nf6 $4 *"
+            ),
+            "{}",
+            pgn
+        );
+
+        let (_, moves, read) = Chessboard::from_pgn(&pgn).unwrap();
+        assert_eq!(moves.len(), 4);
+        assert_eq!(read, clocks.to_vec());
+    }
+
+    #[test]
+    fn saved_game_keeps_move_clocks() {
+        // save/pgn 命令和终局保存走的都是 game_text
+        let mut board = Chessboard::new();
+        for notation in ["e4", "e5", "Nf3"] {
+            let mv = board.parse_move(notation).unwrap();
+            board.make_move(&mv).unwrap();
+        }
+        let clocks = [Some(299), None, Some(287)].map(|secs| secs.map(Duration::from_secs));
+
+        let pgn = game(&board, &Players::default(), &clocks);
+        assert!(
+            pgn.contains("1. e4 {[%clk 0:04:59]} e5 2. Nf3 {[%clk 0:04:47]} *"),
+            "{}",
+            pgn
+        );
+        let (loaded, _, read) = Chessboard::from_pgn(&pgn).unwrap();
+        assert_eq!(read, clocks.to_vec());
+        assert_eq!(loaded.move_history(), board.move_history());
+
+        let pgn = flag_fall_game(&board, &Players::default(), &clocks, Color::Black);
+        assert!(
+            pgn.contains("Nf3 {[%clk 0:04:47]} {Black ran out of time}"),
+            "{}",
+            pgn
+        );
+    }
+}
@@ -0,0 +1,484 @@
+use crate::chessboard::Color;
+use crate::difficulty::Difficulty;
+use crate::engine_profile::{self, EngineProfile};
+use crate::search_limits::SearchLimits;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// 每一方独立的对局设置（让子赛、时间让步等）：
+//   --white human|engine / --black human|engine        由人还是引擎走（缺省白方人、黑方引擎）
+//   --time 5+3 / --time-white 1+0 / --time-black 5+3   时间控制（分钟+每步加秒）
+//   --level N / --level-white N / --level-black N       引擎难度
+//   --assist / --assist-white / --assist-black          悬挂棋子提示
+//   --ai profile:名称                                    引擎一方使用 chess.cfg 中的命名配置
+//   --white-name 名字 / --black-name 名字                 棋手名称（写入PGN）
+//   --white-elo N / --black-elo N                       等级分（写入PGN的WhiteElo/BlackElo）
+// 分方参数优先于不分方的参数；命名配置中的难度优先于 --level，但不覆盖 --level-white/--level-black。
+// 名称和等级分的缺省值可写在 chess.cfg 中（white_name = ...、black_elo = ... 等，写在所有小节之前）；
+// 都没有给出时人类一方叫 Player，引擎一方按后端和设置命名，如 "siliconflow (level 5)"
+
+// 时间控制：初始时间加每步加秒
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub initial: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    // 解析 "5+3"（5分钟，每步加3秒）或 "0.5+1"；省略加秒时为0
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (minutes, increment) = text.split_once('+').unwrap_or((text, "0"));
+        let minutes: f64 = minutes
+            .trim()
+            .parse()
+            .ok()
+            .filter(|minutes: &f64| *minutes > 0.0)
+            .ok_or_else(|| format!("无效的时间控制: {} (格式: 分钟+秒，如 5+3)", text))?;
+        let increment: u64 = increment
+            .trim()
+            .parse()
+            .map_err(|_| format!("无效的时间控制: {} (格式: 分钟+秒，如 5+3)", text))?;
+        Ok(Self {
+            initial: Duration::from_secs_f64(minutes * 60.0),
+            increment: Duration::from_secs(increment),
+        })
+    }
+
+    // PGN TimeControl标签的写法：秒数，有加秒时为 "秒+秒"
+    pub fn pgn_value(&self) -> String {
+        let initial = self.initial.as_secs();
+        match self.increment.as_secs() {
+            0 => initial.to_string(),
+            increment => format!("{}+{}", initial, increment),
+        }
+    }
+}
+
+// 这一方由谁来走
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    Human,
+    Engine,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerConfig {
+    pub name: String,
+    pub elo: Option<u32>,
+    pub controller: Controller,
+    pub time_control: Option<TimeControl>,
+    pub difficulty: Difficulty,
+    pub assist: bool,
+    // --ai profile:名称 指定的引擎配置（只用于引擎一方）
+    pub profile: Option<EngineProfile>,
+}
+
+// 对局中双方时钟的剩余时间，没有时间控制的一方为None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clocks {
+    remaining: [Option<Duration>; 2],
+    increment: [Option<Duration>; 2],
+}
+
+impl Clocks {
+    // 开局时双方各自的初始时间
+    pub fn start(players: &Players) -> Self {
+        let control = |color| players.get(color).time_control;
+        Self {
+            remaining: [Color::White, Color::Black]
+                .map(|color| control(color).map(|tc| tc.initial)),
+            increment: [Color::White, Color::Black]
+                .map(|color| control(color).map(|tc| tc.increment)),
+        }
+    }
+
+    pub fn remaining(&self, color: Color) -> Option<Duration> {
+        self.remaining[Self::index(color)]
+    }
+
+    pub fn increment(&self, color: Color) -> Option<Duration> {
+        self.increment[Self::index(color)]
+    }
+
+    // color一方的时间已用完
+    pub fn flagged(&self, color: Color) -> bool {
+        self.remaining(color) == Some(Duration::ZERO)
+    }
+
+    // color一方走完一步用时elapsed：扣除用时（最少剩0），时间没用完时再加上该方的加秒
+    pub fn spend(&mut self, color: Color, elapsed: Duration) {
+        let index = Self::index(color);
+        if let Some(remaining) = self.remaining[index] {
+            let left = remaining.saturating_sub(elapsed);
+            self.remaining[index] = Some(if left.is_zero() {
+                left
+            } else {
+                left + self.increment[index].unwrap_or_default()
+            });
+        }
+    }
+
+    fn index(color: Color) -> usize {
+        match color {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
+}
+
+// 双方设置
+#[derive(Debug, Clone, PartialEq)]
+pub struct Players {
+    pub white: PlayerConfig,
+    pub black: PlayerConfig,
+}
+
+impl PlayerConfig {
+    // 带等级分的名称，如 "Alice (1850)"
+    pub fn display_name(&self) -> String {
+        match self.elo {
+            Some(elo) => format!("{} ({})", self.name, elo),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl Players {
+    // 命令行对局：缺省白方为人类、黑方为引擎，--white/--black 可以改变
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        Self::with_profiles(args, &BTreeMap::new())
+    }
+
+    // 同 from_args，--ai 引用的配置在 profiles 中查找；找不到时报错并列出可用的配置
+    pub fn with_profiles(
+        args: &[String],
+        profiles: &BTreeMap<String, EngineProfile>,
+    ) -> Result<Self, String> {
+        Self::with_config(args, profiles, "")
+    }
+
+    // 同 with_profiles，命令行没有给出的棋手名称和等级分取config（chess.cfg的内容）中的缺省值
+    pub fn with_config(
+        args: &[String],
+        profiles: &BTreeMap<String, EngineProfile>,
+        config: &str,
+    ) -> Result<Self, String> {
+        let profile = match flag_value(args, "--ai") {
+            Some(spec) => Some(engine_profile::resolve(spec, profiles)?.clone()),
+            None => None,
+        };
+        let difficulty = Difficulty::from_args(args)?;
+        let assist = args.iter().any(|arg| arg == "--assist");
+        let time_control = match flag_value(args, "--time") {
+            Some(text) => Some(TimeControl::parse(text)?),
+            None => None,
+        };
+
+        let side = |suffix: &str, controller| -> Result<PlayerConfig, String> {
+            let controller = match flag_value(args, &format!("--{}", suffix)) {
+                Some("human") => Controller::Human,
+                Some("engine") => Controller::Engine,
+                Some(other) => {
+                    return Err(format!("无效的棋手类型: {} (可选 human/engine)", other))
+                }
+                None => controller,
+            };
+            let time_control = match flag_value(args, &format!("--time-{}", suffix)) {
+                Some(text) => Some(TimeControl::parse(text)?),
+                None => time_control,
+            };
+            let profile = match controller {
+                Controller::Engine => profile.clone(),
+                Controller::Human => None,
+            };
+            let difficulty = Difficulty::from_flag(args, &format!("--level-{}", suffix))?
+                .or(profile.as_ref().and_then(|profile| profile.level))
+                .unwrap_or(difficulty);
+            let setting = |key: &str| {
+                flag_value(args, &format!("--{}-{}", suffix, key))
+                    .or_else(|| config_value(config, &format!("{}_{}", suffix, key)))
+            };
+            let name = match (setting("name"), controller, &profile) {
+                (Some(name), _, _) => name.to_string(),
+                (None, Controller::Human, _) => "Player".to_string(),
+                (None, Controller::Engine, Some(profile)) => {
+                    format!("siliconflow (profile {})", profile.name)
+                }
+                (None, Controller::Engine, None) => {
+                    format!("siliconflow (level {})", difficulty.level())
+                }
+            };
+            let elo = match setting("elo") {
+                Some(text) => Some(
+                    text.parse()
+                        .map_err(|_| format!("无效的等级分: {}", text))?,
+                ),
+                None => None,
+            };
+            Ok(PlayerConfig {
+                name,
+                elo,
+                controller,
+                time_control,
+                difficulty,
+                assist: assist
+                    || args
+                        .iter()
+                        .any(|arg| *arg == format!("--assist-{}", suffix)),
+                profile,
+            })
+        };
+
+        Ok(Self {
+            white: side("white", Controller::Human)?,
+            black: side("black", Controller::Engine)?,
+        })
+    }
+
+    pub fn get(&self, color: Color) -> &PlayerConfig {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    // color一方引擎的搜索限制：深度取该方难度，双方剩余时间取clocks中的当前值，
+    // 加秒取该方的加秒；再应用该方的命名配置，--depth/--nodes/--movetime 仍可覆盖
+    pub fn search_limits(
+        &self,
+        color: Color,
+        args: &[String],
+        clocks: &Clocks,
+    ) -> Result<SearchLimits, String> {
+        let player = self.get(color);
+        let base = SearchLimits {
+            wtime: clocks.remaining(Color::White),
+            btime: clocks.remaining(Color::Black),
+            inc: clocks.increment(color),
+            ..SearchLimits::depth(player.difficulty.search_depth())
+        };
+        let base = match &player.profile {
+            Some(profile) => profile.apply(base),
+            None => base,
+        };
+        SearchLimits::from_args(args, base)
+    }
+
+    // 终局小结中的对局结果，result为PGN结果标签，如 "Alice (1850) 胜 siliconflow (level 5)"
+    pub fn result_line(&self, result: &str) -> String {
+        let (white, black) = (self.white.display_name(), self.black.display_name());
+        match result {
+            "1-0" => format!("{} (白方) 胜 {} (黑方)", white, black),
+            "0-1" => format!("{} (黑方) 胜 {} (白方)", black, white),
+            "1/2-1/2" => format!("{} (白方) 与 {} (黑方) 和棋", white, black),
+            _ => format!("{} (白方) 对 {} (黑方)，对局未完成", white, black),
+        }
+    }
+
+    // 有等级分的一方写 WhiteElo/BlackElo 标签
+    pub fn elo_tags(&self) -> Vec<(String, String)> {
+        [("WhiteElo", &self.white), ("BlackElo", &self.black)]
+            .into_iter()
+            .filter_map(|(tag, player)| player.elo.map(|elo| (tag.to_string(), elo.to_string())))
+            .collect()
+    }
+
+    // PGN时间控制标签：双方相同时只写TimeControl；
+    // 不同时TimeControl按规范写 "?"，双方的实际设置分别写在WhiteTimeControl/BlackTimeControl中
+    pub fn time_control_tags(&self) -> Vec<(String, String)> {
+        let value = |tc: Option<TimeControl>| tc.map_or("-".to_string(), |tc| tc.pgn_value());
+        let (white, black) = (self.white.time_control, self.black.time_control);
+        if white == black {
+            vec![("TimeControl".to_string(), value(white))]
+        } else {
+            vec![
+                ("TimeControl".to_string(), "?".to_string()),
+                ("WhiteTimeControl".to_string(), value(white)),
+                ("BlackTimeControl".to_string(), value(black)),
+            ]
+        }
+    }
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Self::from_args(&[]).expect("缺省设置总是有效")
+    }
+}
+
+// chess.cfg 中第一个小节之前的 key = value 设置（小节内的同名键属于引擎配置，不在这里读）
+fn config_value<'a>(config: &'a str, key: &str) -> Option<&'a str> {
+    config
+        .lines()
+        .map(str::trim)
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim().trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn each_side_reads_its_own_settings() {
+        let players = Players::from_args(&args(&[
+            "chess",
+            "--time-white",
+            "1+0",
+            "--time-black",
+            "5+3",
+            "--level",
+            "4",
+            "--level-black",
+            "9",
+            "--assist-white",
+        ]))
+        .unwrap();
+
+        assert_eq!(players.white.difficulty.level(), 4);
+        assert_eq!(players.black.difficulty.level(), 9);
+        assert!(players.white.assist && !players.black.assist);
+
+        // 白方用时20秒（无加秒），黑方用时10秒后加3秒
+        let mut clocks = Clocks::start(&players);
+        clocks.spend(Color::White, Duration::from_secs(20));
+        clocks.spend(Color::Black, Duration::from_secs(10));
+        let limits = players
+            .search_limits(Color::Black, &args(&["chess"]), &clocks)
+            .unwrap();
+        assert_eq!(limits.depth, Some(5));
+        assert_eq!(limits.wtime, Some(Duration::from_secs(40)));
+        assert_eq!(limits.btime, Some(Duration::from_secs(293)));
+        assert_eq!(limits.inc, Some(Duration::from_secs(3)));
+
+        // 超时后剩余时间停在0，不再加秒
+        clocks.spend(Color::Black, Duration::from_secs(600));
+        assert_eq!(clocks.remaining(Color::Black), Some(Duration::ZERO));
+        assert!(clocks.flagged(Color::Black) && !clocks.flagged(Color::White));
+        assert!(!Clocks::start(&Players::default()).flagged(Color::White));
+        assert_eq!(
+            clocks.remaining(Color::White),
+            Some(Duration::from_secs(40))
+        );
+
+        assert_eq!(
+            players.time_control_tags(),
+            vec![
+                ("TimeControl".to_string(), "?".to_string()),
+                ("WhiteTimeControl".to_string(), "60".to_string()),
+                ("BlackTimeControl".to_string(), "300+3".to_string()),
+            ]
+        );
+        assert!(Players::from_args(&args(&["chess", "--time-black", "x+1"])).is_err());
+        assert_eq!(
+            Players::default().time_control_tags(),
+            vec![("TimeControl".to_string(), "-".to_string())]
+        );
+    }
+
+    #[test]
+    fn engine_side_uses_named_profile() {
+        let profiles =
+            engine_profile::parse_profiles("[profiles.blitz]\nlevel = 3\nmovetime = 500\n")
+                .unwrap();
+        let players =
+            Players::with_profiles(&args(&["chess", "--ai", "profile:blitz"]), &profiles).unwrap();
+        assert_eq!(players.black.difficulty.level(), 3);
+        assert!(players.white.profile.is_none());
+        let limits = players
+            .search_limits(
+                Color::Black,
+                &args(&["chess", "--depth", "4"]),
+                &Clocks::start(&players),
+            )
+            .unwrap();
+        assert_eq!(limits.depth, Some(4));
+        assert_eq!(limits.movetime, Some(Duration::from_millis(500)));
+
+        // 分方难度优先于配置
+        let players = Players::with_profiles(
+            &args(&["chess", "--ai", "profile:blitz", "--level-black", "8"]),
+            &profiles,
+        )
+        .unwrap();
+        assert_eq!(players.black.difficulty.level(), 8);
+
+        assert!(
+            Players::with_profiles(&args(&["chess", "--ai", "profile:deep"]), &profiles)
+                .unwrap_err()
+                .contains("可选: blitz")
+        );
+    }
+
+    #[test]
+    fn names_and_ratings_come_from_flags_then_config() {
+        let config = "white_name = Alice\nwhite_elo = 1500\nblack_elo = 2000\n\
+                      [profiles.blitz]\nwhite_name = Ignored\n";
+        let profiles = engine_profile::parse_profiles("[profiles.blitz]\ndepth = 2\n").unwrap();
+        let players = Players::with_config(
+            &args(&["chess", "--white-elo", "1850", "--level", "5"]),
+            &profiles,
+            config,
+        )
+        .unwrap();
+        assert_eq!(players.white.display_name(), "Alice (1850)");
+        assert_eq!(players.black.display_name(), "siliconflow (level 5) (2000)");
+        assert_eq!(
+            players.elo_tags(),
+            vec![
+                ("WhiteElo".to_string(), "1850".to_string()),
+                ("BlackElo".to_string(), "2000".to_string()),
+            ]
+        );
+        assert_eq!(
+            players.result_line("0-1"),
+            "siliconflow (level 5) (2000) (黑方) 胜 Alice (1850) (白方)"
+        );
+
+        let players = Players::with_config(
+            &args(&["chess", "--black-name", "Bob", "--ai", "profile:blitz"]),
+            &profiles,
+            "",
+        )
+        .unwrap();
+        assert_eq!(players.white.name, "Player");
+        assert_eq!(players.black.name, "Bob");
+        let players =
+            Players::with_config(&args(&["chess", "--ai", "profile:blitz"]), &profiles, "")
+                .unwrap();
+        assert_eq!(players.black.name, "siliconflow (profile blitz)");
+        assert!(players.elo_tags().is_empty());
+
+        assert!(Players::from_args(&args(&["chess", "--black-elo", "strong"])).is_err());
+    }
+
+    #[test]
+    fn either_side_can_be_human_or_engine() {
+        let players =
+            Players::from_args(&args(&["chess", "--white", "engine", "--level", "3"])).unwrap();
+        assert_eq!(players.white.controller, Controller::Engine);
+        assert_eq!(players.black.controller, Controller::Engine);
+        assert_eq!(players.white.name, "siliconflow (level 3)");
+
+        let players = Players::from_args(&args(&["chess", "--black", "human"])).unwrap();
+        assert_eq!(players.white.controller, Controller::Human);
+        assert_eq!(players.black.controller, Controller::Human);
+
+        assert!(Players::from_args(&args(&["chess", "--white", "robot"])).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// 长时间任务（perft、批量分析等）的进度报告接口，测试中可替换为记录用的实现
+pub trait Progress {
+    // 开始任务，total为总项数
+    fn start(&self, total: u64);
+    // 又完成了delta项
+    fn advance(&self, delta: u64);
+    // 任务结束（正常完成或被取消）
+    fn finish(&self);
+}
+
+// 命令行进度条：显示已完成项数、速度和预计剩余时间
+pub struct TerminalProgress {
+    bar: ProgressBar,
+}
+
+impl TerminalProgress {
+    pub fn new() -> Self {
+        let style = ProgressStyle::with_template(
+            "[{elapsed_precise}] {bar:40} {pos}/{len} {per_sec} 剩余{eta}",
+        )
+        .unwrap_or_else(|_| ProgressStyle::default_bar());
+        Self {
+            bar: ProgressBar::new(0).with_style(style),
+        }
+    }
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Progress for TerminalProgress {
+    fn start(&self, total: u64) {
+        self.bar.set_length(total);
+        self.bar.reset();
+    }
+
+    fn advance(&self, delta: u64) {
+        self.bar.inc(delta);
+    }
+
+    fn finish(&self) {
+        self.bar.finish();
+    }
+}
+
+// 协作式取消标记：长循环中定期检查，Ctrl-C或取消按钮触发后尽快停止
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // 收到Ctrl-C时触发取消（需要在tokio运行时中调用）
+    pub fn cancel_on_ctrl_c(&self) {
+        let token = self.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                token.cancel();
+            }
+        });
+    }
+}
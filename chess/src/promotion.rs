@@ -0,0 +1,122 @@
+use crate::chessboard::{Color, Piece};
+use crate::credentials::CONFIG_FILE;
+
+// 兵升变的默认处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromotionMode {
+    // 每次弹出菜单询问
+    Ask,
+    // 自动升变为后
+    Queen,
+}
+
+impl PromotionMode {
+    // 读取 --auto-queen 或 --promotion ask|queen；命令行没有给出时取 config（chess.cfg 的内容）
+    // 中的 promotion = ask|queen，缺省为询问
+    pub fn from_config(args: &[String], config: &str) -> Result<Self, String> {
+        if args.iter().any(|arg| arg == "--auto-queen") {
+            return Ok(PromotionMode::Queen);
+        }
+
+        if let Some(index) = args.iter().position(|arg| arg == "--promotion") {
+            return Self::parse(args.get(index + 1).map(String::as_str))
+                .ok_or_else(|| "--promotion 只能是 ask 或 queen".to_string());
+        }
+
+        let setting = config
+            .lines()
+            .map(str::trim)
+            .take_while(|line| !line.starts_with('['))
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim() == "promotion")
+            .map(|(_, value)| value.trim());
+        match setting {
+            None => Ok(PromotionMode::Ask),
+            Some(value) => Self::parse(Some(value))
+                .ok_or_else(|| format!("{} 中的 promotion 只能是 ask 或 queen", CONFIG_FILE)),
+        }
+    }
+
+    // 从 chess.cfg（不存在时视为空）和命令行参数读取
+    pub fn load(args: &[String]) -> Result<Self, String> {
+        let config = std::fs::read_to_string(CONFIG_FILE).unwrap_or_default();
+        Self::from_config(args, &config)
+    }
+
+    fn parse(value: Option<&str>) -> Option<Self> {
+        match value {
+            Some("ask") => Some(PromotionMode::Ask),
+            Some("queen") => Some(PromotionMode::Queen),
+            _ => None,
+        }
+    }
+}
+
+// 升变字母（q/r/b/n，不区分大小写）对应的棋子
+pub fn piece_from_letter(letter: &str, color: Color) -> Option<Piece> {
+    match letter.to_ascii_lowercase().as_str() {
+        "q" => Some(Piece::Queen(color)),
+        "r" => Some(Piece::Rook(color, true)),
+        "b" => Some(Piece::Bishop(color)),
+        "n" => Some(Piece::Knight(color)),
+        _ => None,
+    }
+}
+
+// 走法没有带升变字母时决定升变棋子：自动升变模式或非交互输入时升变为后；
+// 返回None表示需要弹出菜单询问
+pub fn resolve_promotion(mode: PromotionMode, interactive: bool, color: Color) -> Option<Piece> {
+    if mode == PromotionMode::Queen || !interactive {
+        Some(Piece::Queen(color))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_queen_unless_asking_interactively() {
+        assert_eq!(
+            resolve_promotion(PromotionMode::Queen, true, Color::White),
+            Some(Piece::Queen(Color::White))
+        );
+        assert_eq!(
+            resolve_promotion(PromotionMode::Ask, false, Color::Black),
+            Some(Piece::Queen(Color::Black))
+        );
+        assert_eq!(
+            resolve_promotion(PromotionMode::Ask, true, Color::White),
+            None
+        );
+    }
+
+    #[test]
+    fn command_line_overrides_config_file() {
+        let args =
+            |list: &[&str]| -> Vec<String> { list.iter().map(|arg| arg.to_string()).collect() };
+        let config = "# 升变设置\npromotion = queen\n";
+
+        assert_eq!(
+            PromotionMode::from_config(&args(&["chess"]), config),
+            Ok(PromotionMode::Queen)
+        );
+        assert_eq!(
+            PromotionMode::from_config(&args(&["chess", "--promotion", "ask"]), config),
+            Ok(PromotionMode::Ask)
+        );
+        assert_eq!(
+            PromotionMode::from_config(&args(&["chess", "--auto-queen"]), ""),
+            Ok(PromotionMode::Queen)
+        );
+        assert_eq!(
+            PromotionMode::from_config(&args(&["chess"]), ""),
+            Ok(PromotionMode::Ask)
+        );
+        assert!(PromotionMode::from_config(&args(&["chess"]), "promotion = rook").is_err());
+        assert!(PromotionMode::from_config(&args(&["chess", "--promotion"]), config).is_err());
+    }
+}
@@ -0,0 +1,577 @@
+use crate::chessboard::{Chessboard, Color, Move};
+use crate::search_limits::SearchLimits;
+use std::time::Instant;
+
+// 本地搜索引擎：negamax + alpha-beta剪枝，叶子节点用子力加位置分估值（兵100、马320、象330、车500、后900）
+// 云端后端不可用或给出非法走法时作为备用AI；深度不大也能抓住一步杀和白送的子
+
+// 将死分值；减去距根节点的层数，越快的将杀分值越高
+const MATE_SCORE: i32 = 100_000;
+
+// 没有给出深度、节点数和时间时的搜索深度
+pub const DEFAULT_DEPTH: u8 = 4;
+// 按节点数或时间搜索时最多加深到的层数
+pub const MAX_DEPTH: u8 = 32;
+
+// 每展开这么多节点看一次时钟
+const CLOCK_CHECK_INTERVAL: u64 = 1024;
+
+// 局面评估（Chessboard::evaluate，白方为正）换算为轮到走棋的一方为正
+fn evaluate(board: &Chessboard) -> i32 {
+    match board.current_turn() {
+        Color::White => board.evaluate(),
+        Color::Black => -board.evaluate(),
+    }
+}
+
+// 置换表条目的分值类型：搜索窗口内得到精确值，或因剪枝只知道下界/上界
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    // 分值 >= score（发生了beta剪枝）
+    Lower,
+    // 分值 <= score（没有走法超过alpha）
+    Upper,
+}
+
+#[derive(Debug, Clone)]
+struct TtEntry {
+    depth: u8,
+    score: i32,
+    bound: Bound,
+    best: Option<Move>,
+}
+
+// 置换表：以Zobrist局面哈希为键，记录已搜索过的局面，不同走法次序到达同一局面时不再重复展开。
+// 固定大小的数组，局面放在 hash % capacity 号槽位；槽位冲突时保留搜索更深的条目（深度优先替换）
+pub struct TranspositionTable {
+    slots: Vec<Option<(u64, TtEntry)>>,
+    capacity: usize,
+    used: usize,
+}
+
+impl TranspositionTable {
+    // 缺省容量，条目约几十字节，总共不到十几MB
+    pub const DEFAULT_CAPACITY: usize = 200_000;
+
+    // 槽位在第一次写入时才分配
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: Vec::new(),
+            capacity: capacity.max(1),
+            used: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.used = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.used
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.used == 0
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        (hash % self.capacity as u64) as usize
+    }
+
+    fn probe(&self, hash: u64) -> Option<&TtEntry> {
+        match self.slots.get(self.slot(hash)) {
+            Some(Some((stored, entry))) if *stored == hash => Some(entry),
+            _ => None,
+        }
+    }
+
+    // 空槽位、同一局面或搜索不浅于原条目时写入，否则保留原来更深的条目
+    fn store(&mut self, hash: u64, entry: TtEntry) {
+        if self.slots.is_empty() {
+            self.slots = vec![None; self.capacity];
+        }
+        let index = self.slot(hash);
+        let slot = &mut self.slots[index];
+        match slot {
+            None => self.used += 1,
+            Some((stored, old)) if *stored != hash && old.depth > entry.depth => return,
+            Some(_) => {}
+        }
+        *slot = Some((hash, entry));
+    }
+}
+
+// 将杀分值与距根节点的层数有关，存表时换算为距当前节点的层数，取出时再换算回来
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_SCORE - 1000 {
+        score + ply
+    } else if score < -MATE_SCORE + 1000 {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_SCORE - 1000 {
+        score - ply
+    } else if score < -MATE_SCORE + 1000 {
+        score + ply
+    } else {
+        score
+    }
+}
+
+// 一次搜索的状态：可选的置换表、已展开的节点数，以及节点数和时间上限；
+// 超出上限后 stopped 置位，此后各层立即返回，这一轮的结果不再可信
+struct Searcher<'a> {
+    table: Option<&'a mut TranspositionTable>,
+    nodes: u64,
+    node_limit: Option<u64>,
+    deadline: Option<Instant>,
+    stopped: bool,
+}
+
+// 搜索depth层（至少1层）后的最佳走法；没有合法走法时返回None
+pub fn best_move(board: &Chessboard, depth: u8) -> Option<Move> {
+    best_move_with_score(board, depth).map(|(mv, _)| mv)
+}
+
+// 最佳走法及其分值（厘兵，轮到走棋的一方为正）
+pub fn best_move_with_score(board: &Chessboard, depth: u8) -> Option<(Move, i32)> {
+    best_move_with_limits(board, &SearchLimits::depth(depth))
+}
+
+// 按搜索限制找最佳走法：只限深度时直接搜索 depth 层（未给出时为 DEFAULT_DEPTH）；
+// 限制了节点数或时间时迭代加深到 depth 层（未给出时为 MAX_DEPTH），超出上限就停下，
+// 返回最后一轮完整搜索的结果。有合法走法时至少返回一步
+pub fn best_move_with_limits(board: &Chessboard, limits: &SearchLimits) -> Option<(Move, i32)> {
+    deepen(board, limits, |searcher, depth| searcher.root(board, depth)).or_else(|| {
+        // 第一轮的第一步都没搜完，按走法排序取第一步
+        ordered_moves(board)
+            .into_iter()
+            .next()
+            .map(|mv| (mv, evaluate(board)))
+    })
+}
+
+// 所有合法走法按搜索depth层后的分值从高到低排列（分值同 best_move_with_score）；
+// 每步都用完整窗口搜索，分值准确，可以按名次选择次优走法
+pub fn ranked_moves(board: &Chessboard, depth: u8) -> Vec<(Move, i32)> {
+    ranked_moves_with_limits(board, &SearchLimits::depth(depth))
+}
+
+// 按搜索限制给走法排名，加深方式同 best_move_with_limits；
+// 第一轮就超出上限时只包含已经搜完的走法
+pub fn ranked_moves_with_limits(board: &Chessboard, limits: &SearchLimits) -> Vec<(Move, i32)> {
+    let ranked = deepen(board, limits, |searcher, depth| {
+        searcher.ranked(board, depth)
+    });
+    if ranked.is_empty() {
+        return best_move_with_limits(board, limits).into_iter().collect();
+    }
+    ranked
+}
+
+// 使用调用方提供的置换表搜索，连续多步分析时可以复用之前的结果
+pub fn best_move_with_table(
+    board: &Chessboard,
+    depth: u8,
+    table: &mut TranspositionTable,
+) -> Option<Move> {
+    Searcher::new(Some(table))
+        .root(board, depth)
+        .map(|(mv, _)| mv)
+}
+
+// 按limits搜索：不限节点数和时间时搜索一轮；否则从1层开始迭代加深，
+// 某一轮中途超出上限时丢弃这一轮（第一轮除外），用时过半后不再加深（下一层通常比前面所有层加起来还久）
+fn deepen<T: Default>(
+    board: &Chessboard,
+    limits: &SearchLimits,
+    mut search: impl FnMut(&mut Searcher, u8) -> T,
+) -> T {
+    let mut table = TranspositionTable::new(TranspositionTable::DEFAULT_CAPACITY);
+    let started = Instant::now();
+    let budget = limits.time_budget(board.current_turn());
+    let mut searcher = Searcher::new(Some(&mut table));
+    searcher.node_limit = limits.nodes;
+    searcher.deadline = budget.map(|budget| started + budget);
+    if searcher.node_limit.is_none() && searcher.deadline.is_none() {
+        return search(&mut searcher, limits.depth.unwrap_or(DEFAULT_DEPTH));
+    }
+
+    let mut result = None;
+    for depth in 1..=limits.depth.unwrap_or(MAX_DEPTH).max(1) {
+        let found = search(&mut searcher, depth);
+        if searcher.stopped {
+            if result.is_none() {
+                result = Some(found);
+            }
+            break;
+        }
+        result = Some(found);
+        if budget.is_some_and(|budget| started.elapsed() * 2 >= budget) {
+            break;
+        }
+    }
+    result.unwrap_or_default()
+}
+
+impl<'a> Searcher<'a> {
+    fn new(table: Option<&'a mut TranspositionTable>) -> Self {
+        Self {
+            table,
+            nodes: 0,
+            node_limit: None,
+            deadline: None,
+            stopped: false,
+        }
+    }
+
+    // 节点数用完或过了截止时间时置位 stopped；时钟每 CLOCK_CHECK_INTERVAL 个节点看一次
+    fn out_of_budget(&mut self) -> bool {
+        if !self.stopped {
+            let nodes_spent = self.node_limit.is_some_and(|limit| self.nodes >= limit);
+            let time_up = self.nodes.is_multiple_of(CLOCK_CHECK_INTERVAL)
+                && self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline);
+            self.stopped = nodes_spent || time_up;
+        }
+        self.stopped
+    }
+
+    // 中途停止时返回已经搜完的走法中最好的一步
+    fn root(&mut self, board: &Chessboard, depth: u8) -> Option<(Move, i32)> {
+        let mut board = board.clone();
+        let depth = depth.max(1);
+        let mut alpha = -MATE_SCORE - 1;
+        let mut best = None;
+        for mv in self.ordered_moves(&board, None) {
+            let Ok(undo) = board.make_move_unchecked(&mv) else {
+                continue;
+            };
+            let score = -self.negamax(&mut board, depth - 1, 1, -MATE_SCORE - 1, -alpha);
+            board.undo_move(&mv, undo);
+            if self.stopped {
+                break;
+            }
+            if best.is_none() || score > alpha {
+                alpha = score;
+                best = Some(mv);
+            }
+        }
+        best.map(|mv| (mv, alpha))
+    }
+
+    // 各走法完整窗口搜索后的分值，从高到低排列；中途停止时只包含已经搜完的走法
+    fn ranked(&mut self, board: &Chessboard, depth: u8) -> Vec<(Move, i32)> {
+        let mut board = board.clone();
+        let depth = depth.max(1);
+        let mut ranked = Vec::new();
+        for mv in ordered_moves(&board) {
+            let Ok(undo) = board.make_move_unchecked(&mv) else {
+                continue;
+            };
+            let score = -self.negamax(&mut board, depth - 1, 1, -MATE_SCORE - 1, MATE_SCORE + 1);
+            board.undo_move(&mv, undo);
+            if self.stopped {
+                break;
+            }
+            ranked.push((mv, score));
+        }
+        ranked.sort_by_key(|(_, score)| -score);
+        ranked
+    }
+
+    // 以轮到走棋的一方为正的分值；没有合法走法时被将军为将死，否则为逼和（0分）
+    fn negamax(
+        &mut self,
+        board: &mut Chessboard,
+        depth: u8,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if self.out_of_budget() {
+            return 0;
+        }
+        self.nodes += 1;
+        let original_alpha = alpha;
+        let hash = board.position_hash();
+
+        // 先查置换表：深度足够时直接用记录的分值或收紧窗口
+        let mut hash_move = None;
+        if let Some(entry) = self.table.as_ref().and_then(|table| table.probe(hash)) {
+            if entry.depth >= depth {
+                let score = score_from_table(entry.score, ply);
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if cutoff {
+                    return score;
+                }
+                if entry.bound == Bound::Lower {
+                    alpha = alpha.max(score);
+                }
+            }
+            hash_move = entry.best.clone();
+        }
+
+        let moves = self.ordered_moves(board, hash_move);
+        if moves.is_empty() {
+            return if board.is_in_check(board.current_turn()) {
+                -MATE_SCORE + ply
+            } else {
+                0
+            };
+        }
+        if depth == 0 {
+            return evaluate(board);
+        }
+
+        let mut best_score = -MATE_SCORE - 1;
+        let mut best = None;
+        for mv in moves {
+            let Ok(undo) = board.make_move_unchecked(&mv) else {
+                continue;
+            };
+            let score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha);
+            board.undo_move(&mv, undo);
+            if self.stopped {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best = Some(mv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if let Some(table) = self.table.as_mut() {
+            let bound = if best_score >= beta {
+                Bound::Lower
+            } else if best_score <= original_alpha {
+                Bound::Upper
+            } else {
+                Bound::Exact
+            };
+            table.store(
+                hash,
+                TtEntry {
+                    depth,
+                    score: score_to_table(best_score, ply),
+                    bound,
+                    best,
+                },
+            );
+        }
+        best_score
+    }
+
+    // 置换表记录的最佳走法排在最前，其余按 ordered_moves 排序
+    fn ordered_moves(&self, board: &Chessboard, hash_move: Option<Move>) -> Vec<Move> {
+        let mut moves = ordered_moves(board);
+        if let Some(hash_move) = hash_move {
+            if let Some(index) = moves
+                .iter()
+                .position(|mv| mv.to_uci() == hash_move.to_uci())
+            {
+                let mv = moves.remove(index);
+                moves.insert(0, mv);
+            }
+        }
+        moves
+    }
+}
+
+// 吃子走法在前（先吃价值高的子，同样的子用价值低的棋子去吃），让剪枝更早发生
+fn ordered_moves(board: &Chessboard) -> Vec<Move> {
+    let mut moves = board.get_all_legal_moves();
+    moves.sort_by_key(|mv| match board.get(mv.to) {
+        Some(victim) => {
+            let attacker = board.get(mv.from).map_or(0, |piece| piece.value());
+            -(victim.value() * 10 - attacker.min(1000))
+        }
+        None => 0,
+    });
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn score(board: &Chessboard, depth: u8) -> i32 {
+        let mut searcher = Searcher::new(None);
+        searcher.negamax(
+            &mut board.clone(),
+            depth,
+            0,
+            -MATE_SCORE - 1,
+            MATE_SCORE + 1,
+        )
+    }
+
+    fn best(fen: &str, depth: u8) -> String {
+        let board = Chessboard::from_fen(fen).unwrap();
+        let mv = best_move(&board, depth).unwrap();
+        board.move_to_san(&mv)
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        // 底线杀 Ra8#，比吃掉白送的车（Nxh5）更好
+        assert_eq!(best("6k1/5ppp/8/7r/8/6N1/8/R5K1 w - - 0 1", 2), "Ra8#");
+        // 黑方走子时同样找到一步杀
+        assert_eq!(best("6k1/8/8/8/8/8/r4PPP/6K1 b - - 0 1", 1), "Ra1#");
+    }
+
+    #[test]
+    fn takes_free_material_and_scores_terminal_positions() {
+        assert_eq!(best("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 1), "Rxd5");
+
+        let stalemate = Chessboard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(best_move(&stalemate, 3).is_none());
+        assert_eq!(score(&stalemate, 3), 0);
+
+        let mated =
+            Chessboard::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        assert_eq!(score(&mated, 2), -MATE_SCORE);
+        assert_eq!(evaluate(&Chessboard::new()), 0);
+    }
+
+    #[test]
+    fn moves_are_ranked_by_score() {
+        // 吃后最好，其次吃车，其余走法都不吃子
+        let board = Chessboard::from_fen("4k3/8/8/3q4/8/1r6/3R4/1R2K3 w - - 0 1").unwrap();
+        let ranked = ranked_moves(&board, 1);
+        let san: Vec<String> = ranked.iter().map(|(mv, _)| board.move_to_san(mv)).collect();
+        assert_eq!(&san[..2], ["Rxd5", "Rxb3"]);
+        assert_eq!(ranked.len(), board.get_all_legal_moves().len());
+        assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(ranked[0].1, best_move_with_score(&board, 1).unwrap().1);
+    }
+
+    #[test]
+    fn transposition_table_reduces_nodes() {
+        // 王兵残局里王的走法次序不同会频繁到达同一局面
+        let board = Chessboard::from_fen("8/8/3k4/8/8/3K4/4P3/8 w - - 0 1").unwrap();
+        let search = |table: Option<&mut TranspositionTable>| {
+            let mut searcher = Searcher::new(table);
+            assert!(searcher.root(&board, 5).is_some());
+            searcher.nodes
+        };
+
+        let plain_nodes = search(None);
+        let mut table = TranspositionTable::new(TranspositionTable::DEFAULT_CAPACITY);
+        let table_nodes = search(Some(&mut table));
+        assert!(
+            table_nodes < plain_nodes,
+            "{} >= {}",
+            table_nodes,
+            plain_nodes
+        );
+        assert!(!table.is_empty());
+
+        table.clear();
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn node_limit_stops_the_search() {
+        let board = Chessboard::new();
+        let mut searcher = Searcher::new(None);
+        searcher.node_limit = Some(300);
+        searcher.root(&board, 6);
+        assert!(searcher.stopped);
+        assert!(searcher.nodes <= 300);
+
+        // 节点数再少也给出一步合法走法，足够时照样找到一步杀
+        let starved = SearchLimits {
+            nodes: Some(0),
+            ..SearchLimits::default()
+        };
+        assert!(best_move_with_limits(&board, &starved).is_some());
+        assert!(!ranked_moves_with_limits(&board, &starved).is_empty());
+        let mate = Chessboard::from_fen("6k1/5ppp/8/7r/8/6N1/8/R5K1 w - - 0 1").unwrap();
+        let limits = SearchLimits {
+            nodes: Some(2_000),
+            ..SearchLimits::default()
+        };
+        let (mv, _) = best_move_with_limits(&mate, &limits).unwrap();
+        assert_eq!(mate.move_to_san(&mv), "Ra8#");
+    }
+
+    #[test]
+    fn time_limit_stops_the_search() {
+        let board = Chessboard::new();
+        // 不限深度时靠 movetime 或本方剩余时间停下
+        let movetime = SearchLimits {
+            movetime: Some(Duration::from_millis(50)),
+            ..SearchLimits::default()
+        };
+        let clock = SearchLimits {
+            wtime: Some(Duration::from_millis(600)),
+            ..SearchLimits::default()
+        };
+        for limits in [movetime, clock] {
+            let started = Instant::now();
+            assert!(best_move_with_limits(&board, &limits).is_some());
+            assert!(!ranked_moves_with_limits(&board, &limits).is_empty());
+            assert!(started.elapsed() < Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn table_capacity_is_respected() {
+        let entry = |depth| TtEntry {
+            depth,
+            score: 0,
+            bound: Bound::Exact,
+            best: None,
+        };
+        let mut table = TranspositionTable::new(2);
+        for hash in 0..5 {
+            table.store(hash, entry(1));
+        }
+        assert_eq!(table.len(), 2);
+        assert!(table.probe(4).is_some());
+        assert!(table.probe(3).is_some());
+        assert!(table.probe(2).is_none());
+    }
+
+    #[test]
+    fn deeper_entry_survives_shallow_insert() {
+        let entry = |depth| TtEntry {
+            depth,
+            score: depth as i32,
+            bound: Bound::Exact,
+            best: None,
+        };
+        let mut table = TranspositionTable::new(4);
+        // 0 和 4 落在同一个槽位
+        table.store(0, entry(5));
+        table.store(4, entry(1));
+        assert_eq!(table.probe(0).map(|entry| entry.depth), Some(5));
+        assert!(table.probe(4).is_none());
+
+        // 同一局面总是覆盖，更深的新条目替换旧条目
+        table.store(0, entry(2));
+        assert_eq!(table.probe(0).map(|entry| entry.depth), Some(2));
+        table.store(4, entry(3));
+        assert_eq!(table.probe(4).map(|entry| entry.depth), Some(3));
+        assert!(table.probe(0).is_none());
+        assert_eq!(table.len(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::chessboard::Color;
+use std::time::Duration;
+
+// 搜索限制：所有AI后端统一接收这一类型，各后端只处理自己支持的字段
+// - SiliconFlow API：只支持 depth，其余字段忽略
+// - 本地搜索：depth 为最大深度，nodes 为节点数上限，时间上限取 movetime 或按 wtime/btime 分配
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub inc: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u8) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    // 从命令行参数读取 --depth N、--nodes N、--movetime 毫秒，未给出的字段保持base中的值
+    pub fn from_args(args: &[String], base: SearchLimits) -> Result<Self, String> {
+        let mut limits = base;
+
+        for (index, arg) in args.iter().enumerate() {
+            let value = || {
+                args.get(index + 1)
+                    .ok_or_else(|| format!("{} 需要一个参数", arg))
+            };
+            match arg.as_str() {
+                "--depth" => {
+                    limits.depth = Some(
+                        value()?
+                            .parse()
+                            .ok()
+                            .filter(|depth| *depth > 0)
+                            .ok_or_else(|| "--depth 需要正整数".to_string())?,
+                    )
+                }
+                "--nodes" => {
+                    limits.nodes = Some(
+                        value()?
+                            .parse()
+                            .map_err(|_| "--nodes 需要正整数".to_string())?,
+                    )
+                }
+                "--movetime" => {
+                    let millis: u64 = value()?
+                        .parse()
+                        .map_err(|_| "--movetime 需要毫秒数".to_string())?;
+                    limits.movetime = Some(Duration::from_millis(millis));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(limits)
+    }
+
+    // side一方本步可用的时间：给出 movetime 时就用它；否则从该方剩余时间中分配
+    // 约1/30再加一半加秒，最多不超过剩余时间的一半；都没有给出时不限时
+    pub fn time_budget(&self, side: Color) -> Option<Duration> {
+        if self.movetime.is_some() {
+            return self.movetime;
+        }
+        let remaining = match side {
+            Color::White => self.wtime,
+            Color::Black => self.btime,
+        }?;
+        let share = remaining / 30 + self.inc.unwrap_or_default() / 2;
+        Some(share.min(remaining / 2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_flags_override_base_limits() {
+        let args: Vec<String> = ["chess", "--movetime", "1500", "--nodes", "20000"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let limits = SearchLimits::from_args(&args, SearchLimits::depth(4)).unwrap();
+
+        assert_eq!(limits.depth, Some(4));
+        assert_eq!(limits.nodes, Some(20000));
+        assert_eq!(limits.movetime, Some(Duration::from_millis(1500)));
+
+        let bad: Vec<String> = vec!["chess".to_string(), "--depth".to_string()];
+        assert!(SearchLimits::from_args(&bad, SearchLimits::default()).is_err());
+        let zero: Vec<String> = ["chess", "--depth", "0"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert!(SearchLimits::from_args(&zero, SearchLimits::default()).is_err());
+    }
+
+    #[test]
+    fn time_budget_uses_movetime_or_the_movers_clock() {
+        let clocks = SearchLimits {
+            wtime: Some(Duration::from_secs(300)),
+            btime: Some(Duration::from_secs(2)),
+            inc: Some(Duration::from_secs(2)),
+            ..SearchLimits::default()
+        };
+        assert_eq!(
+            clocks.time_budget(Color::White),
+            Some(Duration::from_secs(11))
+        );
+        // 剩余时间不多时最多用一半
+        assert_eq!(
+            clocks.time_budget(Color::Black),
+            Some(Duration::from_secs(1))
+        );
+
+        let fixed = SearchLimits {
+            movetime: Some(Duration::from_millis(500)),
+            ..clocks
+        };
+        assert_eq!(
+            fixed.time_budget(Color::Black),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(SearchLimits::depth(3).time_budget(Color::White), None);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::chessboard::{Chessboard, Outcome};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// 对局录制与回放，用于复现问题：--record session.json 记录每一步已提交的走法、
+// 认输/退出等操作和随机数种子；--replay session.json 按同样的路径重新执行，
+// 每步之后核对FEN，找出第一个不一致的地方
+// 文件格式是稳定的JSON，新增字段时提高 version
+
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub version: u32,
+    // 本局随机数种子（低难度故意失误等）
+    pub seed: u64,
+    pub events: Vec<LoggedEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    // 距录制开始的毫秒数
+    pub elapsed_ms: u64,
+    pub action: Action,
+    // 执行操作后的局面
+    pub fen: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    // 已提交的走法（SAN，如 "e8=Q"；坐标记法 "e7 e8Q" 也能重放）
+    Move { notation: String },
+    // 悔棋一步（第2版新增）
+    Undo,
+    Resign,
+    Quit,
+}
+
+// 对棋盘执行一项操作；录制时的对局和回放共用这一路径
+pub fn apply(board: &mut Chessboard, action: &Action) -> Result<(), String> {
+    match action {
+        Action::Move { notation } => {
+            let mv = board.parse_move(notation)?;
+            board.make_move(&mv).map_err(String::from)
+        }
+        Action::Undo => board.undo_last(),
+        Action::Resign => {
+            board.conclude(Outcome::Resigned(board.current_turn()));
+            Ok(())
+        }
+        Action::Quit => Ok(()),
+    }
+}
+
+// 录制器：每记录一项就写回文件，程序中途崩溃也能留下完整记录
+pub struct Recorder {
+    path: String,
+    start: Instant,
+    log: SessionLog,
+}
+
+impl Recorder {
+    pub fn new(path: &str, seed: u64) -> Self {
+        Self {
+            path: path.to_string(),
+            start: Instant::now(),
+            log: SessionLog {
+                version: FORMAT_VERSION,
+                seed,
+                events: Vec::new(),
+            },
+        }
+    }
+
+    pub fn record(&mut self, action: Action, board: &Chessboard) -> Result<(), String> {
+        self.log.events.push(LoggedEvent {
+            elapsed_ms: self.start.elapsed().as_millis() as u64,
+            action,
+            fen: board.to_fen(),
+        });
+        save(&self.log, &self.path)
+    }
+}
+
+pub fn save(log: &SessionLog, path: &str) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(log).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("无法写入 {}: {}", path, e))
+}
+
+pub fn load(path: &str) -> Result<SessionLog, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("无法读取 {}: {}", path, e))?;
+    let log: SessionLog =
+        serde_json::from_str(&json).map_err(|e| format!("{} 不是有效的录制文件: {}", path, e))?;
+    if log.version > FORMAT_VERSION {
+        return Err(format!(
+            "录制文件版本 {} 比本程序支持的版本 {} 新",
+            log.version, FORMAT_VERSION
+        ));
+    }
+    Ok(log)
+}
+
+// 从初始局面回放全部操作，每步之后核对FEN；on_step在每步执行后调用（用于显示）
+// speedup为加速倍数，0表示不等待
+pub fn replay(
+    log: &SessionLog,
+    speedup: u32,
+    mut on_step: impl FnMut(usize, &Chessboard),
+) -> Result<Chessboard, String> {
+    let mut board = Chessboard::new();
+    let mut previous_ms = 0;
+
+    for (index, event) in log.events.iter().enumerate() {
+        if speedup > 0 {
+            let wait = event.elapsed_ms.saturating_sub(previous_ms) / speedup as u64;
+            std::thread::sleep(Duration::from_millis(wait));
+        }
+        previous_ms = event.elapsed_ms;
+
+        apply(&mut board, &event.action)
+            .map_err(|e| format!("第{}步 {:?} 执行失败: {}", index + 1, event.action, e))?;
+        let fen = board.to_fen();
+        if fen != event.fen {
+            return Err(format!(
+                "第{}步 {:?} 之后局面不一致\n  记录: {}\n  实际: {}",
+                index + 1,
+                event.action,
+                event.fen,
+                fen
+            ));
+        }
+        on_step(index, &board);
+    }
+
+    Ok(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(actions: &[Action]) -> SessionLog {
+        let mut board = Chessboard::new();
+        let mut log = SessionLog {
+            version: FORMAT_VERSION,
+            seed: 42,
+            events: Vec::new(),
+        };
+        for (index, action) in actions.iter().enumerate() {
+            apply(&mut board, action).unwrap();
+            log.events.push(LoggedEvent {
+                elapsed_ms: index as u64 * 100,
+                action: action.clone(),
+                fen: board.to_fen(),
+            });
+        }
+        log
+    }
+
+    fn play(notation: &str) -> Action {
+        Action::Move {
+            notation: notation.to_string(),
+        }
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_session() {
+        let log = record(&[
+            play("e2 e4"),
+            play("e7 e5"),
+            Action::Undo,
+            play("d7 d5"),
+            play("g1 f3"),
+            Action::Resign,
+        ]);
+        let json = serde_json::to_string(&log).unwrap();
+        assert!(json.contains(r#""type":"move""#));
+        assert!(json.contains(r#""type":"undo""#));
+        let parsed: SessionLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, log);
+
+        let mut steps = 0;
+        let board = replay(&parsed, 0, |_, _| steps += 1).unwrap();
+        assert_eq!(steps, 6);
+        assert!(board.is_game_over());
+        assert_eq!(board.move_history(), ["e4", "d5", "Nf3"]);
+    }
+
+    #[test]
+    fn replay_pinpoints_first_divergence() {
+        let mut log = record(&[play("e2 e4"), play("e7 e5"), play("g1 f3")]);
+        log.events[1].fen = log.events[0].fen.clone();
+
+        let error = replay(&log, 0, |_, _| {}).unwrap_err();
+        assert!(error.starts_with("第2步"), "{}", error);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::chessboard::{Chessboard, Color, GameStatus};
+use crate::epd;
+use crate::pgn;
+use std::fmt;
+
+// 一致性检查子命令：chess verify <PGN或FEN文件>
+// PGN中的每盘棋逐步重放：重新生成SAN与原文比对、每步导出FEN再导入、
+// 把走子时记录的局面哈希与从导出局面重新计算的哈希比对，最后核对Result标签；
+// FEN/EPD文件每行一个局面，检查导入导出往返，并对每个合法走法检查SAN往返和走子/悔棋
+
+// 一处不一致
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discrepancy {
+    // 第几盘棋（PGN）或第几行（FEN），从1开始
+    pub item: usize,
+    // 着法位置，如 "12. e4" 或 "12... Nf6"；整盘或整个局面的问题为空
+    pub at: String,
+    pub check: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.item)?;
+        if !self.at.is_empty() {
+            write!(f, " {}", self.at)?;
+        }
+        write!(
+            f,
+            " [{}] 期望: {} 实际: {}",
+            self.check, self.expected, self.actual
+        )
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let input = args
+        .get(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("用法: chess verify <PGN或FEN文件>")?;
+    let content =
+        std::fs::read_to_string(input).map_err(|e| format!("无法读取 {}: {}", input, e))?;
+
+    let (kind, (count, discrepancies)) = if looks_like_pgn(&content) {
+        ("盘棋", verify_pgn(&content))
+    } else {
+        ("个局面", verify_fens(&content))
+    };
+    for discrepancy in &discrepancies {
+        println!("{}", discrepancy);
+    }
+    println!(
+        "共检查{}{}，发现{}处不一致",
+        count,
+        kind,
+        discrepancies.len()
+    );
+    Ok(())
+}
+
+// 有标签行或着法编号的文件按PGN处理，否则每行一个FEN
+fn looks_like_pgn(content: &str) -> bool {
+    content.lines().any(|line| {
+        let line = line.trim();
+        line.starts_with('[') || line.starts_with("1.")
+    })
+}
+
+// 导出FEN后重新导入，检查往返一致和哈希一致
+fn check_position(board: &Chessboard, item: usize, at: &str, found: &mut Vec<Discrepancy>) {
+    let mut report = |check, expected: String, actual: String| {
+        found.push(Discrepancy {
+            item,
+            at: at.to_string(),
+            check,
+            expected,
+            actual,
+        })
+    };
+
+    let fen = board.to_fen();
+    let reimported = match Chessboard::from_fen(&fen) {
+        Ok(reimported) => reimported,
+        Err(e) => return report("FEN导入", fen, e.to_string()),
+    };
+    if reimported.to_fen() != fen {
+        report("FEN往返", fen, reimported.to_fen());
+    }
+    if reimported.position_hash() != board.recorded_hash() {
+        report(
+            "哈希",
+            format!("{:016x}", reimported.position_hash()),
+            format!("{:016x}", board.recorded_hash()),
+        );
+    }
+}
+
+// 检查PGN文本，返回棋局数和发现的不一致
+pub fn verify_pgn(content: &str) -> (usize, Vec<Discrepancy>) {
+    let games = pgn::split_games(content);
+    let mut found = Vec::new();
+    for (index, game) in games.iter().enumerate() {
+        verify_game(game, index + 1, &mut found);
+    }
+    (games.len(), found)
+}
+
+fn verify_game(game: &pgn::PgnGame, item: usize, found: &mut Vec<Discrepancy>) {
+    let mut board = match &game.fen {
+        Some(fen) => match Chessboard::from_fen(fen) {
+            Ok(board) => board,
+            Err(e) => {
+                found.push(Discrepancy {
+                    item,
+                    at: String::new(),
+                    check: "FEN标签",
+                    expected: fen.clone(),
+                    actual: e.to_string(),
+                });
+                return;
+            }
+        },
+        None => Chessboard::new(),
+    };
+
+    for token in &game.moves {
+        let number = board.fullmove_number();
+        let at = match board.current_turn() {
+            Color::White => format!("{}. {}", number, token),
+            Color::Black => format!("{}... {}", number, token),
+        };
+        let source = token.trim_end_matches(['!', '?']);
+
+        let played = board
+            .parse_san(source)
+            .and_then(|mv| board.make_move(&mv).map_err(String::from));
+        if let Err(e) = played {
+            // 之后的着法都无从检查，这盘棋到此为止
+            found.push(Discrepancy {
+                item,
+                at,
+                check: "着法",
+                expected: source.to_string(),
+                actual: e,
+            });
+            return;
+        }
+
+        let san = board.move_history().last().expect("刚走过一步");
+        if san != source {
+            found.push(Discrepancy {
+                item,
+                at: at.clone(),
+                check: "SAN",
+                expected: source.to_string(),
+                actual: san.clone(),
+            });
+        }
+        check_position(&board, item, &at, found);
+    }
+
+    // 已分胜负或和棋时结果必须一致；对局未结束时任何结果都可能（认输、议和）
+    let computed = pgn::result_tag(&board);
+    let tag = game.result.as_deref().unwrap_or("*");
+    if board.status() != GameStatus::Ongoing && tag != computed {
+        found.push(Discrepancy {
+            item,
+            at: String::new(),
+            check: "结果",
+            expected: computed.to_string(),
+            actual: tag.to_string(),
+        });
+    }
+}
+
+// 检查FEN/EPD文件（每行一个局面，# 开头为注释），返回局面数和发现的不一致
+pub fn verify_fens(content: &str) -> (usize, Vec<Discrepancy>) {
+    let mut found = Vec::new();
+    let mut count = 0;
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        count += 1;
+        let item = index + 1;
+        let board = match Chessboard::from_fen(line) {
+            Ok(board) => board,
+            Err(fen_error) => match epd::parse_epd(line) {
+                Ok(record) => record.board,
+                Err(_) => {
+                    found.push(Discrepancy {
+                        item,
+                        at: String::new(),
+                        check: "FEN导入",
+                        expected: line.to_string(),
+                        actual: fen_error.to_string(),
+                    });
+                    continue;
+                }
+            },
+        };
+        check_position(&board, item, "", &mut found);
+
+        // 每个合法走法：SAN能读回同一走法，走子再悔棋回到原局面
+        let fen = board.to_fen();
+        let mut scratch = board.clone();
+        for mv in board.get_all_legal_moves() {
+            let san = board.move_to_san(&mv);
+            match board.parse_san(&san) {
+                Ok(parsed) if parsed.to_uci() == mv.to_uci() => {}
+                parsed => found.push(Discrepancy {
+                    item,
+                    at: san.clone(),
+                    check: "SAN往返",
+                    expected: mv.to_uci(),
+                    actual: parsed.map_or_else(|e| e, |parsed| parsed.to_uci()),
+                }),
+            }
+            let restored = scratch.make_move(&mv).is_ok() && scratch.undo_last().is_ok();
+            if !restored || scratch.to_fen() != fen {
+                found.push(Discrepancy {
+                    item,
+                    at: san,
+                    check: "悔棋",
+                    expected: fen.clone(),
+                    actual: scratch.to_fen(),
+                });
+                scratch = board.clone();
+            }
+        }
+    }
+    (count, found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_games_have_no_discrepancies() {
+        let content = "[Event \"a\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n\
+                       [Event \"b\"]\n[Result \"*\"]\n\n\
+                       1. e4 {最常见} e5 (1... c5 2. Nf3) 2. Nf3 $1 Nc6 3. Bb5 a6 ; 西班牙开局\n\
+                       4. O-O *\n";
+        let (count, found) = verify_pgn(content);
+        assert_eq!(count, 2);
+        assert!(found.is_empty(), "{:?}", found);
+    }
+
+    #[test]
+    fn discrepancies_are_reported_with_position() {
+        // 缺少将杀符号、结果与将杀不符、第二盘走了不合法的着法
+        let content = "[Result \"0-1\"]\n\n1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7 0-1\n\n\
+                       [Result \"*\"]\n\n1. e4 e5 2. Ke3 *\n";
+        let (count, found) = verify_pgn(content);
+        assert_eq!(count, 2);
+        assert_eq!(found.len(), 3, "{:?}", found);
+
+        assert_eq!(found[0].item, 1);
+        assert_eq!(found[0].at, "4. Qxf7");
+        assert_eq!(found[0].check, "SAN");
+        assert_eq!(found[0].actual, "Qxf7#");
+
+        assert_eq!(found[1].check, "结果");
+        assert_eq!(found[1].expected, "1-0");
+        assert_eq!(found[1].actual, "0-1");
+
+        assert_eq!(found[2].item, 2);
+        assert_eq!(found[2].at, "2. Ke3");
+        assert_eq!(found[2].check, "着法");
+    }
+
+    #[test]
+    fn fen_lines_are_checked() {
+        let content = "# 测试局面\n\
+                       rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n\
+                       r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - bm e2a6;\n\
+                       8/8/8 w - -\n";
+        assert!(!looks_like_pgn(content));
+        let (count, found) = verify_fens(content);
+        assert_eq!(count, 3);
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert_eq!(found[0].item, 4);
+        assert_eq!(found[0].check, "FEN导入");
+    }
+}
@@ -0,0 +1,31 @@
+// 只通过库的公开接口使用棋盘，不依赖命令行程序
+use chess::{search, Chessboard, Color, Piece, Position};
+
+#[test]
+fn library_plays_a_game_without_the_binary() {
+    let mut board = Chessboard::new();
+    for notation in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6"] {
+        let mv = board.parse_move(notation).unwrap();
+        board.make_move(&mv).unwrap();
+    }
+    assert_eq!(board.current_turn(), Color::White);
+
+    // 搜索应找到一步杀 Qxf7#
+    let mate = search::best_move(&board, 2).unwrap();
+    assert_eq!(mate.to_notation(), "h5 f7");
+    board.make_move(&mate).unwrap();
+    assert!(board.is_game_over());
+    assert_eq!(
+        board.get(Position::from_notation("f7").unwrap()),
+        Some(Piece::Queen(Color::White))
+    );
+    assert!(board.get_random_legal_move().is_none());
+}
+
+#[test]
+fn library_reads_and_writes_fen() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    let board = Chessboard::from_fen(fen).unwrap();
+    assert!(board.castling_rights().black_queenside);
+    assert_eq!(board.to_fen(), fen);
+}
@@ -1,343 +1,2036 @@
-use bevy::prelude::*;
-use bevy_tweening::*;
-
-// 棋盘属性（8x8格子，单个格子尺寸）
-#[derive(Component)]
-struct Chessboard {
-    cell_size: f32,  // 单个格子像素尺寸（如100.0）
-}
-
-// 棋子类型（王/后/车/象/马/兵）
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum PieceType {
-    King, Queen, Rook, Bishop, Knight, Pawn
-}
-
-// 棋子颜色（黑/白）
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum PieceColor {
-    White, Black
-}
-
-// 棋子组件（关联类型、颜色、位置）
-#[derive(Component)]
-struct Piece {
-    piece_type: PieceType,
-    color: PieceColor,
-    position: (u8, u8),  // (行, 列)，范围0-7（对应棋盘8x8）
-}
-
-// 拖放状态组件（标记是否正在拖动）
-#[derive(Component)]
-struct Dragging {
-    start_position: Vec3,  // 拖动起始位置
-}
-
-// 动画组件（用于棋子移动/消失动画）
-#[derive(Component)]
-struct PieceAnimation(Tween<Transform>);
-/// 初始化棋盘
-fn setup_board(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
-    let cell_size = 100.0;  // 每个格子100x100像素
-    let board_size = cell_size * 8.0;  // 棋盘总尺寸800x800
-
-    // 生成8x8格子
-    for row in 0..8 {
-        for col in 0..8 {
-            // 交替颜色（白/棕）
-            let color = if (row + col) % 2 == 0 {
-                Color::rgb(0.9, 0.9, 0.9)  // 白色格子
-            } else {
-                Color::rgb(0.5, 0.3, 0.1)  // 棕色格子
-            };
-
-            // 计算格子位置（原点在屏幕中心，棋盘居中）
-            let x = col as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-            let y = row as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-
-            // 生成格子实体（2D矩形）
-            commands.spawn(SpriteBundle {
-                sprite: Sprite {
-                    custom_size: Some(Vec2::new(cell_size, cell_size)),
-                    ..default()
-                },
-                transform: Transform::from_xyz(x, y, 0.0),  // z=0（底层）
-                material: materials.add(color.into()),
-                ..default()
-            });
-        }
-    }
-
-    // 生成棋盘根实体（存储属性）
-    commands.spawn((
-        Chessboard { cell_size },
-        Transform::from_xyz(0.0, 0.0, 0.0),  // 棋盘居中
-        GlobalTransform::default(),
-    ));
-}
-// 棋子纹理资源（存储所有棋子的图片句柄）
-#[derive(Resource)]
-struct PieceTextures {
-    white_king: Handle<Image>,
-    white_queen: Handle<Image>,
-    // ... 其他白棋类型
-    black_king: Handle<Image>,
-    black_queen: Handle<Image>,
-    // ... 其他黑棋类型
-}
-
-/// 加载棋子纹理资源
-fn load_piece_textures(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-) {
-    commands.insert_resource(PieceTextures {
-        white_king: asset_server.load("textures/white_king.png"),
-        white_queen: asset_server.load("textures/white_queen.png"),
-        // ... 补充其他棋子纹理路径
-        black_king: asset_server.load("textures/black_king.png"),
-        black_queen: asset_server.load("textures/black_queen.png"),
-        // ... 补充其他棋子纹理路径
-    });
-}
-
-/// 初始化棋子（按国际象棋初始位置放置）
-fn setup_pieces(
-    mut commands: Commands,
-    board: Query<&Chessboard>,
-    textures: Res<PieceTextures>,
-) {
-    let board = board.single();
-    let cell_size = board.cell_size;
-    let board_size = cell_size * 8.0;
-
-    // 白方后排（row=0）：车、马、象、后、王、象、马、车
-    let white_back_row = [
-        (PieceType::Rook, 0, 0),
-        (PieceType::Knight, 0, 1),
-        (PieceType::Bishop, 0, 2),
-        (PieceType::Queen, 0, 3),
-        (PieceType::King, 0, 4),
-        (PieceType::Bishop, 0, 5),
-        (PieceType::Knight, 0, 6),
-        (PieceType::Rook, 0, 7),
-    ];
-    // 白方兵（row=1）
-    let white_pawns: Vec<_> = (0..8).map(|col| (PieceType::Pawn, 1, col)).collect();
-
-    // 黑方后排（row=7）和兵（row=6）类似，略...
-
-    // 生成白方棋子
-    for (piece_type, row, col) in white_back_row.into_iter().chain(white_pawns) {
-        spawn_piece(
-            &mut commands,
-            piece_type,
-            PieceColor::White,
-            (row, col),
-            cell_size,
-            board_size,
-            &textures,
-        );
-    }
-}
-
-/// 生成单个棋子实体
-fn spawn_piece(
-    commands: &mut Commands,
-    piece_type: PieceType,
-    color: PieceColor,
-    position: (u8, u8),
-    cell_size: f32,
-    board_size: f32,
-    textures: &PieceTextures,
-) {
-    // 根据类型和颜色获取纹理
-    let texture = match (color, piece_type) {
-        (PieceColor::White, PieceType::King) => textures.white_king.clone(),
-        (PieceColor::White, PieceType::Queen) => textures.white_queen.clone(),
-        // ... 补充其他类型映射
-        _ => panic!("未定义的棋子纹理"),
-    };
-
-    // 计算棋子位置（居中于格子）
-    let (row, col) = position;
-    let x = col as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-    let y = row as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-
-    // 生成棋子实体（Sprite + Piece组件）
-    commands.spawn((
-        SpriteBundle {
-            texture,
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(cell_size * 0.8, cell_size * 0.8)),  // 棋子比格子小20%
-                ..default()
-            },
-            transform: Transform::from_xyz(x, y, 1.0),  // z=1（在棋盘上方）
-            ..default()
-        },
-        Piece { piece_type, color, position },
-    ));
-}
-/// 处理拖动开始（鼠标按下时）
-fn start_drag(
-    mut commands: Commands,
-    mouse_btn_input: Res<Input<MouseButton>>,
-    cursor_pos: Res<CursorPosition>,  // 需要手动实现的光标位置资源
-    mut pieces: Query<(Entity, &mut Transform, &Piece)>,
-) {
-    if mouse_btn_input.just_pressed(MouseButton::Left) {
-        if let Some(cursor_world_pos) = cursor_pos.0 {  // 光标世界坐标（需转换屏幕->世界）
-            // 检测鼠标是否点击了棋子（简化：距离判断）
-            for (entity, transform, _) in &mut pieces {
-                let distance = transform.translation.distance(cursor_world_pos);
-                if distance < 50.0 {  // 假设棋子半径50像素内视为点击
-                    // 标记为正在拖动
-                    commands.entity(entity).insert(Dragging {
-                        start_position: transform.translation.clone(),
-                    });
-                    // 提升z轴层级（避免被其他棋子遮挡）
-                    transform.translation.z = 2.0;
-                    break;
-                }
-            }
-        }
-    }
-}
-
-/// 处理拖动中（鼠标移动时）
-fn drag_move(
-    cursor_pos: Res<CursorPosition>,
-    mut dragging_pieces: Query<&mut Transform, With<Dragging>>,
-) {
-    if let Some(cursor_world_pos) = cursor_pos.0 {
-        for mut transform in &mut dragging_pieces {
-            // 棋子跟随鼠标（保持z轴不变）
-            transform.translation.x = cursor_world_pos.x;
-            transform.translation.y = cursor_world_pos.y;
-        }
-    }
-}
-
-/// 处理拖动结束（鼠标释放时）
-fn end_drag(
-    mut commands: Commands,
-    mouse_btn_input: Res<Input<MouseButton>>,
-    board: Query<&Chessboard>,
-    mut dragging_pieces: Query<(Entity, &mut Transform, &Piece, &Dragging)>,
-) {
-    if mouse_btn_input.just_released(MouseButton::Left) {
-        let board = board.single();
-        let cell_size = board.cell_size;
-        let board_size = cell_size * 8.0;
-
-        for (entity, mut transform, piece, dragging) in &mut dragging_pieces {
-            // 计算鼠标释放位置对应的棋盘格子（行/列）
-            let target_col = ((transform.translation.x + board_size / 2.0) / cell_size).round() as u8;
-            let target_row = ((transform.translation.y + board_size / 2.0) / cell_size).round() as u8;
-            let target_pos = (target_row.clamp(0, 7), target_col.clamp(0, 7));  // 限制在棋盘内
-
-            // 检查移动是否合法（简化：仅示例，需对接国际象棋规则）
-            let is_valid = true;  // 实际需根据棋子类型/颜色判断
-
-            if is_valid {
-                // 移动到目标格子（触发动画）
-                let target_x = target_col as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-                let target_y = target_row as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-                start_move_animation(&mut commands, entity, transform.translation, Vec3::new(target_x, target_y, 1.0));
-            } else {
-                // 非法移动，回到起始位置（触发动画）
-                start_move_animation(&mut commands, entity, transform.translation, dragging.start_position);
-            }
-
-            // 移除拖动状态，恢复z轴
-            commands.entity(entity).remove::<Dragging>();
-            transform.translation.z = 1.0;
-        }
-    }
-}
-
-/// 辅助函数：开始移动动画
-fn start_move_animation(commands: &mut Commands, entity: Entity, start: Vec3, end: Vec3) {
-    // 使用bevy_tweening创建位置插值动画（0.3秒线性移动）
-    let tween = Tween::new(
-        EaseFunction::Linear,
-        Duration::from_secs_f32(0.3),
-        TransformPositionLens { start, end },
-    );
-    commands.entity(entity).insert(PieceAnimation(tween));
-}
-/// 驱动棋子动画
-fn run_animations(
-    mut query: Query<(&mut Transform, &mut PieceAnimation)>,
-    time: Res<Time>,
-) {
-    for (mut transform, mut animation) in &mut query {
-        // 更新动画进度
-        let _ = animation.0.update(time.delta());
-        // 应用动画到Transform
-        animation.0.apply(&mut transform);
-        // 动画结束后移除组件
-        if animation.0.finished() {
-            // 可选：更新棋子位置属性（Piece.position）
-        }
-    }
-}
-
-/// 选中棋子时高亮格子（示例）
-fn highlight_selected(
-    mut commands: Commands,
-    selected_piece: Query<&Piece, With<Dragging>>,  // 仅高亮正在拖动的棋子原位置
-    board: Query<&Chessboard>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    // 清除之前的高亮
-    // ...
-
-    if let Ok(piece) = selected_piece.get_single() {
-        let (row, col) = piece.position;
-        let board = board.single();
-        let cell_size = board.cell_size;
-        let board_size = cell_size * 8.0;
-
-        // 计算高亮位置（原格子上方，半透明绿色）
-        let x = col as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-        let y = row as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
-
-        commands.spawn(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(cell_size, cell_size)),
-                ..default()
-            },
-            transform: Transform::from_xyz(x, y, 0.5),  // z=0.5（在棋盘和棋子之间）
-            material: materials.add(Color::rgba(0.2, 0.8, 0.2, 0.3).into()),  // 半透明绿
-            ..default()
-        });
-    }
-}
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            window: WindowDescriptor {
-                title: "国际象棋".to_string(),
-                width: 800.0,
-                height: 800.0,
-                ..default()
-            },
-            ..default()
-        }))
-        .add_plugin(TweeningPlugin)  // 动画插件
-        .insert_resource(CursorPosition(None))  // 光标位置资源（需实现更新逻辑）
-        // 初始化系统
-        .add_startup_system(setup_board)
-        .add_startup_system(load_piece_textures)
-        .add_startup_system(setup_pieces.after(load_piece_textures))
-        // 交互系统
-        .add_system(update_cursor_position)  // 需实现：屏幕坐标转世界坐标
-        .add_system(start_drag)
-        .add_system(drag_move)
-        .add_system(end_drag)
-        // 动画系统
-        .add_system(run_animations)
-        .add_system(highlight_selected)
-        .run();
-}
\ No newline at end of file
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_tweening::lens::TransformPositionLens;
+use bevy_tweening::*;
+use chess::chessboard::{GameStatus, Language, Move, Position};
+use chess::RenderOptions;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// 棋盘属性（8x8格子，单个格子尺寸）
+#[derive(Component)]
+struct Chessboard {
+    cell_size: f32, // 单个格子像素尺寸（如100.0）
+}
+
+// 棋子类型（王/后/车/象/马/兵）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PieceType {
+    King,
+    Queen,
+    Rook,
+    Bishop,
+    Knight,
+    Pawn,
+}
+
+// 棋子颜色（黑/白）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PieceColor {
+    White,
+    Black,
+}
+
+// 棋子组件（关联类型、颜色、位置）
+#[derive(Component)]
+struct Piece {
+    piece_type: PieceType,
+    color: PieceColor,
+    position: (u8, u8), // (行, 列)，范围0-7（对应棋盘8x8）
+}
+
+// 坐标约定（与核心规则的 Position 一致，白方视角）：
+// row=0 为第8横排、显示在屏幕顶部；col=0 为a列、显示在屏幕左侧
+
+/// 棋盘格子 (行, 列) -> 屏幕坐标（格子中心，原点在棋盘中心）
+fn square_to_screen(position: (u8, u8), cell_size: f32) -> Vec2 {
+    let (row, col) = position;
+    let board_size = cell_size * 8.0;
+    let x = col as f32 * cell_size - board_size / 2.0 + cell_size / 2.0;
+    let y = board_size / 2.0 - row as f32 * cell_size - cell_size / 2.0;
+    Vec2::new(x, y)
+}
+
+/// 屏幕坐标 -> 棋盘格子 (行, 列)，在棋盘外时返回None
+fn screen_to_square(point: Vec2, cell_size: f32) -> Option<(u8, u8)> {
+    let board_size = cell_size * 8.0;
+    let col = ((point.x + board_size / 2.0) / cell_size).floor();
+    let row = ((board_size / 2.0 - point.y) / cell_size).floor();
+    if (0.0..8.0).contains(&col) && (0.0..8.0).contains(&row) {
+        Some((row as u8, col as u8))
+    } else {
+        None
+    }
+}
+
+/// 棋盘格子 (行, 列) -> 代数记谱（如 a1）
+fn square_to_notation(position: (u8, u8)) -> String {
+    let (row, col) = position;
+    format!("{}{}", (b'a' + col) as char, 8 - row)
+}
+
+// 权威棋局：走法是否合法、吃子/易位/升变后的布局都以核心规则引擎（chess 库）为准，
+// 屏幕上的棋子实体只是它的显示（不叫 Chessboard，以免与上面的棋盘组件重名）
+#[derive(Resource, Default)]
+struct Game {
+    board: chess::Chessboard,
+}
+
+/// 棋盘格子 (行, 列) -> 核心规则的 Position（两者的行列约定相同）
+fn to_position(square: (u8, u8)) -> Position {
+    Position::new(square.0 as usize, square.1 as usize).expect("格子在棋盘内")
+}
+
+fn from_position(position: Position) -> (u8, u8) {
+    (position.row as u8, position.col as u8)
+}
+
+/// 核心规则的棋子 -> 棋子类型和颜色
+fn piece_kind(piece: chess::Piece) -> (PieceType, PieceColor) {
+    let piece_type = match piece {
+        chess::Piece::King(_, _) => PieceType::King,
+        chess::Piece::Queen(_) => PieceType::Queen,
+        chess::Piece::Rook(_, _) => PieceType::Rook,
+        chess::Piece::Bishop(_) => PieceType::Bishop,
+        chess::Piece::Knight(_) => PieceType::Knight,
+        chess::Piece::Pawn(_, _) => PieceType::Pawn,
+    };
+    (piece_type, piece_color(piece.color()))
+}
+
+fn piece_color(color: chess::Color) -> PieceColor {
+    match color {
+        chess::Color::White => PieceColor::White,
+        chess::Color::Black => PieceColor::Black,
+    }
+}
+
+// 棋盘上的一个棋子：类型、颜色和所在格子 (行, 列)
+type PlacedPiece = (PieceType, PieceColor, (u8, u8));
+
+/// 规则引擎当前局面的全部棋子
+fn board_layout(board: &chess::Chessboard) -> Vec<PlacedPiece> {
+    let mut layout = Vec::new();
+    for row in 0..8 {
+        for col in 0..8 {
+            if let Some(piece) = board.get(to_position((row, col))) {
+                let (piece_type, color) = piece_kind(piece);
+                layout.push((piece_type, color, (row, col)));
+            }
+        }
+    }
+    layout
+}
+
+/// 起点上棋子的全部合法落点（已考虑牵制、将军和易位）；不是走棋方的棋子没有落点
+fn valid_moves(board: &chess::Chessboard, from: (u8, u8)) -> Vec<(u8, u8)> {
+    let from = to_position(from);
+    match board.get(from) {
+        Some(piece) if piece.color() == board.current_turn() => {
+            let mut targets: Vec<(u8, u8)> = board
+                .get_legal_moves(from)
+                .iter()
+                .map(|mv| from_position(mv.to))
+                .collect();
+            // 升变的四种走法落点相同
+            targets.sort();
+            targets.dedup();
+            targets
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// 拖放对应的走法；兵走到底线时自动升变为后
+fn drop_move(board: &chess::Chessboard, from: (u8, u8), to: (u8, u8)) -> Move {
+    let mut mv = Move {
+        from: to_position(from),
+        to: to_position(to),
+        promotion: None,
+    };
+    if board.is_promotion(&mv) {
+        mv.promotion = Some(chess::Piece::Queen(board.current_turn()));
+    }
+    mv
+}
+
+/// 走完一步后，把屏幕上的棋子与规则引擎的新布局对比：
+/// 返回不再存在的棋子（被吃的、易位前的车、升变前的兵）在 shown 中的下标，以及需要新生成的棋子
+fn layout_changes(shown: &[PlacedPiece], layout: &[PlacedPiece]) -> (Vec<usize>, Vec<PlacedPiece>) {
+    let stale = (0..shown.len())
+        .filter(|&i| !layout.contains(&shown[i]))
+        .collect();
+    let missing = layout
+        .iter()
+        .filter(|entry| !shown.contains(entry))
+        .copied()
+        .collect();
+    (stale, missing)
+}
+
+// 拖放状态组件（标记是否正在拖动）
+#[derive(Component)]
+struct Dragging {
+    start_position: Vec3,       // 拖动起始位置
+    valid_moves: Vec<(u8, u8)>, // 规则引擎给出的合法落点
+}
+
+// 光标的世界坐标（相机在原点，与棋盘坐标一致），光标不在窗口内时为None
+#[derive(Resource, Default)]
+struct CursorPosition(Option<Vec3>);
+
+/// 窗口坐标（左下角为原点）换算为世界坐标（窗口中心为原点）
+fn update_cursor_position(windows: Res<Windows>, mut cursor: ResMut<CursorPosition>) {
+    cursor.0 = windows.get_primary().and_then(|window| {
+        let position = window.cursor_position()?;
+        Some(Vec3::new(
+            position.x - window.width() / 2.0,
+            position.y - window.height() / 2.0,
+            0.0,
+        ))
+    });
+}
+/// 初始化棋盘
+fn setup_board(mut commands: Commands, theme: Res<ThemeConfig>) {
+    let cell_size = 100.0; // 每个格子100x100像素，棋盘总尺寸800x800
+    commands.spawn(Camera2dBundle::default()); // 2D相机，原点在窗口中心
+
+    // 生成8x8格子
+    for row in 0..8 {
+        for col in 0..8 {
+            // 交替颜色（浅/深，取自主题）
+            let color = if (row + col) % 2 == 0 {
+                theme.light_square
+            } else {
+                theme.dark_square
+            };
+
+            // 计算格子位置（原点在屏幕中心，棋盘居中）
+            let center = square_to_screen((row as u8, col as u8), cell_size);
+
+            // 生成格子实体（2D矩形）
+            commands.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(cell_size, cell_size)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(center.x, center.y, 0.0), // z=0（底层）
+                ..default()
+            });
+        }
+    }
+
+    // 生成棋盘根实体（存储属性）
+    commands.spawn((
+        Chessboard { cell_size },
+        Transform::from_xyz(0.0, 0.0, 0.0), // 棋盘居中
+        GlobalTransform::default(),
+    ));
+}
+// 主题配置资源（棋子纹理目录和棋盘配色）
+#[derive(Resource, Clone)]
+struct ThemeConfig {
+    name: String,
+    piece_dir: String,   // 棋子图片目录，文件名形如 white_king.png
+    light_square: Color, // 浅色格子
+    dark_square: Color,  // 深色格子
+}
+
+impl ThemeConfig {
+    /// 内置主题：经典
+    fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            piece_dir: "themes/classic".to_string(),
+            light_square: Color::rgb(0.9, 0.9, 0.9),
+            dark_square: Color::rgb(0.5, 0.3, 0.1),
+        }
+    }
+
+    /// 内置主题：木纹
+    fn wood() -> Self {
+        Self {
+            name: "wood".to_string(),
+            piece_dir: "themes/wood".to_string(),
+            light_square: Color::rgb(0.94, 0.85, 0.71),
+            dark_square: Color::rgb(0.71, 0.53, 0.39),
+        }
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Self::classic()),
+            "wood" => Some(Self::wood()),
+            _ => None,
+        }
+    }
+
+    /// 从配置文件加载主题（每行 key = value，支持 theme / piece_dir / light / dark）
+    /// 文件不存在或无法解析时回退到经典主题
+    fn load(path: &str) -> Self {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return Self::classic(),
+        };
+
+        let mut theme = Self::classic();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "theme" => {
+                    if let Some(builtin) = Self::builtin(value) {
+                        theme = builtin;
+                    }
+                }
+                "piece_dir" => theme.piece_dir = value.to_string(),
+                "light" => {
+                    if let Ok(color) = Color::hex(value) {
+                        theme.light_square = color;
+                    }
+                }
+                "dark" => {
+                    if let Ok(color) = Color::hex(value) {
+                        theme.dark_square = color;
+                    }
+                }
+                _ => {}
+            }
+        }
+        theme
+    }
+
+    /// 棋子图片路径（相对于assets目录）
+    fn piece_path(&self, color: PieceColor, piece_type: PieceType) -> String {
+        let color = match color {
+            PieceColor::White => "white",
+            PieceColor::Black => "black",
+        };
+        let piece = match piece_type {
+            PieceType::King => "king",
+            PieceType::Queen => "queen",
+            PieceType::Rook => "rook",
+            PieceType::Bishop => "bishop",
+            PieceType::Knight => "knight",
+            PieceType::Pawn => "pawn",
+        };
+        format!("{}/{}_{}.png", self.piece_dir, color, piece)
+    }
+}
+
+// 棋子纹理资源（按 [颜色][棋子类型] 存储当前主题的图片句柄）
+#[derive(Resource)]
+struct PieceTextures {
+    handles: [[Handle<Image>; 6]; 2],
+}
+
+impl PieceTextures {
+    fn get(&self, color: PieceColor, piece_type: PieceType) -> Handle<Image> {
+        self.handles[color as usize][piece_type as usize].clone()
+    }
+}
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+/// 加载棋子纹理资源（路径来自当前主题）
+fn load_piece_textures(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<ThemeConfig>,
+) {
+    let load_color = |color: PieceColor| {
+        PIECE_TYPES.map(|piece_type| asset_server.load(theme.piece_path(color, piece_type)))
+    };
+    commands.insert_resource(PieceTextures {
+        handles: [load_color(PieceColor::White), load_color(PieceColor::Black)],
+    });
+}
+
+/// 初始化棋子（按国际象棋初始位置放置）
+fn setup_pieces(
+    mut commands: Commands,
+    board: Query<&Chessboard>,
+    game: Res<Game>,
+    textures: Res<PieceTextures>,
+) {
+    // 按规则引擎的局面摆放（从自动存档恢复时不是初始位置）
+    let board = board.single();
+    for (piece_type, color, position) in board_layout(&game.board) {
+        spawn_piece(
+            &mut commands,
+            piece_type,
+            color,
+            position,
+            board.cell_size,
+            &textures,
+        );
+    }
+}
+
+/// 初始布局（与核心规则一致：row=0 为第8横排，黑方在上，白方在下）
+fn initial_layout() -> Vec<PlacedPiece> {
+    let back_row = [
+        PieceType::Rook,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Queen,
+        PieceType::King,
+        PieceType::Bishop,
+        PieceType::Knight,
+        PieceType::Rook,
+    ];
+
+    let mut layout = Vec::new();
+    for (col, &piece_type) in back_row.iter().enumerate() {
+        let col = col as u8;
+        layout.push((piece_type, PieceColor::Black, (0, col)));
+        layout.push((PieceType::Pawn, PieceColor::Black, (1, col)));
+        layout.push((PieceType::Pawn, PieceColor::White, (6, col)));
+        layout.push((piece_type, PieceColor::White, (7, col)));
+    }
+    layout
+}
+
+/// 按初始位置生成全部棋子（开局和再来一局时共用）
+fn spawn_initial_pieces(commands: &mut Commands, cell_size: f32, textures: &PieceTextures) {
+    for (piece_type, color, position) in initial_layout() {
+        spawn_piece(commands, piece_type, color, position, cell_size, textures);
+    }
+}
+
+/// 生成单个棋子实体
+fn spawn_piece(
+    commands: &mut Commands,
+    piece_type: PieceType,
+    color: PieceColor,
+    position: (u8, u8),
+    cell_size: f32,
+    textures: &PieceTextures,
+) {
+    // 根据类型和颜色获取当前主题的纹理
+    let texture = textures.get(color, piece_type);
+
+    // 计算棋子位置（居中于格子）
+    let center = square_to_screen(position, cell_size);
+
+    // 生成棋子实体（Sprite + Piece组件）
+    commands.spawn((
+        SpriteBundle {
+            texture,
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(cell_size * 0.8, cell_size * 0.8)), // 棋子比格子小20%
+                ..default()
+            },
+            transform: Transform::from_xyz(center.x, center.y, 1.0), // z=1（在棋盘上方）
+            ..default()
+        },
+        Piece {
+            piece_type,
+            color,
+            position,
+        },
+    ));
+}
+/// 处理拖动开始（鼠标按下时）
+fn start_drag(
+    mut commands: Commands,
+    mouse_btn_input: Res<Input<MouseButton>>,
+    cursor_pos: Res<CursorPosition>, // 需要手动实现的光标位置资源
+    game: Res<Game>,
+    mut pieces: Query<(Entity, &mut Transform, &Piece)>,
+) {
+    if mouse_btn_input.just_pressed(MouseButton::Left) {
+        if let Some(cursor_world_pos) = cursor_pos.0 {
+            // 光标世界坐标（需转换屏幕->世界）
+            // 检测鼠标是否点击了棋子（简化：距离判断）
+            for (entity, mut transform, piece) in &mut pieces {
+                let distance = transform.translation.distance(cursor_world_pos);
+                if distance < 50.0 {
+                    // 假设棋子半径50像素内视为点击
+                    // 标记为正在拖动
+                    commands.entity(entity).insert(Dragging {
+                        start_position: transform.translation,
+                        valid_moves: valid_moves(&game.board, piece.position),
+                    });
+                    // 提升z轴层级（避免被其他棋子遮挡）
+                    transform.translation.z = 2.0;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// 处理拖动中（鼠标移动时）
+fn drag_move(
+    cursor_pos: Res<CursorPosition>,
+    mut dragging_pieces: Query<&mut Transform, With<Dragging>>,
+) {
+    if let Some(cursor_world_pos) = cursor_pos.0 {
+        for mut transform in &mut dragging_pieces {
+            // 棋子跟随鼠标（保持z轴不变）
+            transform.translation.x = cursor_world_pos.x;
+            transform.translation.y = cursor_world_pos.y;
+        }
+    }
+}
+
+/// 处理拖动结束（鼠标释放时）：合法落点交给规则引擎走棋，其余放回原位
+#[allow(clippy::too_many_arguments)]
+fn end_drag(
+    mut commands: Commands,
+    mouse_btn_input: Res<Input<MouseButton>>,
+    board: Query<&Chessboard>,
+    mut game: ResMut<Game>,
+    mut dragging_pieces: Query<(Entity, &mut Transform, &mut Piece, &Dragging)>,
+    other_pieces: Query<(Entity, &Piece), Without<Dragging>>,
+    textures: Res<PieceTextures>,
+    mut toasts: ResMut<Toasts>,
+    mut arrows: ResMut<AnalysisArrows>,
+    mut game_over: EventWriter<GameOverEvent>,
+) {
+    if mouse_btn_input.just_released(MouseButton::Left) {
+        let board = board.single();
+        let cell_size = board.cell_size;
+
+        for (entity, mut transform, mut piece, dragging) in &mut dragging_pieces {
+            // 计算鼠标释放位置对应的棋盘格子（行/列），棋盘外视为非法
+            let target_pos = screen_to_square(transform.translation.truncate(), cell_size);
+
+            // 只有规则引擎给出的落点才提交；提交时仍以 make_move 的结果为准
+            let moved = match target_pos {
+                Some(target_pos) if dragging.valid_moves.contains(&target_pos) => {
+                    let mv = drop_move(&game.board, piece.position, target_pos);
+                    match game.board.make_move(&mv) {
+                        Ok(()) => Some(target_pos),
+                        Err(e) => {
+                            toasts.push(ToastLevel::Warning, format!("{}，棋子已放回原位", e));
+                            None
+                        }
+                    }
+                }
+                // 放回原格不算走棋，也不提示
+                Some(target_pos) if target_pos == piece.position => None,
+                Some(target_pos) => {
+                    toasts.push(
+                        ToastLevel::Warning,
+                        format!(
+                            "{} 不能走到 {}，棋子已放回原位",
+                            square_to_notation(piece.position),
+                            square_to_notation(target_pos)
+                        ),
+                    );
+                    None
+                }
+                None => {
+                    toasts.push(ToastLevel::Warning, "落点在棋盘外，棋子已放回原位");
+                    None
+                }
+            };
+
+            if let Some(target_pos) = moved {
+                // 移动到目标格子（触发动画），旧的引擎箭头失效
+                arrows.clear();
+                piece.position = target_pos;
+                let target = square_to_screen(target_pos, cell_size);
+                start_move_animation(
+                    &mut commands,
+                    entity,
+                    transform.translation,
+                    target.extend(1.0),
+                );
+
+                // 吃子、易位的车、升变：按规则引擎的新布局增删棋子
+                let mut entities = vec![entity];
+                let mut shown = vec![(piece.piece_type, piece.color, piece.position)];
+                for (other, other_piece) in &other_pieces {
+                    entities.push(other);
+                    shown.push((
+                        other_piece.piece_type,
+                        other_piece.color,
+                        other_piece.position,
+                    ));
+                }
+                let (stale, missing) = layout_changes(&shown, &board_layout(&game.board));
+                for index in stale {
+                    commands.entity(entities[index]).despawn();
+                }
+                for (piece_type, color, position) in missing {
+                    spawn_piece(
+                        &mut commands,
+                        piece_type,
+                        color,
+                        position,
+                        cell_size,
+                        &textures,
+                    );
+                }
+                // 与命令行相同的走法说明，如 "白方: exd5 吃掉黑兵"
+                if let Some(description) = game.board.describe_last_move(Language::Chinese) {
+                    toasts.push(ToastLevel::Info, description);
+                }
+
+                match game.board.status() {
+                    GameStatus::Ongoing => {}
+                    GameStatus::Checkmate { winner } => game_over.send(GameOverEvent {
+                        winner: Some(piece_color(winner)),
+                        quality_report: Vec::new(),
+                        score_series: Vec::new(),
+                    }),
+                    GameStatus::Draw { .. } => game_over.send(GameOverEvent {
+                        winner: None,
+                        quality_report: Vec::new(),
+                        score_series: Vec::new(),
+                    }),
+                }
+            } else {
+                // 非法移动，回到起始位置（触发动画）
+                start_move_animation(
+                    &mut commands,
+                    entity,
+                    transform.translation,
+                    dragging.start_position,
+                );
+            }
+
+            // 移除拖动状态，恢复z轴
+            commands.entity(entity).remove::<Dragging>();
+            transform.translation.z = 1.0;
+        }
+    }
+}
+
+/// 辅助函数：开始移动动画
+fn start_move_animation(commands: &mut Commands, entity: Entity, start: Vec3, end: Vec3) {
+    // 使用bevy_tweening创建位置插值动画（0.3秒线性移动）
+    let tween = Tween::new(
+        EaseMethod::Linear,
+        Duration::from_secs_f32(0.3),
+        TransformPositionLens { start, end },
+    );
+    commands.entity(entity).insert(Animator::new(tween));
+}
+/// 动画由 TweeningPlugin 驱动，播放完后移除动画组件
+fn finish_animations(mut commands: Commands, query: Query<(Entity, &Animator<Transform>)>) {
+    for (entity, animator) in &query {
+        if animator.tweenable().progress() >= 1.0 {
+            commands.entity(entity).remove::<Animator<Transform>>();
+        }
+    }
+}
+
+// 拖动时原格子上的高亮
+#[derive(Component)]
+struct SelectionHighlight;
+
+/// 拖动棋子时高亮它的原格子
+fn highlight_selected(
+    mut commands: Commands,
+    selected_piece: Query<&Piece, With<Dragging>>, // 仅高亮正在拖动的棋子原位置
+    board: Query<&Chessboard>,
+    highlights: Query<Entity, With<SelectionHighlight>>,
+) {
+    // 清除之前的高亮
+    for entity in &highlights {
+        commands.entity(entity).despawn();
+    }
+
+    if let Ok(piece) = selected_piece.get_single() {
+        let board = board.single();
+        let cell_size = board.cell_size;
+
+        // 计算高亮位置（原格子上方，半透明绿色）
+        let center = square_to_screen(piece.position, cell_size);
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.2, 0.8, 0.2, 0.3), // 半透明绿
+                    custom_size: Some(Vec2::new(cell_size, cell_size)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(center.x, center.y, 0.5), // z=0.5（在棋盘和棋子之间）
+                ..default()
+            },
+            SelectionHighlight,
+        ));
+    }
+}
+// 引擎箭头：在棋盘上用箭头标出引擎当前的最佳着（和较淡的次佳着）
+// 只在允许辅助的休闲分析中显示，正式对局（allowed=false）时A键无效
+// 箭头的起点格和终点格
+type SquarePair = ((u8, u8), (u8, u8));
+
+#[derive(Resource, Default)]
+struct AnalysisArrows {
+    allowed: bool, // 辅助设置：是否允许显示引擎提示
+    visible: bool, // 当前是否显示（A键切换）
+    best: Option<SquarePair>,
+    second: Option<SquarePair>,
+    dirty: bool, // 需要重画
+}
+
+impl AnalysisArrows {
+    /// 后台分析每加深一层时调用，更新箭头
+    fn set_lines(&mut self, best: Option<SquarePair>, second: Option<SquarePair>) {
+        self.best = best;
+        self.second = second;
+        self.dirty = true;
+    }
+
+    /// 走子后旧的提示失效
+    fn clear(&mut self) {
+        self.set_lines(None, None);
+    }
+
+    /// A键：只有允许辅助时才能打开
+    fn toggle(&mut self) {
+        self.visible = self.allowed && !self.visible;
+        self.dirty = true;
+    }
+
+    /// 关闭辅助时同时隐藏箭头
+    fn set_allowed(&mut self, allowed: bool) {
+        self.allowed = allowed;
+        if !allowed {
+            self.visible = false;
+        }
+        self.dirty = true;
+    }
+
+    /// 当前应画出的箭头及颜色
+    fn arrows(&self) -> Vec<(SquarePair, Color)> {
+        if !self.allowed || !self.visible {
+            return Vec::new();
+        }
+        let mut arrows = Vec::new();
+        if let Some(squares) = self.best {
+            arrows.push((squares, Color::rgba(0.1, 0.6, 0.2, 0.8)));
+        }
+        if let Some(squares) = self.second {
+            arrows.push((squares, Color::rgba(0.1, 0.6, 0.2, 0.35)));
+        }
+        arrows
+    }
+}
+
+// 箭头精灵标记
+#[derive(Component)]
+struct ArrowUi;
+
+/// 箭头杆的位置、长度和旋转角（弧度），从起点格中心指向终点格中心
+fn arrow_geometry(from: (u8, u8), to: (u8, u8), cell_size: f32) -> (Vec2, f32, f32) {
+    let start = square_to_screen(from, cell_size);
+    let end = square_to_screen(to, cell_size);
+    let delta = end - start;
+    ((start + end) / 2.0, delta.length(), delta.y.atan2(delta.x))
+}
+
+/// A键切换引擎箭头
+fn toggle_arrows(
+    keys: Res<Input<KeyCode>>,
+    mut arrows: ResMut<AnalysisArrows>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if keys.just_pressed(KeyCode::A) {
+        arrows.toggle();
+        if !arrows.allowed {
+            toasts.push(ToastLevel::Info, "当前对局不允许显示引擎提示");
+        }
+    }
+}
+
+// 计算引擎箭头时的本地搜索深度
+const ARROW_DEPTH: u8 = 2;
+
+/// 箭头打开且当前局面还没有提示时，用本地搜索找出最佳和次佳着
+fn update_arrow_lines(game: Res<Game>, mut arrows: ResMut<AnalysisArrows>) {
+    if !arrows.visible || arrows.best.is_some() || game.board.status() != GameStatus::Ongoing {
+        return;
+    }
+    let ranked = chess::search::ranked_moves(&game.board, ARROW_DEPTH);
+    let line = |index: usize| {
+        ranked
+            .get(index)
+            .map(|(mv, _)| (from_position(mv.from), from_position(mv.to)))
+    };
+    arrows.set_lines(line(0), line(1));
+}
+
+/// 箭头有变化时重画（z=0.8，在高亮层和棋子之间）
+fn draw_arrows(
+    mut commands: Commands,
+    mut arrows: ResMut<AnalysisArrows>,
+    existing: Query<Entity, With<ArrowUi>>,
+    board: Query<&Chessboard>,
+) {
+    if !arrows.dirty {
+        return;
+    }
+    arrows.dirty = false;
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+
+    let cell_size = board.single().cell_size;
+    for ((from, to), color) in arrows.arrows() {
+        let (center, length, angle) = arrow_geometry(from, to, cell_size);
+        let rotation = Quat::from_rotation_z(angle);
+        // 箭杆
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(length - cell_size * 0.3, cell_size * 0.15)),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    (center - Vec2::from_angle(angle) * cell_size * 0.15).extend(0.8),
+                )
+                .with_rotation(rotation),
+                ..default()
+            },
+            ArrowUi,
+        ));
+        // 箭头（转45度的方块，露出半个作为三角形头部）
+        let tip = square_to_screen(to, cell_size) - Vec2::from_angle(angle) * cell_size * 0.3;
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(cell_size * 0.3)),
+                    ..default()
+                },
+                transform: Transform::from_translation(tip.extend(0.8))
+                    .with_rotation(rotation * Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                ..default()
+            },
+            ArrowUi,
+        ));
+    }
+}
+
+// 提示消息级别，决定提示框的背景颜色
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ToastLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    fn color(self) -> Color {
+        match self {
+            ToastLevel::Info => Color::rgb(0.2, 0.4, 0.8),
+            ToastLevel::Success => Color::rgb(0.2, 0.6, 0.3),
+            ToastLevel::Warning => Color::rgb(0.85, 0.6, 0.1),
+            ToastLevel::Error => Color::rgb(0.8, 0.2, 0.2),
+        }
+    }
+}
+
+const TOAST_LIFETIME: f32 = 3.0; // 每条提示显示的秒数
+const TOAST_FADE: f32 = 0.5; // 最后这段时间内逐渐淡出
+const MAX_TOASTS: usize = 5; // 同时最多显示的条数，超出时挤掉最早的一条
+
+// 一条非模态提示（非法走法原因、自动保存确认、AI回退等）
+struct Toast {
+    message: String,
+    level: ToastLevel,
+    remaining: f32,
+    entity: Option<Entity>, // 已生成的提示框实体
+}
+
+// 提示队列资源：各系统通过 push 发出提示，update_toasts 负责显示和过期
+#[derive(Resource, Default)]
+struct Toasts {
+    queue: VecDeque<Toast>,
+    removed: Vec<Entity>, // 已过期或被挤掉、等待销毁的提示框
+}
+
+impl Toasts {
+    fn push(&mut self, level: ToastLevel, message: impl Into<String>) {
+        if self.queue.len() >= MAX_TOASTS {
+            if let Some(oldest) = self.queue.pop_front() {
+                self.removed.extend(oldest.entity);
+            }
+        }
+        self.queue.push_back(Toast {
+            message: message.into(),
+            level,
+            remaining: TOAST_LIFETIME,
+            entity: None,
+        });
+    }
+
+    /// 经过delta秒，移除过期提示；返回需要销毁的提示框实体
+    fn tick(&mut self, delta: f32) -> Vec<Entity> {
+        for toast in &mut self.queue {
+            toast.remaining -= delta;
+        }
+        while let Some(index) = self.queue.iter().position(|toast| toast.remaining <= 0.0) {
+            let expired = self.queue.remove(index).unwrap();
+            self.removed.extend(expired.entity);
+        }
+        std::mem::take(&mut self.removed)
+    }
+}
+
+/// 提示框的不透明度：最后TOAST_FADE秒内线性淡出
+fn toast_alpha(remaining: f32) -> f32 {
+    (remaining / TOAST_FADE).clamp(0.0, 1.0)
+}
+
+// 提示框UI标记
+#[derive(Component)]
+struct ToastUi;
+
+/// 在右上角堆叠显示提示，处理淡出和过期
+fn update_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: ResMut<Toasts>,
+    asset_server: Res<AssetServer>,
+    mut panels: Query<(&mut Style, &mut BackgroundColor), With<ToastUi>>,
+) {
+    for entity in toasts.tick(time.delta_seconds()) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let font = asset_server.load("fonts/NotoSansSC-Regular.ttf");
+    for (index, toast) in toasts.queue.iter_mut().enumerate() {
+        let top = Val::Px(10.0 + index as f32 * 50.0);
+        let mut background = toast.level.color();
+        background.set_a(0.9 * toast_alpha(toast.remaining));
+
+        match toast.entity.and_then(|entity| panels.get_mut(entity).ok()) {
+            Some((mut style, mut color)) => {
+                style.position.top = top;
+                *color = background.into();
+            }
+            None => {
+                let text_style = TextStyle {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                };
+                let entity = commands
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: UiRect {
+                                    top,
+                                    right: Val::Px(10.0),
+                                    ..default()
+                                },
+                                padding: UiRect::all(Val::Px(8.0)),
+                                ..default()
+                            },
+                            background_color: background.into(),
+                            ..default()
+                        },
+                        ToastUi,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(toast.message.clone(), text_style));
+                    })
+                    .id();
+                toast.entity = Some(entity);
+            }
+        }
+    }
+}
+// 单实例锁：数据目录（当前目录）下同时只允许一个GUI实例写记分和存档
+const LOCK_FILE: &str = "chess-gui.lock";
+
+// 持有期间锁文件存在（内容为本进程PID），释放时删除
+#[derive(Resource)]
+struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// 创建锁文件；已被占用时报错。force 为真且占用锁的进程已不存在（上次崩溃留下的锁）时接管
+    fn acquire(path: &Path, force: bool) -> Result<Self, String> {
+        use std::io::Write;
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+        {
+            Ok(mut file) => {
+                file.write_all(std::process::id().to_string().as_bytes())
+                    .map_err(|e| format!("写入锁文件失败: {}", e))?;
+                Ok(Self {
+                    path: path.to_path_buf(),
+                })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let owner = std::fs::read_to_string(path)
+                    .ok()
+                    .and_then(|text| text.trim().parse::<u32>().ok());
+                match owner {
+                    Some(pid) if process_alive(pid) => {
+                        Err(format!("另一个实例正在运行 (PID {})", pid))
+                    }
+                    _ if force => {
+                        std::fs::remove_file(path).map_err(|e| format!("无法接管锁文件: {}", e))?;
+                        Self::acquire(path, false)
+                    }
+                    _ => Err(format!(
+                        "另一个实例正在运行 (锁文件 {})；若确认它已退出，可用 --force 接管",
+                        path.display()
+                    )),
+                }
+            }
+            Err(e) => Err(format!("无法创建锁文件: {}", e)),
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// 进程是否仍在运行：Linux 查 /proc；其他平台无法判断，视为仍在运行（不擅自接管）
+fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() || !cfg!(target_os = "linux") {
+        return true;
+    }
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// 窗口关闭时释放锁（winit 的事件循环不会返回，main 中的局部变量不会被析构）
+fn release_lock_on_exit(mut commands: Commands, mut exits: EventReader<AppExit>) {
+    if exits.iter().next().is_some() {
+        commands.remove_resource::<InstanceLock>();
+    }
+}
+
+/// 先写临时文件再改名替换：写到一半崩溃时原文件保持完整，不会被截断
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&temp, path)
+}
+
+/// 在文件末尾追加：读出原内容，连同新内容经 write_atomic 整体写回，崩溃时不会留下半条记录
+fn append_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    text.push_str(content);
+    write_atomic(path, &text)
+}
+
+// 自动存档：走棋后（防抖）和每隔一段时间写入，两个文件轮流使用，
+// 写坏最新的一个时还能退回上一个；正常退出时写入"clean"标记，启动时据此判断是否异常退出
+// （存档内容是核心棋盘的JSON，需要 chess 库开启 serde 特性）
+const AUTOSAVE_FILES: [&str; 2] = ["autosave-0.sav", "autosave-1.sav"];
+const AUTOSAVE_MAGIC: &str = "chess-autosave-v1";
+const AUTOSAVE_INTERVAL: f32 = 30.0; // 定期存档间隔（秒）
+const AUTOSAVE_DEBOUNCE: f32 = 1.0; // 走棋后等待这么久没有新走法再存档（秒）
+
+#[derive(Resource)]
+struct Autosaver {
+    dir: PathBuf,
+    seq: u64,     // 最近一次存档的序号，下一次写入另一个文件
+    elapsed: f32, // 距上次存档的秒数
+    quiet: f32,   // 距最近一次局面变化的秒数
+    dirty: bool,  // 上次存档后局面是否变化
+}
+
+// 启动时从自动存档恢复的对局；crashed 表示上次没有正常退出
+struct Recovered {
+    board: chess::Chessboard,
+    crashed: bool,
+}
+
+/// FNV-1a 校验和，用于发现写了一半的存档
+fn checksum(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// 存档格式：首行为 "魔数 序号 clean/running 校验和"，其后为棋盘JSON
+fn encode_autosave(seq: u64, clean: bool, board: &chess::Chessboard) -> String {
+    let json = board.to_json();
+    let state = if clean { "clean" } else { "running" };
+    format!(
+        "{} {} {} {:016x}\n{}",
+        AUTOSAVE_MAGIC,
+        seq,
+        state,
+        checksum(&json),
+        json
+    )
+}
+
+/// 解析并校验存档，返回 (序号, 是否正常退出, 棋盘)；截断、校验和不符或局面无效时返回None
+fn decode_autosave(text: &str) -> Option<(u64, bool, chess::Chessboard)> {
+    let (header, json) = text.split_once('\n')?;
+    let fields: Vec<&str> = header.split_whitespace().collect();
+    if fields.len() != 4 || fields[0] != AUTOSAVE_MAGIC {
+        return None;
+    }
+    let seq = fields[1].parse().ok()?;
+    let clean = match fields[2] {
+        "clean" => true,
+        "running" => false,
+        _ => return None,
+    };
+    if u64::from_str_radix(fields[3], 16).ok()? != checksum(json) {
+        return None;
+    }
+    let board = chess::Chessboard::from_json(json).ok()?;
+    Some((seq, clean, board))
+}
+
+impl Autosaver {
+    /// 读取目录中的存档，取序号最大的有效存档
+    fn open(dir: &Path) -> (Self, Option<Recovered>) {
+        let newest = AUTOSAVE_FILES
+            .iter()
+            .filter_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+            .filter_map(|text| decode_autosave(&text))
+            .max_by_key(|(seq, _, _)| *seq);
+        let saver = Self {
+            dir: dir.to_path_buf(),
+            seq: newest.as_ref().map_or(0, |(seq, _, _)| *seq),
+            elapsed: 0.0,
+            quiet: 0.0,
+            dirty: false,
+        };
+        let recovered = newest.map(|(_, clean, board)| Recovered {
+            board,
+            crashed: !clean,
+        });
+        (saver, recovered)
+    }
+
+    /// 局面发生变化（走棋、重新开局）
+    fn note_change(&mut self) {
+        self.dirty = true;
+        self.quiet = 0.0;
+    }
+
+    /// 推进计时，返回现在是否该存档
+    fn tick(&mut self, delta: f32) -> bool {
+        self.elapsed += delta;
+        self.quiet += delta;
+        (self.dirty && self.quiet >= AUTOSAVE_DEBOUNCE) || self.elapsed >= AUTOSAVE_INTERVAL
+    }
+
+    /// 写入较旧的那个文件；失败时等到下一个间隔再试
+    fn save(&mut self, board: &chess::Chessboard, clean: bool) -> Result<(), String> {
+        self.elapsed = 0.0;
+        self.dirty = false;
+        let seq = self.seq + 1;
+        let path = self.dir.join(AUTOSAVE_FILES[(seq % 2) as usize]);
+        write_atomic(&path, &encode_autosave(seq, clean, board))
+            .map_err(|e| format!("自动存档失败: {}", e))?;
+        self.seq = seq;
+        Ok(())
+    }
+}
+
+/// 定期和走棋后自动存档
+fn autosave(
+    time: Res<Time>,
+    game: Res<Game>,
+    mut saver: ResMut<Autosaver>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if game.is_changed() {
+        saver.note_change();
+    }
+    if saver.tick(time.delta_seconds()) {
+        if let Err(e) = saver.save(&game.board, false) {
+            toasts.push(ToastLevel::Error, e);
+        }
+    }
+}
+
+/// 正常退出时写入带 clean 标记的存档
+fn autosave_on_exit(
+    mut exits: EventReader<AppExit>,
+    game: Res<Game>,
+    mut saver: ResMut<Autosaver>,
+) {
+    if exits.iter().next().is_some() {
+        if let Err(e) = saver.save(&game.board, true) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+// 异常退出后恢复对局的提示，preview 为恢复的局面（文字棋盘）
+#[derive(Resource)]
+struct RecoveryNotice {
+    preview: String,
+}
+
+#[derive(Component)]
+struct RecoveryUi;
+
+#[derive(Component)]
+struct DismissRecoveryButton;
+
+/// 启动界面：异常退出后显示提示和恢复的局面
+fn show_recovery_notice(
+    mut commands: Commands,
+    notice: Option<Res<RecoveryNotice>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(notice) = notice else {
+        return;
+    };
+    let font = asset_server.load("fonts/NotoSansSC-Regular.ttf");
+    let text_style = TextStyle {
+        font,
+        font_size: 28.0,
+        color: Color::WHITE,
+    };
+    let preview_style = TextStyle {
+        font_size: 18.0,
+        ..text_style.clone()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::all(Val::Auto),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                ..default()
+            },
+            RecoveryUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "检测到异常退出，已恢复对局",
+                text_style.clone(),
+            ));
+            parent.spawn(TextBundle::from_section(
+                notice.preview.clone(),
+                preview_style,
+            ));
+            parent
+                .spawn((ButtonBundle::default(), DismissRecoveryButton))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section("继续对局", text_style.clone()));
+                });
+        });
+}
+
+fn handle_recovery_buttons(
+    mut commands: Commands,
+    dismiss: Query<&Interaction, (Changed<Interaction>, With<DismissRecoveryButton>)>,
+    ui: Query<Entity, With<RecoveryUi>>,
+) {
+    if dismiss
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        for entity in &ui {
+            commands.entity(entity).despawn_recursive();
+        }
+        commands.remove_resource::<RecoveryNotice>();
+    }
+}
+
+// 对局系列记分（人类对AI），跨会话保存在数据文件中
+#[derive(Resource)]
+struct Series {
+    human_color: PieceColor, // 本局人类执子颜色
+    human_score: f32,
+    ai_score: f32,
+    round: u32, // 当前局序号（写入PGN的Round标签）
+}
+
+const SERIES_FILE: &str = "series.dat";
+const SERIES_PGN: &str = "series.pgn";
+
+impl Series {
+    fn new() -> Self {
+        Self {
+            human_color: PieceColor::White,
+            human_score: 0.0,
+            ai_score: 0.0,
+            round: 1,
+        }
+    }
+
+    /// 读取系列数据（格式：颜色 人类得分 AI得分 局序号），读取失败时开始新系列
+    fn load() -> Self {
+        let content = std::fs::read_to_string(SERIES_FILE).unwrap_or_default();
+        let fields: Vec<&str> = content.split_whitespace().collect();
+        if fields.len() != 4 {
+            return Self::new();
+        }
+        let human_color = if fields[0] == "black" {
+            PieceColor::Black
+        } else {
+            PieceColor::White
+        };
+        match (fields[1].parse(), fields[2].parse(), fields[3].parse()) {
+            (Ok(human_score), Ok(ai_score), Ok(round)) => Self {
+                human_color,
+                human_score,
+                ai_score,
+                round,
+            },
+            _ => Self::new(),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let color = match self.human_color {
+            PieceColor::White => "white",
+            PieceColor::Black => "black",
+        };
+        let content = format!(
+            "{} {} {} {}",
+            color, self.human_score, self.ai_score, self.round
+        );
+        write_atomic(Path::new(SERIES_FILE), &content)
+            .map_err(|e| format!("保存系列记分失败: {}", e))
+    }
+
+    /// 记分牌文字，例如 "你 1.5 – 0.5 AI"
+    fn label(&self) -> String {
+        format!("你 {} – {} AI", self.human_score, self.ai_score)
+    }
+
+    /// 记录一局结果（winner为None表示和棋），并追加到系列PGN
+    fn record_result(&mut self, winner: Option<PieceColor>) -> Result<(), String> {
+        let result = match winner {
+            Some(PieceColor::White) => "1-0",
+            Some(PieceColor::Black) => "0-1",
+            None => "1/2-1/2",
+        };
+        match winner {
+            Some(color) if color == self.human_color => self.human_score += 1.0,
+            Some(_) => self.ai_score += 1.0,
+            None => {
+                self.human_score += 0.5;
+                self.ai_score += 0.5;
+            }
+        }
+
+        let (white, black) = match self.human_color {
+            PieceColor::White => ("Human", "AI"),
+            PieceColor::Black => ("AI", "Human"),
+        };
+        let pgn = format!(
+            "[Event \"Rematch series\"]\n[Round \"{}\"]\n[White \"{}\"]\n[Black \"{}\"]\n[Result \"{}\"]\n\n{}\n\n",
+            self.round, white, black, result, result
+        );
+        append_atomic(Path::new(SERIES_PGN), &pgn).map_err(|e| format!("保存系列PGN失败: {}", e))
+    }
+
+    /// 交换颜色，进入下一局
+    fn next_round(&mut self) -> Result<(), String> {
+        self.human_color = match self.human_color {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        };
+        self.round += 1;
+        self.save()
+    }
+
+    /// 清零记分，重新开始系列
+    fn reset(&mut self) -> Result<(), String> {
+        *self = Self::new();
+        self.save()
+    }
+}
+
+// 对局结束事件（winner为None表示和棋）
+struct GameOverEvent {
+    winner: Option<PieceColor>,
+    // 走法质量报告（每方一行，来自核心的 move_quality::report_lines），未评估时为空
+    quality_report: Vec<String>,
+    // 评分走势（来自核心的 move_quality::score_series，已截断将杀评分），未评估时为空
+    score_series: Vec<i32>,
+}
+
+// 评分图：与核心 move_quality 的截断值一致
+const CHART_EVAL_CAP: i32 = 1_000;
+const CHART_WIDTH: f32 = 400.0;
+const CHART_HEIGHT: f32 = 120.0;
+
+// 评分图上的一个点，ply为走完的步数（0为开局局面）
+#[derive(Component)]
+struct ScorePoint {
+    ply: usize,
+    eval: i32,
+}
+
+// 鼠标悬停时显示步数和评分的文字
+#[derive(Component)]
+struct ScoreChartLabel;
+
+// 点击评分图上的点：历史面板和预览棋盘跳到该步
+struct JumpToPly(usize);
+
+/// 评分点在图中的位置（左上角为原点）：横向按步数均分，纵向中线为均势，白优在上
+fn chart_point_position(ply: usize, eval: i32, points: usize) -> Vec2 {
+    let x = if points > 1 {
+        ply as f32 * CHART_WIDTH / (points - 1) as f32
+    } else {
+        0.0
+    };
+    let eval = eval.clamp(-CHART_EVAL_CAP, CHART_EVAL_CAP) as f32;
+    let y = CHART_HEIGHT / 2.0 - eval / CHART_EVAL_CAP as f32 * CHART_HEIGHT / 2.0;
+    Vec2::new(x, y)
+}
+
+/// 评分点的说明文字，如 "第12步 (白方第6手后) +0.35"
+fn score_point_label(ply: usize, eval: i32) -> String {
+    if ply == 0 {
+        return format!("开局 {:+.2}", eval as f32 / 100.0);
+    }
+    let side = if ply % 2 == 1 { "白方" } else { "黑方" };
+    format!(
+        "第{}步 ({}第{}手后) {:+.2}",
+        ply,
+        side,
+        ply.div_ceil(2),
+        eval as f32 / 100.0
+    )
+}
+
+/// 生成评分图面板：每个局面一个小圆点，中间一条均势线
+fn spawn_score_chart(parent: &mut ChildBuilder, series: &[i32], text_style: &TextStyle) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(CHART_WIDTH), Val::Px(CHART_HEIGHT)),
+                margin: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.1).into(),
+            ..default()
+        })
+        .with_children(|chart| {
+            chart.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(CHART_HEIGHT / 2.0),
+                        ..default()
+                    },
+                    size: Size::new(Val::Px(CHART_WIDTH), Val::Px(1.0)),
+                    ..default()
+                },
+                background_color: Color::GRAY.into(),
+                ..default()
+            });
+            for (ply, &eval) in series.iter().enumerate() {
+                let point = chart_point_position(ply, eval, series.len());
+                chart.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Px(point.x - 3.0),
+                                top: Val::Px(point.y - 3.0),
+                                ..default()
+                            },
+                            size: Size::new(Val::Px(6.0), Val::Px(6.0)),
+                            ..default()
+                        },
+                        background_color: Color::WHITE.into(),
+                        ..default()
+                    },
+                    ScorePoint { ply, eval },
+                ));
+            }
+        });
+    parent.spawn((
+        TextBundle::from_section("", text_style.clone()),
+        ScoreChartLabel,
+    ));
+}
+
+/// 点击评分图后提示该步走的着法
+fn preview_jump(game: Res<Game>, mut jumps: EventReader<JumpToPly>, mut toasts: ResMut<Toasts>) {
+    for JumpToPly(ply) in jumps.iter() {
+        let message = match ply
+            .checked_sub(1)
+            .and_then(|index| game.board.move_history().get(index))
+        {
+            Some(san) => format!("第{}步: {}", ply, san),
+            None => "开局局面".to_string(),
+        };
+        toasts.push(ToastLevel::Info, message);
+    }
+}
+
+/// 悬停显示步数和评分，点击跳到该步
+fn score_chart_interaction(
+    points: Query<(&Interaction, &ScorePoint), Changed<Interaction>>,
+    mut label: Query<&mut Text, With<ScoreChartLabel>>,
+    mut jumps: EventWriter<JumpToPly>,
+) {
+    for (interaction, point) in &points {
+        match interaction {
+            Interaction::Hovered => {
+                for mut text in &mut label {
+                    text.sections[0].value = score_point_label(point.ply, point.eval);
+                }
+            }
+            Interaction::Clicked => jumps.send(JumpToPly(point.ply)),
+            Interaction::None => {}
+        }
+    }
+}
+
+// 结束界面上的UI元素
+#[derive(Component)]
+struct GameOverUi;
+
+#[derive(Component)]
+struct RematchButton;
+
+#[derive(Component)]
+struct ResetSeriesButton;
+
+/// 对局结束：记录结果并显示记分牌和再来一局按钮
+fn show_game_over(
+    mut commands: Commands,
+    mut events: EventReader<GameOverEvent>,
+    mut series: ResMut<Series>,
+    mut toasts: ResMut<Toasts>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.iter() {
+        if let Err(e) = series.record_result(event.winner) {
+            toasts.push(ToastLevel::Error, e);
+        }
+        match series.save() {
+            Ok(()) => toasts.push(ToastLevel::Success, "系列记分已自动保存"),
+            Err(e) => toasts.push(ToastLevel::Error, e),
+        }
+
+        let font = asset_server.load("fonts/NotoSansSC-Regular.ttf");
+        let text_style = TextStyle {
+            font,
+            font_size: 32.0,
+            color: Color::WHITE,
+        };
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Auto),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                },
+                GameOverUi,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(series.label(), text_style.clone()));
+                let report_style = TextStyle {
+                    font_size: 20.0,
+                    ..text_style.clone()
+                };
+                for line in &event.quality_report {
+                    parent.spawn(TextBundle::from_section(line.clone(), report_style.clone()));
+                }
+                if !event.score_series.is_empty() {
+                    spawn_score_chart(parent, &event.score_series, &report_style);
+                }
+                parent
+                    .spawn((ButtonBundle::default(), RematchButton))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            "交换颜色再来一局",
+                            text_style.clone(),
+                        ));
+                    });
+                parent
+                    .spawn((ButtonBundle::default(), ResetSeriesButton))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section("重置系列", text_style.clone()));
+                    });
+            });
+    }
+}
+
+/// 处理结束界面按钮：交换颜色再来一局 / 重置系列
+#[allow(clippy::too_many_arguments)]
+fn handle_game_over_buttons(
+    mut commands: Commands,
+    rematch: Query<&Interaction, (Changed<Interaction>, With<RematchButton>)>,
+    reset: Query<&Interaction, (Changed<Interaction>, With<ResetSeriesButton>)>,
+    ui: Query<Entity, With<GameOverUi>>,
+    pieces: Query<Entity, With<Piece>>,
+    board: Query<&Chessboard>,
+    textures: Res<PieceTextures>,
+    mut game: ResMut<Game>,
+    mut series: ResMut<Series>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if reset
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        match series.reset() {
+            Ok(()) => toasts.push(ToastLevel::Info, "系列记分已重置"),
+            Err(e) => toasts.push(ToastLevel::Error, e),
+        }
+    }
+    if !rematch
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        return;
+    }
+
+    if let Err(e) = series.next_round() {
+        toasts.push(ToastLevel::Error, e);
+    }
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+    for entity in &pieces {
+        commands.entity(entity).despawn();
+    }
+    // 重新开局并摆放棋子；人类执黑时由AI先走（AI对手配置保持不变）
+    game.board = chess::Chessboard::new();
+    let board = board.single();
+    spawn_initial_pieces(&mut commands, board.cell_size, &textures);
+}
+
+fn main() {
+    // 同一数据目录只运行一个实例；--force 可接管崩溃后留下的锁
+    let force = std::env::args().any(|arg| arg == "--force");
+    let lock = match InstanceLock::acquire(Path::new(LOCK_FILE), force) {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    // 从自动存档恢复未结束的对局；上次异常退出时在启动界面提示
+    let (autosaver, recovered) = Autosaver::open(Path::new("."));
+    let mut game = Game::default();
+    let mut notice = None;
+    if let Some(recovered) = recovered {
+        if recovered.board.status() == GameStatus::Ongoing {
+            if recovered.crashed {
+                let preview = recovered.board.render(&RenderOptions::ascii());
+                notice = Some(RecoveryNotice { preview });
+            }
+            game.board = recovered.board;
+        }
+    }
+
+    // 引擎箭头只在 --assist 时允许（与命令行版的辅助模式一致）
+    let mut arrows = AnalysisArrows::default();
+    arrows.set_allowed(std::env::args().any(|arg| arg == "--assist"));
+    let theme = ThemeConfig::load("theme.cfg"); // 主题配置（缺省为经典主题）
+
+    let mut app = App::new();
+    if let Some(notice) = notice {
+        app.insert_resource(notice);
+    }
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        window: WindowDescriptor {
+            title: format!("国际象棋 ({})", theme.name),
+            width: 800.0,
+            height: 800.0,
+            ..default()
+        },
+        ..default()
+    }))
+    .add_plugin(TweeningPlugin) // 动画插件
+    .init_resource::<CursorPosition>() // 光标的世界坐标
+    .insert_resource(theme)
+    .insert_resource(lock) // 单实例锁，退出时释放
+    .insert_resource(Series::load()) // 对局系列记分（跨会话保留）
+    .insert_resource(game) // 权威棋局（核心规则引擎），可能来自自动存档
+    .insert_resource(autosaver) // 自动存档
+    .init_resource::<Toasts>() // 非模态提示队列
+    .insert_resource(arrows) // 引擎箭头
+    .add_event::<GameOverEvent>()
+    .add_event::<JumpToPly>() // 评分图点击，由历史面板/预览棋盘处理
+    // 初始化系统
+    .add_startup_system(setup_board)
+    .add_startup_system(load_piece_textures)
+    .add_startup_system(setup_pieces.after(load_piece_textures))
+    .add_startup_system(show_recovery_notice)
+    // 交互系统
+    .add_system(update_cursor_position) // 屏幕坐标转世界坐标
+    .add_system(start_drag)
+    .add_system(drag_move)
+    .add_system(end_drag)
+    // 动画系统
+    .add_system(finish_animations)
+    .add_system(highlight_selected)
+    // 对局结束与再来一局
+    .add_system(show_game_over)
+    .add_system(handle_game_over_buttons)
+    .add_system(score_chart_interaction)
+    .add_system(preview_jump)
+    // 提示消息
+    .add_system(update_toasts)
+    // 引擎箭头
+    .add_system(toggle_arrows)
+    .add_system(update_arrow_lines.before(draw_arrows))
+    .add_system(draw_arrows)
+    // 自动存档（正常退出时先存档再释放锁）
+    .add_system(autosave)
+    .add_system(autosave_on_exit.before(release_lock_on_exit))
+    .add_system(handle_recovery_buttons)
+    .add_system(release_lock_on_exit)
+    .run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 把布局转成FEN的棋子布局字段
+    fn layout_placement(layout: &[PlacedPiece]) -> String {
+        let mut squares = [[None; 8]; 8];
+        for &(piece_type, color, (row, col)) in layout {
+            let symbol = match piece_type {
+                PieceType::King => 'k',
+                PieceType::Queen => 'q',
+                PieceType::Rook => 'r',
+                PieceType::Bishop => 'b',
+                PieceType::Knight => 'n',
+                PieceType::Pawn => 'p',
+            };
+            squares[row as usize][col as usize] = Some(match color {
+                PieceColor::White => symbol.to_ascii_uppercase(),
+                PieceColor::Black => symbol,
+            });
+        }
+
+        let ranks: Vec<String> = squares
+            .iter()
+            .map(|rank| {
+                let mut text = String::new();
+                let mut empty = 0;
+                for square in rank {
+                    match square {
+                        Some(symbol) => {
+                            if empty > 0 {
+                                text.push_str(&empty.to_string());
+                                empty = 0;
+                            }
+                            text.push(*symbol);
+                        }
+                        None => empty += 1,
+                    }
+                }
+                if empty > 0 {
+                    text.push_str(&empty.to_string());
+                }
+                text
+            })
+            .collect();
+        ranks.join("/")
+    }
+
+    #[test]
+    fn initial_layout_matches_start_fen() {
+        assert_eq!(
+            layout_placement(&initial_layout()),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"
+        );
+    }
+
+    #[test]
+    fn engine_layout_matches_initial_layout() {
+        assert_eq!(
+            layout_placement(&board_layout(&chess::Chessboard::new())),
+            layout_placement(&initial_layout())
+        );
+    }
+
+    #[test]
+    fn valid_moves_come_from_the_rules_engine() {
+        let board = chess::Chessboard::new();
+        assert_eq!(valid_moves(&board, (6, 4)), vec![(4, 4), (5, 4)]);
+        // 不是走棋方的棋子不能拖动到任何格子
+        assert!(valid_moves(&board, (1, 4)).is_empty());
+
+        // e2的马被e8的车牵制
+        let pinned = chess::Chessboard::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        assert!(valid_moves(&pinned, (6, 4)).is_empty());
+
+        let castling = chess::Chessboard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let king_targets = valid_moves(&castling, (7, 4));
+        assert!(king_targets.contains(&(7, 6)) && king_targets.contains(&(7, 2)));
+    }
+
+    #[test]
+    fn layout_follows_castling_and_promotion() {
+        let mut board = chess::Chessboard::from_fen("1n2k3/P7/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let before = board_layout(&board);
+
+        // 短易位：拖动的王已在g1，h1的车要换成f1的车
+        board.make_move(&drop_move(&board, (7, 4), (7, 6))).unwrap();
+        let shown: Vec<_> = before
+            .iter()
+            .map(|&entry| match entry {
+                (PieceType::King, PieceColor::White, _) => {
+                    (PieceType::King, PieceColor::White, (7, 6))
+                }
+                entry => entry,
+            })
+            .collect();
+        let (stale, missing) = layout_changes(&shown, &board_layout(&board));
+        let stale: Vec<_> = stale.iter().map(|&i| shown[i]).collect();
+        assert_eq!(stale, vec![(PieceType::Rook, PieceColor::White, (7, 7))]);
+        assert_eq!(missing, vec![(PieceType::Rook, PieceColor::White, (7, 5))]);
+
+        // 兵吃b8的马并自动升变为后
+        board
+            .make_move(&chess::Move::from_notation("e8 e7").unwrap())
+            .unwrap();
+        let shown = board_layout(&board);
+        let mv = drop_move(&board, (1, 0), (0, 1));
+        assert!(matches!(
+            mv.promotion,
+            Some(chess::Piece::Queen(chess::Color::White))
+        ));
+        board.make_move(&mv).unwrap();
+        let shown: Vec<_> = shown
+            .into_iter()
+            .map(|entry| {
+                if entry.2 == (1, 0) {
+                    (entry.0, entry.1, (0, 1))
+                } else {
+                    entry
+                }
+            })
+            .collect();
+        let (stale, missing) = layout_changes(&shown, &board_layout(&board));
+        let stale: Vec<_> = stale.iter().map(|&i| shown[i]).collect();
+        assert_eq!(stale.len(), 2);
+        assert!(stale.contains(&(PieceType::Pawn, PieceColor::White, (0, 1))));
+        assert!(stale.contains(&(PieceType::Knight, PieceColor::Black, (0, 1))));
+        assert_eq!(missing, vec![(PieceType::Queen, PieceColor::White, (0, 1))]);
+    }
+
+    #[test]
+    fn a1_is_bottom_left_in_white_orientation() {
+        let cell_size = 100.0;
+        let a1 = (7, 0);
+        assert_eq!(square_to_notation(a1), "a1");
+        assert_eq!(square_to_screen(a1, cell_size), Vec2::new(-350.0, -350.0));
+        assert_eq!(square_to_screen((0, 7), cell_size), Vec2::new(350.0, 350.0));
+        assert_eq!(
+            screen_to_square(Vec2::new(-390.0, -390.0), cell_size),
+            Some(a1)
+        );
+        assert_eq!(screen_to_square(Vec2::new(0.0, 401.0), cell_size), None);
+    }
+
+    #[test]
+    fn arrows_only_show_when_assist_allows() {
+        let mut arrows = AnalysisArrows::default();
+        arrows.set_lines(Some(((6, 4), (4, 4))), Some(((7, 6), (5, 5))));
+        arrows.toggle();
+        assert!(arrows.arrows().is_empty());
+
+        arrows.set_allowed(true);
+        arrows.toggle();
+        assert_eq!(arrows.arrows().len(), 2);
+        arrows.set_allowed(false);
+        assert!(arrows.arrows().is_empty());
+
+        // e2-e4：箭杆竖直向上，长两格
+        let (center, length, angle) = arrow_geometry((6, 4), (4, 4), 100.0);
+        assert_eq!(center, Vec2::new(50.0, -150.0));
+        assert_eq!(length, 200.0);
+        assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn score_chart_points_and_labels() {
+        assert_eq!(
+            chart_point_position(0, 0, 5),
+            Vec2::new(0.0, CHART_HEIGHT / 2.0)
+        );
+        assert_eq!(
+            chart_point_position(4, 1_000, 5),
+            Vec2::new(CHART_WIDTH, 0.0)
+        );
+        // 将杀评分截断在图的边上
+        assert_eq!(chart_point_position(2, -10_000, 5).y, CHART_HEIGHT);
+
+        assert_eq!(score_point_label(0, 20), "开局 +0.20");
+        assert_eq!(score_point_label(3, -150), "第3步 (白方第2手后) -1.50");
+        assert_eq!(score_point_label(4, 0), "第4步 (黑方第2手后) +0.00");
+    }
+
+    #[test]
+    fn second_instance_is_refused_and_saves_stay_whole() {
+        let dir = std::env::temp_dir().join(format!("chess_gui_lock_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let lock_path = dir.join(LOCK_FILE);
+        let _ = std::fs::remove_file(&lock_path);
+
+        // 两个句柄模拟两个实例：第二个失败，--force 也不能接管仍在运行的实例
+        let first = InstanceLock::acquire(&lock_path, false).unwrap();
+        match InstanceLock::acquire(&lock_path, false) {
+            Err(e) => assert!(e.contains("另一个实例正在运行"), "{}", e),
+            Ok(_) => panic!("第二个实例不应拿到锁"),
+        }
+        assert!(InstanceLock::acquire(&lock_path, true).is_err());
+        drop(first);
+        assert!(!lock_path.exists());
+
+        // 崩溃留下的锁（进程已不存在）只有 --force 才接管
+        if cfg!(target_os = "linux") {
+            std::fs::write(&lock_path, u32::MAX.to_string()).unwrap();
+            assert!(InstanceLock::acquire(&lock_path, false).is_err());
+            let taken = InstanceLock::acquire(&lock_path, true).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(&lock_path).unwrap(),
+                std::process::id().to_string()
+            );
+            drop(taken);
+        }
+
+        // 写到一半崩溃只会留下临时文件，存档本身保持完整
+        let save = dir.join(SERIES_FILE);
+        write_atomic(&save, "white 1 0 2").unwrap();
+        std::fs::write(dir.join(format!("{}.tmp", SERIES_FILE)), "whi").unwrap();
+        assert_eq!(std::fs::read_to_string(&save).unwrap(), "white 1 0 2");
+        write_atomic(&save, "black 1.5 0.5 3").unwrap();
+        assert_eq!(std::fs::read_to_string(&save).unwrap(), "black 1.5 0.5 3");
+
+        // 系列PGN同样经临时文件整体写回，追加不会丢掉之前的对局
+        let pgn = dir.join(SERIES_PGN);
+        append_atomic(&pgn, "[Round \"1\"]\n\n1-0\n\n").unwrap();
+        append_atomic(&pgn, "[Round \"2\"]\n\n0-1\n\n").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&pgn).unwrap(),
+            "[Round \"1\"]\n\n1-0\n\n[Round \"2\"]\n\n0-1\n\n"
+        );
+        assert!(!dir.join(format!("{}.tmp", SERIES_PGN)).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recovery_falls_back_when_newest_autosave_is_truncated() {
+        let dir = std::env::temp_dir().join(format!("chess_gui_autosave_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in AUTOSAVE_FILES {
+            let _ = std::fs::remove_file(dir.join(name));
+        }
+
+        let (mut saver, recovered) = Autosaver::open(&dir);
+        assert!(recovered.is_none());
+        let mut board = chess::Chessboard::new();
+        board
+            .make_move(&chess::Move::from_notation("e2 e4").unwrap())
+            .unwrap();
+        saver.save(&board, false).unwrap();
+        let older = board.to_fen();
+        board
+            .make_move(&chess::Move::from_notation("e7 e5").unwrap())
+            .unwrap();
+        saver.save(&board, false).unwrap();
+
+        // 两个存档都完好时取最新的，且判定为异常退出
+        let (saver, recovered) = Autosaver::open(&dir);
+        let recovered = recovered.unwrap();
+        assert_eq!(recovered.board.to_fen(), board.to_fen());
+        assert!(recovered.crashed);
+        assert_eq!(saver.seq, 2);
+
+        // 最新的存档写到一半被截断：退回上一个
+        let newest = dir.join(AUTOSAVE_FILES[0]);
+        let text = std::fs::read_to_string(&newest).unwrap();
+        std::fs::write(&newest, &text[..text.len() / 2]).unwrap();
+        let (mut saver, recovered) = Autosaver::open(&dir);
+        assert_eq!(recovered.unwrap().board.to_fen(), older);
+
+        // 下一次存档覆盖坏掉的那个文件；正常退出的存档不算异常退出
+        saver.save(&board, true).unwrap();
+        let (_, recovered) = Autosaver::open(&dir);
+        assert!(!recovered.unwrap().crashed);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn autosave_is_debounced_and_periodic() {
+        let (mut saver, _) = Autosaver::open(Path::new("/nonexistent"));
+        assert!(!saver.tick(AUTOSAVE_DEBOUNCE));
+        // 连续走棋时等停下来再存
+        saver.note_change();
+        assert!(!saver.tick(AUTOSAVE_DEBOUNCE / 2.0));
+        saver.note_change();
+        assert!(!saver.tick(AUTOSAVE_DEBOUNCE / 2.0));
+        assert!(saver.tick(AUTOSAVE_DEBOUNCE / 2.0));
+        // 没有走棋也按间隔定期存档
+        saver.dirty = false;
+        saver.elapsed = 0.0;
+        assert!(!saver.tick(AUTOSAVE_INTERVAL - 1.0));
+        assert!(saver.tick(1.0));
+    }
+
+    #[test]
+    fn toasts_expire_and_queue_is_bounded() {
+        let mut toasts = Toasts::default();
+        for i in 0..20 {
+            toasts.push(ToastLevel::Info, format!("提示{}", i));
+            toasts.queue.back_mut().unwrap().entity = Some(Entity::from_raw(i));
+        }
+        assert_eq!(toasts.queue.len(), MAX_TOASTS);
+        assert_eq!(toasts.queue.front().unwrap().message, "提示15");
+
+        // 被挤掉的提示框在下一次tick时销毁
+        assert_eq!(toasts.tick(0.0).len(), 20 - MAX_TOASTS);
+        assert_eq!(toast_alpha(TOAST_LIFETIME - 1.0), 1.0);
+        assert!(toast_alpha(TOAST_FADE / 2.0) < 1.0);
+
+        assert!(toasts.tick(TOAST_LIFETIME - 0.1).is_empty());
+        assert_eq!(toasts.tick(0.2).len(), MAX_TOASTS);
+        assert!(toasts.queue.is_empty());
+    }
+}